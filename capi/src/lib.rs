@@ -0,0 +1,5 @@
+//! The shared and static libraries behind `include/leb128.h`. The exports
+//! live in `lebase::capi`; this crate only links them, so that the main
+//! library stays an rlib and builds without std.
+
+pub use lebase::capi::*;
@@ -0,0 +1,33 @@
+/* C interface to lebase. `cargo build -p lebase-capi` builds it into
+ * liblebase_capi.so and liblebase_capi.a. */
+
+#ifndef LEB128_H
+#define LEB128_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Return the bytes written, or 0 if `cap` is too small. */
+size_t leb128_encode_u32(uint32_t value, uint8_t *out, size_t cap);
+size_t leb128_encode_u64(uint64_t value, uint8_t *out, size_t cap);
+size_t leb128_encode_i32(int32_t value, uint8_t *out, size_t cap);
+size_t leb128_encode_i64(int64_t value, uint8_t *out, size_t cap);
+
+/* Return the bytes consumed, or a negated error code on failure. */
+ptrdiff_t leb128_decode_u32(const uint8_t *buf, size_t len, uint32_t *out_value);
+ptrdiff_t leb128_decode_u64(const uint8_t *buf, size_t len, uint64_t *out_value);
+ptrdiff_t leb128_decode_i32(const uint8_t *buf, size_t len, int32_t *out_value);
+ptrdiff_t leb128_decode_i64(const uint8_t *buf, size_t len, int64_t *out_value);
+
+/* Describes a decoder result. The string is static. */
+const char *leb128_strerror(int code);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
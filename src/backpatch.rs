@@ -0,0 +1,130 @@
+//! Reserving space for a value that is only known later, such as a section
+//! size written before the section body.
+//!
+//! ```
+//! use lebase::backpatch;
+//!
+//! let mut out = vec![0x01];
+//! let size = backpatch::reserve(&mut out, 5);
+//! out.extend_from_slice(b"body");
+//! let len = (out.len() - size.end()) as u32;
+//! size.patch(&mut out, len).unwrap();
+//! assert_eq!(out, [0x01, 0x84, 0x80, 0x80, 0x80, 0x00, b'b', b'o', b'd', b'y']);
+//! ```
+
+use std::io::{self, SeekFrom};
+
+use crate::LEB128Padded;
+
+/// The location of a reserved field. The reserved bytes hold a padded
+/// encoding of zero until patched, so the output is always decodable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Placeholder {
+    offset: u64,
+    width: usize,
+}
+
+fn zero(width: usize) -> Result<Vec<u8>, io::Error> {
+    let mut bytes = Vec::with_capacity(width);
+    0u128.leb128_encode_padded(&mut bytes, width)?;
+    Ok(bytes)
+}
+
+/// Appends a `width` byte placeholder to `buf`.
+///
+/// # Panics
+///
+/// If `width` is zero or longer than any integer encoding (19 bytes).
+pub fn reserve(buf: &mut Vec<u8>, width: usize) -> Placeholder {
+    let offset = buf.len() as u64;
+    buf.extend(zero(width).expect("placeholder width must be between 1 and 19"));
+    Placeholder { offset, width }
+}
+
+/// Writes a `width` byte placeholder at the stream's current position.
+pub fn reserve_stream<W>(writer: &mut W, width: usize) -> Result<Placeholder, io::Error>
+where
+    W: io::Write + io::Seek,
+{
+    let offset = writer.stream_position()?;
+    writer.write_all(&zero(width)?)?;
+    Ok(Placeholder { offset, width })
+}
+
+impl Placeholder {
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Offset of the first byte after the placeholder.
+    pub fn end(&self) -> usize {
+        self.offset as usize + self.width
+    }
+
+    fn encode<N: LEB128Padded>(&self, value: N) -> Result<Vec<u8>, io::Error> {
+        let mut bytes = Vec::with_capacity(self.width);
+        value.leb128_encode_padded(&mut bytes, self.width)?;
+        Ok(bytes)
+    }
+
+    /// Overwrites the placeholder in `buf` with `value`, padded to the
+    /// reserved width. Fails with `InvalidInput` if it does not fit.
+    pub fn patch<N: LEB128Padded>(&self, buf: &mut [u8], value: N) -> Result<(), io::Error> {
+        let bytes = self.encode(value)?;
+        let start = self.offset as usize;
+        buf.get_mut(start..start + self.width)
+            .ok_or(io::ErrorKind::InvalidInput)?
+            .copy_from_slice(&bytes);
+        Ok(())
+    }
+
+    /// Overwrites the placeholder in a seekable stream, then restores the
+    /// stream position.
+    pub fn patch_stream<N, W>(&self, writer: &mut W, value: N) -> Result<(), io::Error>
+    where
+        N: LEB128Padded,
+        W: io::Write + io::Seek,
+    {
+        let bytes = self.encode(value)?;
+        let position = writer.stream_position()?;
+        writer.seek(SeekFrom::Start(self.offset))?;
+        writer.write_all(&bytes)?;
+        writer.seek(SeekFrom::Start(position))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Cursor, Write};
+
+    use super::{reserve, reserve_stream};
+    use crate::LEB128Codec;
+
+    #[test]
+    fn patches_in_place() {
+        let mut out = Vec::new();
+        let size = reserve(&mut out, 5);
+        assert_eq!(u32::leb128_decode(&mut &out[..]).unwrap(), 0);
+        out.extend([0xAA; 200]);
+        size.patch(&mut out, 200u32).unwrap();
+        assert_eq!(&out[..5], [0xC8, 0x81, 0x80, 0x80, 0x00]);
+        let err = size.patch(&mut out, u64::MAX).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn patches_streams() {
+        let mut stream = Cursor::new(Vec::new());
+        stream.write_all(&[7]).unwrap();
+        let size = reserve_stream(&mut stream, 2).unwrap();
+        stream.write_all(&[1, 2, 3]).unwrap();
+        size.patch_stream(&mut stream, -1i8).unwrap();
+        stream.write_all(&[4]).unwrap();
+        assert_eq!(stream.into_inner(), [7, 0xFF, 0x7F, 1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,206 @@
+//! ASN.1 BER and DER length octets and identifier octets.
+//!
+//! Lengths below 128 are a single byte; longer ones are `0x80 | n` followed
+//! by `n` big-endian bytes. Tag numbers of 31 and above use the high-tag
+//! form: the low five bits of the first identifier byte are all set and the
+//! number follows as a base-128 [VLQ](crate::vlq).
+
+use std::io;
+
+use crate::{vlq, Error, CONTINUATION};
+
+const HIGH_TAG: u8 = 0x1F;
+const INDEFINITE: u8 = 0x80;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Class {
+    Universal,
+    Application,
+    ContextSpecific,
+    Private,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tag {
+    pub class: Class,
+    pub constructed: bool,
+    pub number: u64,
+}
+
+/// Writes a definite length in the shortest form, which is also the DER form.
+pub fn encode_length<W>(len: u64, writer: &mut W) -> Result<usize, io::Error>
+where
+    W: Sized + io::Write,
+{
+    if len < 0x80 {
+        writer.write_all(&[len as u8])?;
+        return Ok(1);
+    }
+    let bytes = len.to_be_bytes();
+    let skip = len.leading_zeros() as usize / 8;
+    writer.write_all(&[INDEFINITE | (8 - skip) as u8])?;
+    writer.write_all(&bytes[skip..])?;
+    Ok(1 + 8 - skip)
+}
+
+fn read_u8<R: io::Read>(reader: &mut R) -> Result<u8, io::Error> {
+    let mut buffer = [0u8; 1];
+    reader.read_exact(&mut buffer)?;
+    Ok(buffer[0])
+}
+
+fn read_length<R: io::Read>(reader: &mut R, der: bool) -> Result<Option<u64>, io::Error> {
+    let first = read_u8(reader)?;
+    if first & CONTINUATION == 0 {
+        return Ok(Some(first as u64));
+    }
+    let count = (first & !CONTINUATION) as usize;
+    match count {
+        0 if der => return Err(io::Error::from(io::ErrorKind::InvalidData)),
+        0 => return Ok(None),
+        // Reserved by X.690.
+        0x7F => return Err(io::Error::from(io::ErrorKind::InvalidData)),
+        9.. => return Err(Error::Overflow.into()),
+        _ => {}
+    }
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes[8 - count..])?;
+    let len = u64::from_be_bytes(bytes);
+    if der && (len < 0x80 || bytes[8 - count] == 0) {
+        return Err(io::Error::from(io::ErrorKind::InvalidData));
+    }
+    Ok(Some(len))
+}
+
+/// Reads BER length octets, returning `None` for the indefinite form.
+/// Lengths that do not fit a `u64` fail with [`Error::Overflow`].
+pub fn decode_length<R>(reader: &mut R) -> Result<Option<u64>, io::Error>
+where
+    R: Sized + io::Read,
+{
+    read_length(reader, false)
+}
+
+/// Reads DER length octets, rejecting the indefinite form and any encoding
+/// longer than necessary with `InvalidData`.
+pub fn decode_der_length<R>(reader: &mut R) -> Result<u64, io::Error>
+where
+    R: Sized + io::Read,
+{
+    read_length(reader, true)?.ok_or(io::Error::from(io::ErrorKind::InvalidData))
+}
+
+pub fn encode_tag<W>(tag: Tag, writer: &mut W) -> Result<usize, io::Error>
+where
+    W: Sized + io::Write,
+{
+    let class = match tag.class {
+        Class::Universal => 0x00,
+        Class::Application => 0x40,
+        Class::ContextSpecific => 0x80,
+        Class::Private => 0xC0,
+    };
+    let first = class | if tag.constructed { 0x20 } else { 0 };
+    if tag.number < HIGH_TAG as u64 {
+        writer.write_all(&[first | tag.number as u8])?;
+        return Ok(1);
+    }
+    writer.write_all(&[first | HIGH_TAG])?;
+    Ok(1 + vlq::encode(tag.number, writer)?)
+}
+
+/// Reads identifier octets. A high-tag number with a leading `0x80` byte or
+/// below 31 is rejected with `InvalidData`, as X.690 requires.
+pub fn decode_tag<R>(reader: &mut R) -> Result<Tag, io::Error>
+where
+    R: Sized + io::Read,
+{
+    let first = read_u8(reader)?;
+    let class = match first >> 6 {
+        0 => Class::Universal,
+        1 => Class::Application,
+        2 => Class::ContextSpecific,
+        _ => Class::Private,
+    };
+    let constructed = first & 0x20 != 0;
+    let number = if first & HIGH_TAG == HIGH_TAG {
+        let lead = read_u8(reader)?;
+        if lead == CONTINUATION {
+            return Err(io::Error::from(io::ErrorKind::InvalidData));
+        }
+        let number = vlq::decode(&mut io::Read::chain(&[lead][..], reader))?;
+        if number < HIGH_TAG as u64 {
+            return Err(io::Error::from(io::ErrorKind::InvalidData));
+        }
+        number
+    } else {
+        (first & HIGH_TAG) as u64
+    };
+    Ok(Tag {
+        class,
+        constructed,
+        number,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+
+    #[test]
+    fn lengths() {
+        for (len, bytes) in [
+            (0, &[0x00][..]),
+            (127, &[0x7F]),
+            (128, &[0x81, 0x80]),
+            (256, &[0x82, 0x01, 0x00]),
+            (
+                u64::MAX,
+                &[0x88, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF],
+            ),
+        ] {
+            let mut out = Vec::new();
+            assert_eq!(encode_length(len, &mut out).unwrap(), bytes.len());
+            assert_eq!(out, bytes);
+            assert_eq!(decode_length(&mut &bytes[..]).unwrap(), Some(len));
+            assert_eq!(decode_der_length(&mut &bytes[..]).unwrap(), len);
+        }
+        assert_eq!(decode_length(&mut &[0x80][..]).unwrap(), None);
+        for lax in [&[0x80][..], &[0x81, 0x05], &[0x82, 0x00, 0x80]] {
+            assert!(decode_length(&mut &lax[..]).is_ok());
+            let err = decode_der_length(&mut &lax[..]).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+        let err = decode_length(&mut &[0x89, 1, 0, 0, 0, 0, 0, 0, 0, 0][..]).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+        assert!(decode_length(&mut &[0xFF][..]).is_err());
+    }
+
+    #[test]
+    fn tags() {
+        let sequence = Tag {
+            class: Class::Universal,
+            constructed: true,
+            number: 16,
+        };
+        let high = Tag {
+            class: Class::ContextSpecific,
+            constructed: false,
+            number: 201,
+        };
+        let mut out = Vec::new();
+        encode_tag(sequence, &mut out).unwrap();
+        assert_eq!(encode_tag(high, &mut out).unwrap(), 3);
+        assert_eq!(out, [0x30, 0x9F, 0x81, 0x49]);
+        let mut readable = &out[..];
+        assert_eq!(decode_tag(&mut readable).unwrap(), sequence);
+        assert_eq!(decode_tag(&mut readable).unwrap(), high);
+
+        for bad in [&[0x1F, 0x80, 0x49][..], &[0x1F, 0x05]] {
+            let err = decode_tag(&mut &bad[..]).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+}
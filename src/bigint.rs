@@ -0,0 +1,99 @@
+//! Arbitrary-precision `BigUint`/`BigInt` support. Values have no fixed width,
+//! so decoding never overflows; it only stops at the terminating byte.
+
+use std::io;
+
+use num_bigint::{BigInt, BigUint, Sign};
+
+use crate::{
+    groups::{read_groups, write_groups},
+    LEB128Codec, LEB128Encode,
+};
+
+impl LEB128Codec for BigUint {
+    fn leb128_decode<R>(reader: &mut R) -> Result<Self, io::Error>
+    where
+        R: Sized + io::Read,
+        Self: Sized,
+    {
+        let bytes = read_groups(reader, false, usize::MAX)?;
+        Ok(BigUint::from_bytes_le(&bytes))
+    }
+}
+
+impl LEB128Encode for BigUint {
+    fn leb128_encode<W>(self, writer: &mut W) -> Result<usize, io::Error>
+    where
+        W: Sized + io::Write,
+        Self: Sized,
+    {
+        let groups = (self.bits() as usize).div_ceil(7).max(1);
+        write_groups(&self.to_bytes_le(), groups, 0, writer)
+    }
+}
+
+impl LEB128Codec for BigInt {
+    fn leb128_decode<R>(reader: &mut R) -> Result<Self, io::Error>
+    where
+        R: Sized + io::Read,
+        Self: Sized,
+    {
+        let bytes = read_groups(reader, true, usize::MAX)?;
+        Ok(BigInt::from_signed_bytes_le(&bytes))
+    }
+}
+
+impl LEB128Encode for BigInt {
+    fn leb128_encode<W>(self, writer: &mut W) -> Result<usize, io::Error>
+    where
+        W: Sized + io::Write,
+        Self: Sized,
+    {
+        let negative = self.sign() == Sign::Minus;
+        let magnitude_bits = if negative {
+            (-&self - 1u8).bits()
+        } else {
+            self.bits()
+        };
+        let groups = (magnitude_bits as usize + 1).div_ceil(7);
+        let fill = if negative { 0xFF } else { 0 };
+        write_groups(&self.to_signed_bytes_le(), groups, fill, writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::{BigInt, BigUint};
+
+    use crate::LEB128Codec;
+
+    fn encode<N: LEB128Codec>(num: N) -> Vec<u8> {
+        let mut buf = Vec::new();
+        num.leb128_encode(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn matches_primitive_encodings() {
+        for x in [0u128, 1, 0x7F, 0x80, 0x29442, u64::MAX as u128, u128::MAX] {
+            let bytes = encode(BigUint::from(x));
+            assert_eq!(bytes, encode(x));
+            assert_eq!(BigUint::leb128_decode(&mut &bytes[..]).unwrap(), x.into());
+        }
+        for x in [0i128, -1, 63, 64, -64, -65, -0x8652, i128::MIN, i128::MAX] {
+            let bytes = encode(BigInt::from(x));
+            assert_eq!(bytes, encode(x));
+            assert_eq!(BigInt::leb128_decode(&mut &bytes[..]).unwrap(), x.into());
+        }
+    }
+
+    #[test]
+    fn trips_beyond_128_bits() {
+        let big = BigUint::from(u128::MAX) * BigUint::from(u128::MAX) + 12345u32;
+        let bytes = encode(big.clone());
+        assert_eq!(BigUint::leb128_decode(&mut &bytes[..]).unwrap(), big);
+        let negative = -BigInt::from(big);
+        let bytes = encode(negative.clone());
+        assert_eq!(BigInt::leb128_decode(&mut &bytes[..]).unwrap(), negative);
+    }
+}
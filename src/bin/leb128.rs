@@ -0,0 +1,179 @@
+use std::{
+    fs,
+    io::{self, Write},
+    process::ExitCode,
+};
+
+use lebase::format::{self, VarintFormat};
+use lebase::vectors;
+
+const USAGE: &str = "usage: leb128 <command>\n\ncommands:\n  formats                      list registered formats\n  gen-vectors [--csv | --json] print conformance vectors\n  encode [-f FORMAT] NUMBER... print the encoding of decimal or 0x hex numbers\n  decode [-f FORMAT] HEX...    decode hex bytes into numbers\n  dump [-f FORMAT] FILE        print every value in a file with its offset\n\nFORMAT defaults to uleb128.";
+
+fn usage() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, USAGE)
+}
+
+/// Splits a leading `-f FORMAT` off the arguments.
+fn format_arg(args: &[String]) -> Result<(&'static dyn VarintFormat, &[String]), io::Error> {
+    let (name, rest) = match args {
+        [flag, name, rest @ ..] if flag == "-f" || flag == "--format" => (name.as_str(), rest),
+        _ => ("uleb128", args),
+    };
+    let format = format::lookup(name).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("unknown format {name}"),
+        )
+    })?;
+    Ok((format, rest))
+}
+
+fn parse_number(arg: &str) -> Result<i128, io::Error> {
+    let (negative, digits) = match arg.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, arg),
+    };
+    let parsed = match digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) => u128::from_str_radix(hex, 16),
+        None => digits.parse::<u128>(),
+    };
+    let invalid = || io::Error::new(io::ErrorKind::InvalidInput, format!("bad number {arg}"));
+    let magnitude = parsed.map_err(|_| invalid())?;
+    if negative {
+        0i128.checked_sub_unsigned(magnitude).ok_or_else(invalid)
+    } else {
+        i128::try_from(magnitude).map_err(|_| invalid())
+    }
+}
+
+fn parse_hex(args: &[String]) -> Result<Vec<u8>, io::Error> {
+    let digits: String = args
+        .iter()
+        .flat_map(|arg| arg.split(|c: char| c == ',' || c.is_whitespace()))
+        .map(|word| word.strip_prefix("0x").unwrap_or(word))
+        .collect();
+    let invalid = || io::Error::new(io::ErrorKind::InvalidInput, "bad hex bytes");
+    if !digits.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| {
+            digits
+                .get(i..i + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(invalid)
+        })
+        .collect()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let pairs: Vec<String> = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    pairs.join(" ")
+}
+
+/// Decodes the value at the start of `bytes`, refusing lengths a
+/// registered format should never report.
+fn decode_next(format: &dyn VarintFormat, bytes: &[u8]) -> Result<(i128, usize), io::Error> {
+    let (value, len) = format.decode(bytes)?;
+    if len == 0 || len > bytes.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "format {} reported a length of {len} for {} bytes",
+                format.name(),
+                bytes.len()
+            ),
+        ));
+    }
+    Ok((value, len))
+}
+
+fn encode(args: &[String]) -> Result<(), io::Error> {
+    let (format, numbers) = format_arg(args)?;
+    if numbers.is_empty() {
+        return Err(usage());
+    }
+    let mut stdout = io::stdout().lock();
+    for number in numbers {
+        let mut out = Vec::new();
+        format.encode(parse_number(number)?, &mut out)?;
+        writeln!(stdout, "{}", hex(&out))?;
+    }
+    stdout.flush()
+}
+
+fn decode(args: &[String]) -> Result<(), io::Error> {
+    let (format, hex_args) = format_arg(args)?;
+    let bytes = parse_hex(hex_args)?;
+    let mut stdout = io::stdout().lock();
+    let mut rest = &bytes[..];
+    while !rest.is_empty() {
+        let (value, len) = decode_next(format, rest)?;
+        writeln!(stdout, "{value}")?;
+        rest = &rest[len..];
+    }
+    stdout.flush()
+}
+
+fn dump(args: &[String]) -> Result<(), io::Error> {
+    let (format, [path]) = format_arg(args)? else {
+        return Err(usage());
+    };
+    let bytes = fs::read(path)?;
+    let mut stdout = io::stdout().lock();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let (value, len) = decode_next(format, &bytes[offset..])
+            .map_err(|e| io::Error::new(e.kind(), format!("at offset {offset:#x}: {e}")))?;
+        writeln!(
+            stdout,
+            "{offset:08x}  {:<30} {value}",
+            hex(&bytes[offset..offset + len])
+        )?;
+        offset += len;
+    }
+    stdout.flush()
+}
+
+fn gen_vectors(args: &[String]) -> Result<(), io::Error> {
+    let vectors = vectors::generate();
+    let mut stdout = io::stdout().lock();
+    match args {
+        [] => vectors::write_json(&vectors, &mut stdout)?,
+        [flag] if flag == "--json" => vectors::write_json(&vectors, &mut stdout)?,
+        [flag] if flag == "--csv" => vectors::write_csv(&vectors, &mut stdout)?,
+        _ => return Err(usage()),
+    }
+    stdout.flush()
+}
+
+fn list_formats() -> Result<(), io::Error> {
+    let mut stdout = io::stdout().lock();
+    for format in format::formats() {
+        writeln!(stdout, "{}", format.name())?;
+    }
+    stdout.flush()
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.split_first() {
+        Some((command, [])) if command == "formats" => list_formats(),
+        Some((command, rest)) if command == "gen-vectors" => gen_vectors(rest),
+        Some((command, rest)) if command == "encode" => encode(rest),
+        Some((command, rest)) if command == "decode" => decode(rest),
+        Some((command, rest)) if command == "dump" => dump(rest),
+        _ => Err(usage()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("leb128: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
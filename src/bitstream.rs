@@ -0,0 +1,254 @@
+//! Bit-granular reading and writing, and the variable bit-rate (VBR)
+//! integers of LLVM bitcode built on it.
+//!
+//! Bits are packed least significant first, so the first bit written is bit
+//! 0 of byte 0, matching LLVM's bitstream container. A VBR-`n` value is split
+//! into `n - 1` bit chunks, low chunk first, each written as an `n` bit field
+//! whose top bit says another chunk follows. The Elias codes in
+//! [`elias`](crate::elias) and the Golomb codes in [`golomb`](crate::golomb)
+//! are also written on top of these.
+
+use std::io;
+
+use crate::Error;
+
+#[derive(Clone, Debug, Default)]
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u64,
+    acc_bits: u32,
+}
+
+impl BitWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bits written so far.
+    pub fn bit_len(&self) -> u64 {
+        self.bytes.len() as u64 * 8 + self.acc_bits as u64
+    }
+
+    /// Writes the low `width` bits of `value`.
+    ///
+    /// # Panics
+    ///
+    /// If `width` is greater than 64.
+    pub fn write_bits(&mut self, value: u64, width: u32) {
+        assert!(width <= 64, "bit width must be at most 64");
+        let mut value = if width == 64 {
+            value
+        } else {
+            value & ((1 << width) - 1)
+        };
+        let mut width = width;
+        while width > 0 {
+            let take = width.min(8);
+            self.acc |= (value & ((1 << take) - 1)) << self.acc_bits;
+            self.acc_bits += take;
+            value = value.checked_shr(take).unwrap_or(0);
+            width -= take;
+            while self.acc_bits >= 8 {
+                self.bytes.push(self.acc as u8);
+                self.acc >>= 8;
+                self.acc_bits -= 8;
+            }
+        }
+    }
+
+    pub fn write_bit(&mut self, bit: bool) {
+        self.write_bits(bit as u64, 1);
+    }
+
+    /// Writes `value` as VBR-`width`.
+    ///
+    /// # Panics
+    ///
+    /// If `width` is not between 2 and 32.
+    pub fn write_vbr(&mut self, value: u64, width: u32) {
+        assert!(
+            (2..=32).contains(&width),
+            "VBR width must be between 2 and 32"
+        );
+        let flag = 1 << (width - 1);
+        let mut value = value;
+        while value >= flag {
+            self.write_bits(value & (flag - 1) | flag, width);
+            value >>= width - 1;
+        }
+        self.write_bits(value, width);
+    }
+
+    /// Pads with zero bits up to the next multiple of 32, as LLVM does before
+    /// blocks and blobs.
+    pub fn align32(&mut self) {
+        let over = (self.bit_len() % 32) as u32;
+        if over != 0 {
+            self.write_bits(0, 32 - over);
+        }
+    }
+
+    /// Returns the written bytes, zero-padding the last partial byte.
+    pub fn into_bytes(mut self) -> Vec<u8> {
+        if self.acc_bits > 0 {
+            self.bytes.push(self.acc as u8);
+        }
+        self.bytes
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: u64,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        BitReader { bytes, position: 0 }
+    }
+
+    /// Bits consumed so far.
+    pub fn bit_position(&self) -> u64 {
+        self.position
+    }
+
+    /// Bits left before the end of the input.
+    pub fn remaining_bits(&self) -> u64 {
+        self.bytes.len() as u64 * 8 - self.position
+    }
+
+    /// Reads a `width` bit field, failing with `UnexpectedEof` without
+    /// consuming anything if fewer bits remain.
+    ///
+    /// # Panics
+    ///
+    /// If `width` is greater than 64.
+    pub fn read_bits(&mut self, width: u32) -> Result<u64, io::Error> {
+        assert!(width <= 64, "bit width must be at most 64");
+        if (width as u64) > self.remaining_bits() {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+        }
+        let mut value = 0u64;
+        let mut filled = 0;
+        while filled < width {
+            let byte = self.bytes[(self.position / 8) as usize];
+            let offset = (self.position % 8) as u32;
+            let take = (8 - offset).min(width - filled);
+            let bits = (byte >> offset) as u64 & ((1 << take) - 1);
+            value |= bits << filled;
+            filled += take;
+            self.position += take as u64;
+        }
+        Ok(value)
+    }
+
+    pub fn read_bit(&mut self) -> Result<bool, io::Error> {
+        Ok(self.read_bits(1)? == 1)
+    }
+
+    /// Reads a VBR-`width` value, failing with [`Error::Overflow`] once it no
+    /// longer fits a `u64`.
+    ///
+    /// # Panics
+    ///
+    /// If `width` is not between 2 and 32.
+    pub fn read_vbr(&mut self, width: u32) -> Result<u64, io::Error> {
+        assert!(
+            (2..=32).contains(&width),
+            "VBR width must be between 2 and 32"
+        );
+        let flag = 1 << (width - 1);
+        let mut value = 0u64;
+        let mut shift = 0;
+        loop {
+            let piece = self.read_bits(width)?;
+            let chunk = piece & (flag - 1);
+            if chunk != 0 && (shift >= 64 || chunk.leading_zeros() < shift) {
+                return Err(Error::Overflow.into());
+            }
+            if shift < 64 {
+                value |= chunk << shift;
+            }
+            if piece & flag == 0 {
+                break Ok(value);
+            }
+            shift += width - 1;
+        }
+    }
+
+    /// Skips to the next multiple of 32 bits.
+    pub fn align32(&mut self) -> Result<(), io::Error> {
+        let over = (self.position % 32) as u32;
+        if over != 0 {
+            self.read_bits(32 - over)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{BitReader, BitWriter};
+    use crate::Error;
+
+    #[test]
+    fn fields_round_trip() {
+        let fields = [
+            (1, 1),
+            (0b101, 3),
+            (0xABCD, 16),
+            (u64::MAX, 64),
+            (0, 0),
+            (0x3F, 7),
+        ];
+        let mut writer = BitWriter::new();
+        for (value, width) in fields {
+            writer.write_bits(value, width);
+        }
+        assert_eq!(writer.bit_len(), 91);
+        let bytes = writer.into_bytes();
+        assert_eq!(bytes.len(), 12);
+        assert_eq!(bytes[0], 0b1101_1011);
+        let mut reader = BitReader::new(&bytes);
+        for (value, width) in fields {
+            assert_eq!(reader.read_bits(width).unwrap(), value);
+        }
+        assert_eq!(reader.remaining_bits(), 5);
+        let err = reader.read_bits(6).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn vbr_matches_llvm() {
+        // VBR6 of 0x3F needs two fields: 0b111111 (continued) and 0b000001.
+        let mut writer = BitWriter::new();
+        writer.write_vbr(0x3F, 6);
+        writer.write_vbr(3, 4);
+        writer.align32();
+        writer.write_vbr(u64::MAX, 8);
+        writer.write_vbr(0, 2);
+        assert_eq!(writer.bit_len(), 32 + 10 * 8 + 2);
+        let bytes = writer.into_bytes();
+        assert_eq!(&bytes[..2], [0b0111_1111, 0b0011_0000]);
+
+        let mut reader = BitReader::new(&bytes);
+        assert_eq!(reader.read_vbr(6).unwrap(), 0x3F);
+        assert_eq!(reader.read_vbr(4).unwrap(), 3);
+        reader.align32().unwrap();
+        assert_eq!(reader.bit_position(), 32);
+        assert_eq!(reader.read_vbr(8).unwrap(), u64::MAX);
+        assert_eq!(reader.read_vbr(2).unwrap(), 0);
+
+        let mut writer = BitWriter::new();
+        for _ in 0..9 {
+            writer.write_bits(0xFF, 8);
+        }
+        writer.write_bits(0x02, 8);
+        let bytes = writer.into_bytes();
+        let err = BitReader::new(&bytes).read_vbr(8).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+    }
+}
@@ -0,0 +1,897 @@
+//! Decoding straight out of byte slices, one value or whole buffers at once.
+
+use std::{hash::Hash, io};
+
+use num_traits::PrimInt;
+
+use crate::{
+    error::relocate, from_group, is_signed, max_encoded_len, scan::leb128_skip_slice,
+    sketch::Sketch, Error, LEB128Codec, CONTINUATION,
+};
+
+/// Decodes every value in `bytes`, calling `on_run` once per run of
+/// consecutive equal values with the value and the run length. Returns the
+/// total number of values decoded. Error locations are relative to the
+/// start of `bytes`.
+pub fn decode_runs<N, F>(bytes: &[u8], mut on_run: F) -> Result<usize, io::Error>
+where
+    N: LEB128Codec + PartialEq + Copy,
+    F: FnMut(N, usize),
+{
+    let mut readable = bytes;
+    let mut run: Option<(N, usize)> = None;
+    let mut count = 0;
+    while !readable.is_empty() {
+        let offset = bytes.len() - readable.len();
+        let num = N::leb128_decode(&mut readable).map_err(|e| relocate(e, offset as u64))?;
+        count += 1;
+        run = match run {
+            Some((value, len)) if value == num => Some((value, len + 1)),
+            Some((value, len)) => {
+                on_run(value, len);
+                Some((num, 1))
+            }
+            None => Some((num, 1)),
+        };
+    }
+    if let Some((value, len)) = run {
+        on_run(value, len);
+    }
+    Ok(count)
+}
+
+/// Appends every value in `bytes` to `out`, returning how many were
+/// appended. Blocks made up entirely of one- or two-byte values are widened
+/// with SIMD on x86-64 (AVX2 when detected at runtime, SSE2 otherwise), or
+/// with `std::simd` on other targets when the nightly-only `portable_simd`
+/// feature is enabled. On x86-64 with SSSE3, runs mixing one- and two-byte
+/// values are split apart with a `pshufb` lookup table as well; longer
+/// values go through the scalar decoder. On error `out` is left as it was
+/// and the error's [`Location`](crate::Location) is relative to the start
+/// of `bytes`.
+pub fn decode_slice_into(bytes: &[u8], out: &mut Vec<u32>) -> Result<usize, io::Error> {
+    let start = out.len();
+    match decode_u32s(bytes, out) {
+        Ok(()) => Ok(out.len() - start),
+        Err(_) => {
+            out.truncate(start);
+            Err(locate_error(bytes))
+        }
+    }
+}
+
+/// Like [`decode_slice_into`], also observing every appended value in
+/// `sketch`. Nothing is observed if decoding fails.
+pub fn decode_slice_into_sketched(
+    bytes: &[u8],
+    out: &mut Vec<u32>,
+    sketch: &mut Sketch<u32>,
+) -> Result<usize, io::Error> {
+    let start = out.len();
+    let count = decode_slice_into(bytes, out)?;
+    sketch.observe_all(&out[start..]);
+    Ok(count)
+}
+
+/// Finds the first failure in `bytes` with the scalar decoder. The SIMD
+/// paths fail on exactly the same input but lose track of where.
+fn locate_error(bytes: &[u8]) -> io::Error {
+    let mut offset = 0;
+    loop {
+        match decode_u32_slice(&bytes[offset..]) {
+            Ok((_, len)) => offset += len,
+            Err(e) => break relocate(e, offset as u64),
+        }
+    }
+}
+
+fn decode_u32s(bytes: &[u8], out: &mut Vec<u32>) -> Result<(), io::Error> {
+    #[allow(unused_mut)]
+    let mut rest = bytes;
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: AVX2 support was just checked.
+            rest = unsafe { x86::decode_avx2(rest, out)? };
+        }
+        if is_x86_feature_detected!("ssse3") {
+            // SAFETY: SSSE3 support was just checked.
+            rest = unsafe { x86::decode_ssse3(rest, out)? };
+        }
+        rest = x86::decode_sse2(rest, out)?;
+    }
+    #[cfg(feature = "portable_simd")]
+    {
+        rest = portable::decode(rest, out)?;
+    }
+    decode_scalar(rest, out, 0)?;
+    Ok(())
+}
+
+/// Decodes values until at most `keep` bytes are left, returning the rest.
+fn decode_scalar<'a>(
+    mut bytes: &'a [u8],
+    out: &mut Vec<u32>,
+    keep: usize,
+) -> Result<&'a [u8], io::Error> {
+    while bytes.len() > keep {
+        let (num, len) = decode_u32_slice(bytes)?;
+        out.push(num);
+        bytes = &bytes[len..];
+    }
+    Ok(bytes)
+}
+
+/// Decodes the value at the start of `bytes`, returning it and its length.
+/// On x86-64 with BMI2, values of up to 8 bytes with at least 8 bytes of
+/// input available are extracted with a single `pext`.
+pub fn decode_u64_slice(bytes: &[u8]) -> Result<(u64, usize), io::Error> {
+    #[cfg(target_arch = "x86_64")]
+    if let Some(found) = x86::pext_short(bytes) {
+        return Ok(found);
+    }
+    slow_slice(bytes)
+}
+
+pub fn decode_u32_slice(bytes: &[u8]) -> Result<(u32, usize), io::Error> {
+    #[cfg(target_arch = "x86_64")]
+    if let Some((num, len)) = x86::pext_short(bytes) {
+        // Anything over five bytes is rejected even when the value is small,
+        // matching the streaming decoder, which also reports where.
+        return match u32::try_from(num) {
+            Ok(num) if len <= 5 => Ok((num, len)),
+            _ => slow_slice(bytes),
+        };
+    }
+    slow_slice(bytes)
+}
+
+/// Fills `out` with the next `out.len()` values from `reader`, returning the
+/// number of bytes consumed. Each refill of the reader's buffer is scanned
+/// once for its last complete value and everything before it is decoded in
+/// place; only a value straddling two refills is copied out first. On error
+/// the reader is left just past the value that failed; a straddling value
+/// longer than `N` can encode fails with [`Error::Overflow`] once its bytes
+/// run out.
+pub fn decode_many_into<N, R>(reader: &mut R, out: &mut [N]) -> Result<usize, io::Error>
+where
+    N: LEB128Codec + PrimInt,
+    R: io::BufRead,
+{
+    let mut consumed = 0;
+    let mut filled = 0;
+    while filled < out.len() {
+        let buf = reader.fill_buf()?;
+        let Some(last) = buf.iter().rposition(|byte| byte & CONTINUATION == 0) else {
+            let pending = read_straddling(reader, max_encoded_len::<N>())?;
+            consumed += pending.len();
+            out[filled] = N::leb128_decode(&mut &pending[..])?;
+            filled += 1;
+            continue;
+        };
+        let mut whole = &buf[..=last];
+        let mut result = Ok(());
+        while filled < out.len() && !whole.is_empty() {
+            let before = whole;
+            match N::leb128_decode(&mut whole) {
+                Ok(num) => out[filled] = num,
+                Err(e) => {
+                    // The decoder may stop before the failed value's end.
+                    let read = before.len() - whole.len();
+                    if read > 0 && before[read - 1] & CONTINUATION != 0 {
+                        whole = &whole[leb128_skip_slice(whole)?..];
+                    }
+                    result = Err(e);
+                    break;
+                }
+            }
+            filled += 1;
+        }
+        let used = last + 1 - whole.len();
+        reader.consume(used);
+        consumed += used;
+        result?;
+    }
+    Ok(consumed)
+}
+
+/// Like [`decode_many_into`], also observing the values in `sketch` once
+/// `out` is filled. Nothing is observed if decoding fails.
+pub fn decode_many_into_sketched<N, R>(
+    reader: &mut R,
+    out: &mut [N],
+    sketch: &mut Sketch<N>,
+) -> Result<usize, io::Error>
+where
+    N: LEB128Codec + PrimInt + Hash,
+    R: io::BufRead,
+{
+    let consumed = decode_many_into(reader, out)?;
+    sketch.observe_all(out);
+    Ok(consumed)
+}
+
+/// Collects one value that begins in the current buffer but ends in a later
+/// refill, failing with [`Error::Overflow`] once more than `max_len` bytes
+/// have gone by without an end.
+fn read_straddling<R: io::BufRead>(reader: &mut R, max_len: usize) -> Result<Vec<u8>, io::Error> {
+    let mut pending = Vec::new();
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+        }
+        let room = max_len - pending.len();
+        let end = buf.iter().position(|byte| byte & CONTINUATION == 0);
+        if end.map_or(buf.len(), |end| end + 1) > room {
+            let last = max_len - 1;
+            reader.consume(room);
+            return Err(Error::Overflow.at(last, last * 7));
+        }
+        match end {
+            Some(end) => {
+                pending.extend_from_slice(&buf[..=end]);
+                reader.consume(end + 1);
+                return Ok(pending);
+            }
+            None => {
+                pending.extend_from_slice(buf);
+                let len = buf.len();
+                reader.consume(len);
+            }
+        }
+    }
+}
+
+pub const ENCODE_CHUNK: usize = 64 * 1024;
+
+/// Encodes `values` back to back into one buffer and hands it to `writer` in
+/// as few writes as possible, returning the total bytes written.
+pub fn encode_many<N, W>(values: &[N], writer: &mut W) -> Result<usize, io::Error>
+where
+    N: LEB128Codec + Copy,
+    W: io::Write,
+{
+    encode_many_with(values, writer, &mut Vec::new())
+}
+
+/// Like [`encode_many`], but stages the output in `scratch`, so repeated
+/// calls can reuse one allocation. Output is written in chunks of about
+/// `ENCODE_CHUNK` bytes.
+pub fn encode_many_with<N, W>(
+    values: &[N],
+    writer: &mut W,
+    scratch: &mut Vec<u8>,
+) -> Result<usize, io::Error>
+where
+    N: LEB128Codec + Copy,
+    W: io::Write,
+{
+    scratch.clear();
+    scratch.reserve(ENCODE_CHUNK.min(values.len() * 2));
+    let mut written = 0;
+    for &num in values {
+        num.leb128_encode(scratch)?;
+        if scratch.len() >= ENCODE_CHUNK {
+            writer.write_all(scratch)?;
+            written += scratch.len();
+            scratch.clear();
+        }
+    }
+    writer.write_all(scratch)?;
+    written += scratch.len();
+    scratch.clear();
+    Ok(written)
+}
+
+/// Decodes the value at the start of `bytes` without overflow or length
+/// checks, for input the caller has already validated or produced itself.
+/// Bits beyond the width of `N` are dropped, and a truncated value decodes
+/// from whatever bytes are present; neither is reported.
+#[inline]
+#[cfg_attr(feature = "no_panic", no_panic::no_panic)]
+pub fn leb128_decode_unchecked<N: PrimInt>(bytes: &[u8]) -> (N, usize) {
+    let bits = N::zero().count_zeros() as usize;
+    let mut num = N::zero();
+    let mut shift = 0;
+    for (i, &byte) in bytes.iter().enumerate() {
+        if shift < bits {
+            num = num | from_group::<N>(byte) << shift;
+        }
+        shift += 7;
+        if byte & CONTINUATION == 0 {
+            if is_signed::<N>() && byte & 0x40 != 0 && shift < bits {
+                num = num | !N::zero() << shift;
+            }
+            return (num, i + 1);
+        }
+    }
+    (num, bytes.len())
+}
+
+/// Bytes needed for each `u32` of a group, as stored in its tag.
+fn group_len(value: u32) -> usize {
+    (4 - value.leading_zeros() as usize / 8).max(1)
+}
+
+/// Total length of a group's data bytes for each tag byte.
+const GROUP_DATA_LEN: [u8; 256] = {
+    let mut table = [0u8; 256];
+    let mut tag = 0;
+    while tag < 256 {
+        let mut i = 0;
+        while i < 4 {
+            table[tag] += ((tag >> (2 * i)) & 3) as u8 + 1;
+            i += 1;
+        }
+        tag += 1;
+    }
+    table
+};
+
+/// Writes `values` in group varint form: groups of four, each a tag byte
+/// holding four two-bit `length - 1` fields, first value in the low bits,
+/// followed by the values' little-endian bytes. A final partial group is
+/// padded with zeros. Returns the bytes written.
+pub fn encode_group_varint<W>(values: &[u32], writer: &mut W) -> Result<usize, io::Error>
+where
+    W: io::Write,
+{
+    let mut out = Vec::with_capacity(values.len().div_ceil(4) * 17);
+    for group in values.chunks(4) {
+        let mut quad = [0u32; 4];
+        quad[..group.len()].copy_from_slice(group);
+        let tag_at = out.len();
+        out.push(0);
+        for (i, &value) in quad.iter().enumerate() {
+            let len = group_len(value);
+            out[tag_at] |= ((len - 1) << (2 * i)) as u8;
+            out.extend_from_slice(&value.to_le_bytes()[..len]);
+        }
+    }
+    writer.write_all(&out)?;
+    Ok(out.len())
+}
+
+/// Fills `out` from group varint data, returning the bytes consumed. The
+/// whole group holding the last value is consumed, padding included.
+pub fn decode_group_varint(bytes: &[u8], out: &mut [u32]) -> Result<usize, io::Error> {
+    let mut pos = 0;
+    for quad in out.chunks_mut(4) {
+        let tag = *bytes
+            .get(pos)
+            .ok_or(io::Error::from(io::ErrorKind::UnexpectedEof))?;
+        let data_len = GROUP_DATA_LEN[tag as usize] as usize;
+        let data = bytes
+            .get(pos + 1..pos + 1 + data_len)
+            .ok_or(io::Error::from(io::ErrorKind::UnexpectedEof))?;
+        let mut offset = 0;
+        for (i, value) in quad.iter_mut().enumerate() {
+            let len = ((tag >> (2 * i)) & 3) as usize + 1;
+            let mut le = [0u8; 4];
+            le[..len].copy_from_slice(&data[offset..offset + len]);
+            *value = u32::from_le_bytes(le);
+            offset += len;
+        }
+        pos += 1 + data_len;
+    }
+    Ok(pos)
+}
+
+fn slow_slice<N: LEB128Codec>(bytes: &[u8]) -> Result<(N, usize), io::Error> {
+    let mut readable = bytes;
+    let num = N::leb128_decode(&mut readable)?;
+    Ok((num, bytes.len() - readable.len()))
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::{arch::x86_64::*, io};
+
+    use super::decode_scalar;
+
+    const HIGH_BITS: u64 = 0x8080_8080_8080_8080;
+
+    /// Decodes a value of at most 8 bytes with `pext`, or returns `None` if
+    /// BMI2 is unavailable, fewer than 8 bytes remain, or the value is longer.
+    #[inline]
+    pub(super) fn pext_short(bytes: &[u8]) -> Option<(u64, usize)> {
+        let word = u64::from_le_bytes(*bytes.first_chunk::<8>()?);
+        if !is_x86_feature_detected!("bmi2") {
+            return None;
+        }
+        let ends = !word & HIGH_BITS;
+        if ends == 0 {
+            return None;
+        }
+        let len = ends.trailing_zeros() as usize / 8 + 1;
+        // Every bit up to and including the first terminator's high bit.
+        let used = ends ^ (ends - 1);
+        // SAFETY: BMI2 support was just checked.
+        Some((unsafe { pext(word & used) }, len))
+    }
+
+    #[target_feature(enable = "bmi2")]
+    unsafe fn pext(word: u64) -> u64 {
+        _pext_u64(word, !HIGH_BITS)
+    }
+
+    /// Continuation mask of a block of alternating two-byte values.
+    const PAIRS: u32 = 0x5555_5555;
+
+    pub(super) fn decode_sse2<'a>(
+        mut bytes: &'a [u8],
+        out: &mut Vec<u32>,
+    ) -> Result<&'a [u8], io::Error> {
+        while bytes.len() >= 16 {
+            let mut values = [0u32; 16];
+            // SAFETY: SSE2 is part of the x86-64 baseline and the block is 16
+            // bytes long. Unaligned loads and stores are used throughout.
+            let count = unsafe {
+                let block = _mm_loadu_si128(bytes.as_ptr().cast());
+                let mask = _mm_movemask_epi8(block) as u32;
+                decode_uniform_sse2(block, mask, values.as_mut_ptr().cast())
+            };
+            if count == 0 {
+                bytes = decode_scalar(bytes, out, bytes.len() - 16)?;
+            } else {
+                out.extend_from_slice(&values[..count]);
+                bytes = &bytes[16..];
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Like [`decode_sse2`], but blocks mixing one- and two-byte values are
+    /// also decoded eight bytes at a time: the continuation bits of those
+    /// bytes select a `pshufb` control from [`SHORT`] that moves each value
+    /// into a 16-bit lane of its own.
+    #[target_feature(enable = "ssse3")]
+    pub(super) unsafe fn decode_ssse3<'a>(
+        mut bytes: &'a [u8],
+        out: &mut Vec<u32>,
+    ) -> Result<&'a [u8], io::Error> {
+        while bytes.len() >= 16 {
+            let mut values = [0u32; 16];
+            let store = values.as_mut_ptr().cast::<__m128i>();
+            let block = _mm_loadu_si128(bytes.as_ptr().cast());
+            let mask = _mm_movemask_epi8(block) as u32;
+            let (count, consumed) = match decode_uniform_sse2(block, mask, store) {
+                0 => decode_short_ssse3(block, mask, store),
+                count => (count, 16),
+            };
+            if count == 0 {
+                bytes = decode_scalar(bytes, out, bytes.len() - 16)?;
+            } else {
+                out.extend_from_slice(&values[..count]);
+                bytes = &bytes[consumed..];
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Widens a block of sixteen one-byte or eight two-byte values into
+    /// `store`, returning how many there were, or 0 for any other block.
+    #[inline(always)]
+    unsafe fn decode_uniform_sse2(block: __m128i, mask: u32, store: *mut __m128i) -> usize {
+        let zero = _mm_setzero_si128();
+        match mask {
+            0 => {
+                let lo = _mm_unpacklo_epi8(block, zero);
+                let hi = _mm_unpackhi_epi8(block, zero);
+                _mm_storeu_si128(store, _mm_unpacklo_epi16(lo, zero));
+                _mm_storeu_si128(store.add(1), _mm_unpackhi_epi16(lo, zero));
+                _mm_storeu_si128(store.add(2), _mm_unpacklo_epi16(hi, zero));
+                _mm_storeu_si128(store.add(3), _mm_unpackhi_epi16(hi, zero));
+                16
+            }
+            mask if mask == PAIRS & 0xFFFF => {
+                let pairs = join_pairs_sse2(block);
+                _mm_storeu_si128(store, _mm_unpacklo_epi16(pairs, zero));
+                _mm_storeu_si128(store.add(1), _mm_unpackhi_epi16(pairs, zero));
+                8
+            }
+            _ => 0,
+        }
+    }
+
+    /// A `pshufb` control gathering the values that start and end in the
+    /// first eight bytes of a block, given those bytes' continuation bits.
+    #[derive(Clone, Copy)]
+    struct Shuffle {
+        control: [u8; 16],
+        /// Values gathered, each one or two bytes long.
+        count: u8,
+        /// Bytes they take; the first value longer than two bytes, or one
+        /// running past the eighth byte, is left for the scalar decoder.
+        consumed: u8,
+    }
+
+    const fn short_shuffles() -> [Shuffle; 256] {
+        let mut table = [Shuffle {
+            control: [0x80; 16],
+            count: 0,
+            consumed: 0,
+        }; 256];
+        let mut mask = 0;
+        while mask < 256 {
+            let entry = &mut table[mask];
+            let mut pos = 0;
+            while pos < 8 {
+                let lane = 2 * entry.count as usize;
+                if mask >> pos & 1 == 0 {
+                    entry.control[lane] = pos as u8;
+                    pos += 1;
+                } else if pos + 1 < 8 && mask >> (pos + 1) & 1 == 0 {
+                    entry.control[lane] = pos as u8;
+                    entry.control[lane + 1] = pos as u8 + 1;
+                    pos += 2;
+                } else {
+                    break;
+                }
+                entry.count += 1;
+            }
+            entry.consumed = pos as u8;
+            mask += 1;
+        }
+        table
+    }
+
+    static SHORT: [Shuffle; 256] = short_shuffles();
+
+    /// Decodes the leading one- and two-byte values of a block into `store`,
+    /// returning how many there were and the bytes they took.
+    #[target_feature(enable = "ssse3")]
+    unsafe fn decode_short_ssse3(block: __m128i, mask: u32, store: *mut __m128i) -> (usize, usize) {
+        let entry = &SHORT[(mask & 0xFF) as usize];
+        let control = _mm_loadu_si128(entry.control.as_ptr().cast());
+        let values = join_pairs_sse2(_mm_shuffle_epi8(block, control));
+        let zero = _mm_setzero_si128();
+        _mm_storeu_si128(store, _mm_unpacklo_epi16(values, zero));
+        _mm_storeu_si128(store.add(1), _mm_unpackhi_epi16(values, zero));
+        (entry.count as usize, entry.consumed as usize)
+    }
+
+    /// Merges each little-endian 16-bit lane holding a two-byte value into
+    /// its 14-bit result.
+    #[inline(always)]
+    unsafe fn join_pairs_sse2(block: __m128i) -> __m128i {
+        let low = _mm_and_si128(block, _mm_set1_epi16(0x007F));
+        let high = _mm_srli_epi16(_mm_and_si128(block, _mm_set1_epi16(0x7F00)), 1);
+        _mm_or_si128(low, high)
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn decode_avx2<'a>(
+        mut bytes: &'a [u8],
+        out: &mut Vec<u32>,
+    ) -> Result<&'a [u8], io::Error> {
+        while bytes.len() >= 32 {
+            let mut values = [0u32; 32];
+            let ptr = bytes.as_ptr();
+            let store = values.as_mut_ptr().cast::<__m256i>();
+            let block = _mm256_loadu_si256(ptr.cast());
+            let mask = _mm256_movemask_epi8(block) as u32;
+            let (count, consumed) = match mask {
+                0 => {
+                    for i in 0..4 {
+                        let eight = _mm_loadl_epi64(ptr.add(i * 8).cast());
+                        _mm256_storeu_si256(store.add(i), _mm256_cvtepu8_epi32(eight));
+                    }
+                    (32, 32)
+                }
+                PAIRS => {
+                    let low = _mm256_and_si256(block, _mm256_set1_epi16(0x007F));
+                    let high =
+                        _mm256_srli_epi16(_mm256_and_si256(block, _mm256_set1_epi16(0x7F00)), 1);
+                    let pairs = _mm256_or_si256(low, high);
+                    let lo = _mm256_castsi256_si128(pairs);
+                    let hi = _mm256_extracti128_si256(pairs, 1);
+                    _mm256_storeu_si256(store, _mm256_cvtepu16_epi32(lo));
+                    _mm256_storeu_si256(store.add(1), _mm256_cvtepu16_epi32(hi));
+                    (16, 32)
+                }
+                _ => decode_short_ssse3(_mm256_castsi256_si128(block), mask, store.cast()),
+            };
+            if count == 0 {
+                bytes = decode_scalar(bytes, out, bytes.len() - 32)?;
+            } else {
+                out.extend_from_slice(&values[..count]);
+                bytes = &bytes[consumed..];
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+#[cfg(feature = "portable_simd")]
+mod portable {
+    use std::{
+        io,
+        simd::{cmp::SimdPartialOrd, num::SimdUint, simd_swizzle, u32x8, u8x16},
+    };
+
+    use super::decode_scalar;
+
+    /// Continuation mask of a block of alternating two-byte values.
+    const PAIRS: u64 = 0x5555;
+
+    pub(super) fn decode<'a>(
+        mut bytes: &'a [u8],
+        out: &mut Vec<u32>,
+    ) -> Result<&'a [u8], io::Error> {
+        while bytes.len() >= 16 {
+            let block = u8x16::from_slice(&bytes[..16]);
+            match block.simd_ge(u8x16::splat(0x80)).to_bitmask() {
+                0 => {
+                    out.extend_from_slice(block.cast::<u32>().as_array());
+                    bytes = &bytes[16..];
+                }
+                PAIRS => {
+                    let low: u32x8 = simd_swizzle!(block, [0, 2, 4, 6, 8, 10, 12, 14]).cast();
+                    let high: u32x8 = simd_swizzle!(block, [1, 3, 5, 7, 9, 11, 13, 15]).cast();
+                    let values = (low & u32x8::splat(0x7F)) | (high << 7);
+                    out.extend_from_slice(values.as_array());
+                    bytes = &bytes[16..];
+                }
+                _ => bytes = decode_scalar(bytes, out, bytes.len() - 16)?,
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{
+        decode_group_varint, decode_many_into, decode_runs, decode_slice_into, decode_u32_slice,
+        decode_u64_slice, encode_group_varint, encode_many, encode_many_with,
+        leb128_decode_unchecked, ENCODE_CHUNK,
+    };
+    use crate::{Error, LEB128Encode, Location};
+
+    #[test]
+    fn reports_runs() {
+        let bytes = [0, 0, 0, 0xAC, 0x02, 0xAC, 0x02, 0, 5];
+        let mut runs = Vec::new();
+        let count = decode_runs::<u16, _>(&bytes, |value, len| runs.push((value, len))).unwrap();
+        assert_eq!(count, 7);
+        assert_eq!(runs, [(0, 3), (300, 2), (0, 1), (5, 1)]);
+        assert_eq!(decode_runs::<u16, _>(&[], |_, _| panic!()).unwrap(), 0);
+        assert!(decode_runs::<u16, _>(&[0, 0x80], |_, _| {}).is_err());
+    }
+
+    #[test]
+    fn decodes_slices_into_vec() {
+        let mut values: Vec<u32> = (0..100).collect();
+        values.extend((0..70).map(|x| 200 + x * 100));
+        values.extend([u32::MAX, 5, 1 << 21, 0]);
+        values.extend((0..40).map(|x| x * 3));
+        values.extend((0..40).map(|x| 128 + x));
+        let mut bytes = Vec::new();
+        for x in &values {
+            x.leb128_encode(&mut bytes).unwrap();
+        }
+        let mut out = vec![7];
+        assert_eq!(decode_slice_into(&bytes, &mut out).unwrap(), values.len());
+        assert_eq!(out[0], 7);
+        assert_eq!(&out[1..], &values[..]);
+        #[cfg(target_arch = "x86_64")]
+        {
+            let mut sse2 = Vec::new();
+            let rest = super::x86::decode_sse2(&bytes, &mut sse2).unwrap();
+            super::decode_scalar(rest, &mut sse2, 0).unwrap();
+            assert_eq!(sse2, values);
+        }
+        #[cfg(feature = "portable_simd")]
+        {
+            let mut portable = Vec::new();
+            let rest = super::portable::decode(&bytes, &mut portable).unwrap();
+            super::decode_scalar(rest, &mut portable, 0).unwrap();
+            assert_eq!(portable, values);
+        }
+
+        bytes.push(0x80);
+        assert!(decode_slice_into(&bytes, &mut out).is_err());
+        assert_eq!(out.len(), values.len() + 1);
+        let overflow = [0xFF, 0xFF, 0xFF, 0xFF, 0x1F];
+        assert!(decode_slice_into(&overflow, &mut out).is_err());
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn shuffles_mixed_lengths() {
+        if !is_x86_feature_detected!("ssse3") {
+            return;
+        }
+        // One- and two-byte values in an irregular order, with a few longer
+        // ones the shuffle has to leave to the scalar decoder.
+        let values: Vec<u32> = (0..2000u32)
+            .map(|i| match i.wrapping_mul(0x9E37_79B9) >> 29 {
+                0..=3 => i % 128,
+                4..=6 => 128 + i % 16000,
+                _ if i % 50 == 0 => 1 << 20 | i,
+                _ => i % 100,
+            })
+            .collect();
+        let mut bytes = Vec::new();
+        for x in &values {
+            x.leb128_encode(&mut bytes).unwrap();
+        }
+        let mut mixed = Vec::new();
+        // SAFETY: SSSE3 support was just checked.
+        let rest = unsafe { super::x86::decode_ssse3(&bytes, &mut mixed) }.unwrap();
+        assert!(rest.len() < 16);
+        super::decode_scalar(rest, &mut mixed, 0).unwrap();
+        assert_eq!(mixed, values);
+        if is_x86_feature_detected!("avx2") {
+            let mut wide = Vec::new();
+            // SAFETY: AVX2 support was just checked.
+            let rest = unsafe { super::x86::decode_avx2(&bytes, &mut wide) }.unwrap();
+            super::decode_scalar(rest, &mut wide, 0).unwrap();
+            assert_eq!(wide, values);
+        }
+
+        // Every continuation mask of the first eight bytes, followed by
+        // enough single bytes to end whatever is left open.
+        for mask in 0..=255u8 {
+            let mut block: Vec<u8> = (0..8)
+                .map(|i| if mask >> i & 1 == 1 { 0x80 | i } else { i })
+                .collect();
+            block.extend([0x01; 16]);
+            let mut expected = Vec::new();
+            let expected = super::decode_scalar(&block, &mut expected, 0).map(|_| expected);
+            let mut mixed = Vec::new();
+            // SAFETY: as above.
+            let mixed = unsafe { super::x86::decode_ssse3(&block, &mut mixed) }
+                .and_then(|rest| super::decode_scalar(rest, &mut mixed, 0))
+                .map(|_| mixed);
+            assert_eq!(mixed.ok(), expected.ok(), "{mask:#010b}");
+        }
+    }
+
+    #[test]
+    fn errors_carry_offsets() {
+        let mut bytes = vec![1; 100];
+        bytes.extend([0xFF, 0xFF, 0xFF, 0xFF, 0x1F]);
+        bytes.extend([1; 40]);
+        let err = decode_slice_into(&bytes, &mut Vec::new()).unwrap_err();
+        let expected = Location {
+            offset: 104,
+            shift: 28,
+        };
+        assert_eq!(Location::of(&err), Some(expected));
+        let err = decode_runs::<u32, _>(&bytes, |_, _| {}).unwrap_err();
+        assert_eq!(Location::of(&err), Some(expected));
+    }
+
+    #[test]
+    fn slice_decode_matches_stream() {
+        let padded = |mut bytes: Vec<u8>| {
+            bytes.resize(bytes.len() + 8, 0);
+            bytes
+        };
+        for x in [
+            0u64,
+            1,
+            127,
+            128,
+            624485,
+            1 << 49,
+            (1 << 56) - 1,
+            1 << 56,
+            u64::MAX,
+        ] {
+            let mut bytes = Vec::new();
+            let len = x.leb128_encode(&mut bytes).unwrap();
+            assert_eq!(decode_u64_slice(&bytes).unwrap(), (x, len));
+            assert_eq!(decode_u64_slice(&padded(bytes.clone())).unwrap(), (x, len));
+            let narrow = u32::try_from(x).ok().map(|x| (x, len));
+            assert_eq!(decode_u32_slice(&padded(bytes)).ok(), narrow);
+        }
+        let overlong = padded(vec![0x80, 0x80, 0x80, 0x80, 0x80, 0x00]);
+        assert!(decode_u32_slice(&overlong).is_err());
+        assert_eq!(decode_u64_slice(&overlong).unwrap(), (0, 6));
+        assert!(decode_u64_slice(&[0x80; 8]).is_err());
+    }
+
+    #[test]
+    fn unchecked_decode_of_valid_input() {
+        let mut bytes = Vec::new();
+        for x in [0i64, 1, -1, 63, -64, 64, -65, i64::MIN, i64::MAX] {
+            bytes.clear();
+            let len = x.leb128_encode(&mut bytes).unwrap();
+            assert_eq!(leb128_decode_unchecked::<i64>(&bytes), (x, len));
+        }
+        for x in [0u8, 127, 128, 255] {
+            bytes.clear();
+            let len = x.leb128_encode(&mut bytes).unwrap();
+            assert_eq!(leb128_decode_unchecked::<u8>(&bytes), (x, len));
+        }
+        assert_eq!(
+            leb128_decode_unchecked::<u32>(&[0xE5, 0x8E, 0x26, 0x7F]),
+            (624485, 3)
+        );
+    }
+
+    #[test]
+    fn fills_slices_across_refills() {
+        let mut bytes = Vec::new();
+        for x in 0..200i64 {
+            (x * x * -1001).leb128_encode(&mut bytes).unwrap();
+        }
+        let mut reader = io::BufReader::with_capacity(7, &bytes[..]);
+        let mut first = [0i64; 150];
+        let consumed = decode_many_into(&mut reader, &mut first).unwrap();
+        let mut rest = [0i64; 50];
+        assert_eq!(
+            consumed + decode_many_into(&mut reader, &mut rest).unwrap(),
+            bytes.len()
+        );
+        let all: Vec<i64> = first.iter().chain(&rest).copied().collect();
+        assert_eq!(all, (0..200).map(|x| x * x * -1001).collect::<Vec<_>>());
+
+        let mut one = [0u8; 1];
+        let err = decode_many_into(&mut &[0x80][..], &mut one).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        let mut reader = &[0x80, 0x02, 0x05][..];
+        assert!(decode_many_into(&mut reader, &mut one).is_err());
+        assert_eq!(reader, &[0x05][..]);
+
+        let mut endless = io::BufReader::with_capacity(4, io::repeat(0x80));
+        let err = decode_many_into(&mut endless, &mut [0u32; 1]).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+    }
+
+    #[test]
+    fn encodes_slices_in_few_writes() {
+        struct CountWrites(Vec<u8>, usize);
+        impl io::Write for CountWrites {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.1 += 1;
+                self.0.write(buf)
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let values: Vec<u64> = (0..100_000).map(|x| x * 1_000_003).collect();
+        let mut writer = CountWrites(Vec::new(), 0);
+        let written = encode_many(&values, &mut writer).unwrap();
+        assert_eq!(written, writer.0.len());
+        assert!(writer.1 <= written / ENCODE_CHUNK + 1);
+        let mut decoded = vec![0u64; values.len()];
+        decode_many_into(&mut &writer.0[..], &mut decoded).unwrap();
+        assert_eq!(decoded, values);
+
+        let mut scratch = Vec::new();
+        let mut out = Vec::new();
+        assert_eq!(
+            encode_many_with(&[300u16, 1], &mut out, &mut scratch).unwrap(),
+            3
+        );
+        assert_eq!(out, [0xAC, 0x02, 0x01]);
+        assert_eq!(encode_many::<u8, _>(&[], &mut out).unwrap(), 0);
+    }
+
+    #[test]
+    fn group_varint_round_trip() {
+        let mut bytes = Vec::new();
+        encode_group_varint(&[1, 0x100, 0x10000, 0x1000000], &mut bytes).unwrap();
+        assert_eq!(bytes, [0b11_10_01_00, 1, 0, 1, 0, 0, 1, 0, 0, 0, 1]);
+
+        let values: Vec<u32> = (0..1001).map(|x| x * x * 4099).collect();
+        bytes.clear();
+        let written = encode_group_varint(&values, &mut bytes).unwrap();
+        assert_eq!(written, bytes.len());
+        let mut out = vec![0; values.len()];
+        assert_eq!(decode_group_varint(&bytes, &mut out).unwrap(), written);
+        assert_eq!(out, values);
+
+        let err = decode_group_varint(&bytes[..written - 1], &mut out).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
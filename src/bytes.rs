@@ -0,0 +1,196 @@
+//! Length-prefixed byte strings: a ULEB128 length followed by the raw bytes.
+
+use std::io::{self, Read};
+
+use crate::{Error, LEB128Codec, LEB128Encode};
+
+/// Reads a ULEB128 length prefix, rejecting lengths above `max_len` with
+/// [`Error::TooLong`].
+pub fn decode_len<R>(reader: &mut R, max_len: usize) -> Result<usize, io::Error>
+where
+    R: Sized + io::Read,
+{
+    let len = usize::leb128_decode(reader)?;
+    if len > max_len {
+        return Err(Error::TooLong.into());
+    }
+    Ok(len)
+}
+
+pub fn encode_bytes<W>(bytes: &[u8], writer: &mut W) -> Result<usize, io::Error>
+where
+    W: Sized + io::Write,
+{
+    let prefix = bytes.len().leb128_encode(writer)?;
+    writer.write_all(bytes)?;
+    Ok(prefix + bytes.len())
+}
+
+/// Decodes a length-prefixed byte string of at most `max_len` bytes. Memory
+/// is only allocated as the payload arrives, so a large prefix on a short
+/// input does not allocate `max_len` bytes up front.
+pub fn decode_bytes<R>(reader: &mut R, max_len: usize) -> Result<Vec<u8>, io::Error>
+where
+    R: Sized + io::Read,
+{
+    let len = decode_len(reader, max_len)?;
+    read_payload(reader, len)
+}
+
+/// Reads exactly `len` bytes, growing the buffer only as they arrive.
+pub(crate) fn read_payload<R>(reader: &mut R, len: usize) -> Result<Vec<u8>, io::Error>
+where
+    R: Sized + io::Read,
+{
+    let mut bytes = Vec::new();
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+    }
+    Ok(bytes)
+}
+
+pub fn encode_str<W>(s: &str, writer: &mut W) -> Result<usize, io::Error>
+where
+    W: Sized + io::Write,
+{
+    encode_bytes(s.as_bytes(), writer)
+}
+
+/// Decodes a length-prefixed string of at most `max_len` bytes, failing with
+/// [`Error::InvalidUtf8`] if it is not valid UTF-8.
+pub fn decode_string<R>(reader: &mut R, max_len: usize) -> Result<String, io::Error>
+where
+    R: Sized + io::Read,
+{
+    let bytes = decode_bytes(reader, max_len)?;
+    String::from_utf8(bytes).map_err(|e| Error::InvalidUtf8(e.utf8_error()).into())
+}
+
+/// Arena space taken ahead of the payload bytes that fill it.
+#[cfg(feature = "bumpalo")]
+const ARENA_CHUNK: usize = 4096;
+
+/// Decodes a length-prefixed byte string into `arena`. The payload is read
+/// straight into arena memory, which grows a chunk at a time as it arrives,
+/// so a large prefix on a short input does not grow the arena.
+#[cfg(feature = "bumpalo")]
+pub fn decode_bytes_in<'arena, R>(
+    reader: &mut R,
+    max_len: usize,
+    arena: &'arena bumpalo::Bump,
+) -> Result<&'arena [u8], io::Error>
+where
+    R: Sized + io::Read,
+{
+    let len = decode_len(reader, max_len)?;
+    let mut bytes = bumpalo::collections::Vec::new_in(arena);
+    while bytes.len() < len {
+        let start = bytes.len();
+        bytes.resize(start + (len - start).min(ARENA_CHUNK), 0);
+        reader.read_exact(&mut bytes[start..])?;
+    }
+    Ok(bytes.into_bump_slice())
+}
+
+/// Decodes a length-prefixed UTF-8 string into `arena`.
+#[cfg(feature = "bumpalo")]
+pub fn decode_str_in<'arena, R>(
+    reader: &mut R,
+    max_len: usize,
+    arena: &'arena bumpalo::Bump,
+) -> Result<&'arena str, io::Error>
+where
+    R: Sized + io::Read,
+{
+    let bytes = decode_bytes_in(reader, max_len, arena)?;
+    std::str::from_utf8(bytes).map_err(|e| Error::InvalidUtf8(e).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{decode_bytes, decode_string, encode_bytes, encode_str};
+    use crate::Error;
+
+    #[test]
+    fn byte_strings() {
+        let mut buf = Vec::new();
+        assert_eq!(encode_bytes(b"abc", &mut buf).unwrap(), 4);
+        assert_eq!(encode_bytes(&[7; 200], &mut buf).unwrap(), 202);
+        encode_bytes(b"", &mut buf).unwrap();
+        let mut readable = &buf[..];
+        assert_eq!(decode_bytes(&mut readable, 200).unwrap(), b"abc");
+        assert_eq!(decode_bytes(&mut readable, 200).unwrap(), [7; 200]);
+        assert_eq!(decode_bytes(&mut readable, 0).unwrap(), b"");
+        assert!(readable.is_empty());
+    }
+
+    #[test]
+    fn length_cap_and_truncation() {
+        let err = decode_bytes(&mut &[0x80, 0x08][..], 1023).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::TooLong));
+        let err = decode_bytes(&mut &[0xFF, 0xFF, 0x03, 1, 2][..], usize::MAX).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn strings() {
+        let mut buf = Vec::new();
+        encode_str("name", &mut buf).unwrap();
+        encode_str("€", &mut buf).unwrap();
+        buf.extend([2, 0xC3, 0x28]);
+        let mut readable = &buf[..];
+        assert_eq!(decode_string(&mut readable, 16).unwrap(), "name");
+        assert_eq!(decode_string(&mut readable, 16).unwrap(), "€");
+        let err = decode_string(&mut readable, 16).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(matches!(
+            Error::from_io_error(&err),
+            Some(Error::InvalidUtf8(_))
+        ));
+    }
+}
+
+#[cfg(all(test, feature = "bumpalo"))]
+mod arena_tests {
+    use std::io;
+
+    use super::{decode_bytes_in, decode_str_in};
+
+    #[test]
+    fn arena_frames() {
+        let arena = bumpalo::Bump::new();
+        let mut readable = &[3, b'a', b'b', b'c', 2, 0xC3, 0xA9, 1, 0xFF][..];
+        assert_eq!(decode_bytes_in(&mut readable, 16, &arena).unwrap(), b"abc");
+        assert_eq!(decode_str_in(&mut readable, 16, &arena).unwrap(), "é");
+        let err = decode_str_in(&mut readable, 16, &arena).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn arena_length_cap() {
+        let arena = bumpalo::Bump::new();
+        let mut readable = &[0x80, 0x08][..];
+        let err = decode_bytes_in(&mut readable, 1023, &arena).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            crate::Error::from_io_error(&err),
+            Some(crate::Error::TooLong)
+        );
+
+        // A prefix within the cap but past the end of the input.
+        let mut readable = &[0x80, 0x80, 0x40, b'a'][..];
+        let err = decode_bytes_in(&mut readable, 1 << 20, &arena).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert!(arena.allocated_bytes() < 1 << 20);
+
+        // Payloads spanning several chunks.
+        let payload: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+        let mut framed = Vec::new();
+        super::encode_bytes(&payload, &mut framed).unwrap();
+        let decoded = decode_bytes_in(&mut &framed[..], payload.len(), &arena).unwrap();
+        assert_eq!(decoded, &payload[..]);
+    }
+}
@@ -0,0 +1,146 @@
+//! Checks that encodings are minimal, for catching producers that emit padded
+//! or otherwise out-of-spec output.
+
+use std::io;
+
+use num_traits::PrimInt;
+
+use crate::{is_signed, LEB128Codec, CONTINUATION};
+
+/// Whether `bytes`, a single complete encoding, uses the fewest bytes possible.
+pub fn is_canonical(bytes: &[u8], signed: bool) -> bool {
+    match bytes {
+        [] => false,
+        [_] => true,
+        [.., prev, last] => {
+            if signed {
+                let sign = prev & 0x40 != 0;
+                !((*last == 0x00 && !sign) || (*last == 0x7F && sign))
+            } else {
+                *last != 0x00
+            }
+        }
+    }
+}
+
+fn check_encoding<N: PrimInt + LEB128Codec>(bytes: &[u8]) -> bool {
+    let mut readable = bytes;
+    N::leb128_decode(&mut readable).is_ok()
+        && readable.is_empty()
+        && is_canonical(bytes, is_signed::<N>())
+}
+
+/// Passes writes through to `inner` while re-decoding them as a stream of `N`.
+///
+/// A value that is non-minimal or does not fit `N` panics in debug builds.
+/// With [`strict`](Self::strict) it is instead reported as an `InvalidData`
+/// error, in any build, and the offending write is not forwarded.
+pub struct DebugCanonicalWriter<W> {
+    inner: W,
+    check: fn(&[u8]) -> bool,
+    max_len: usize,
+    strict: bool,
+    pending: Vec<u8>,
+}
+
+impl<W: io::Write> DebugCanonicalWriter<W> {
+    pub fn new<N: PrimInt + LEB128Codec>(inner: W) -> Self {
+        let bits = N::zero().count_zeros() as usize;
+        DebugCanonicalWriter {
+            inner,
+            check: check_encoding::<N>,
+            max_len: bits.div_ceil(7),
+            strict: false,
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn violation(&self, bytes: &[u8]) -> Result<(), io::Error> {
+        if self.strict {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("non-canonical encoding {bytes:02X?}"),
+            ))
+        } else if cfg!(debug_assertions) {
+            panic!("non-canonical encoding {bytes:02X?}");
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<W: io::Write> io::Write for DebugCanonicalWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, io::Error> {
+        let mut pending = self.pending.clone();
+        for &byte in buf {
+            pending.push(byte);
+            if byte & CONTINUATION == 0 {
+                if !(self.check)(&pending) {
+                    self.violation(&pending)?;
+                }
+                pending.clear();
+            } else if pending.len() > self.max_len {
+                self.violation(&pending)?;
+                pending.clear();
+            }
+        }
+        self.inner.write_all(buf)?;
+        self.pending = pending;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), io::Error> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Write};
+
+    use super::{is_canonical, DebugCanonicalWriter};
+    use crate::LEB128Encode;
+
+    #[test]
+    fn minimal_encodings() {
+        assert!(is_canonical(&[0x00], false));
+        assert!(is_canonical(&[0x80, 0x01], false));
+        assert!(!is_canonical(&[0x80, 0x00], false));
+        assert!(is_canonical(&[0x80, 0x7F], true));
+        assert!(is_canonical(&[0xC0, 0x00], true));
+        assert!(!is_canonical(&[0xFF, 0x7F], true));
+        assert!(!is_canonical(&[0xBF, 0x00], true));
+    }
+
+    #[test]
+    fn strict_writer_rejects_padding() {
+        let mut writer = DebugCanonicalWriter::new::<u32>(Vec::new()).strict(true);
+        624485u32.leb128_encode(&mut writer).unwrap();
+        writer.write_all(&[0x85]).unwrap();
+        let err = writer.write_all(&[0x00]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = writer.write_all(&[0xFF; 6]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(writer.into_inner(), [0xE5, 0x8E, 0x26, 0x85]);
+    }
+
+    #[test]
+    #[should_panic(expected = "non-canonical")]
+    fn debug_writer_panics() {
+        let mut writer = DebugCanonicalWriter::new::<i8>(Vec::new());
+        let _ = writer.write_all(&[0xFF, 0x7F]);
+    }
+}
@@ -0,0 +1,144 @@
+//! A C ABI over the fixed-width codecs, see `include/leb128.h`. The
+//! `lebase-capi` crate in `capi/` links these into shared and static
+//! libraries.
+//!
+//! Encoders return the number of bytes written, or zero if `cap` is too
+//! small, in which case nothing is written. Decoders return the number of
+//! bytes consumed, or the negated [`ErrorCode`] on failure, as an `isize`,
+//! which is `ptrdiff_t` in C.
+
+use std::{
+    ffi::{c_char, c_int},
+    slice,
+};
+
+use crate::{ErrorCode, LEB128Codec, LEB128Encode};
+
+/// Longest encoding of any type exported here.
+const SCRATCH: usize = 10;
+
+/// # Safety
+///
+/// `out` must be valid for writes of `cap` bytes, or null if `cap` is zero.
+unsafe fn encode<N: LEB128Encode>(value: N, out: *mut u8, cap: usize) -> usize {
+    let mut scratch = [0u8; SCRATCH];
+    let len = match value.leb128_encode(&mut &mut scratch[..]) {
+        Ok(len) => len,
+        Err(_) => return 0,
+    };
+    if out.is_null() || len > cap {
+        return 0;
+    }
+    let out = unsafe { slice::from_raw_parts_mut(out, len) };
+    out.copy_from_slice(&scratch[..len]);
+    len
+}
+
+/// # Safety
+///
+/// `buf` must be valid for reads of `len` bytes and `out_value` for a write.
+unsafe fn decode<N: LEB128Codec>(buf: *const u8, len: usize, out_value: *mut N) -> isize {
+    if buf.is_null() || out_value.is_null() {
+        return -(ErrorCode::InvalidInput.code() as isize);
+    }
+    let bytes = unsafe { slice::from_raw_parts(buf, len) };
+    let mut rest = bytes;
+    match N::leb128_decode(&mut rest) {
+        Ok(value) => {
+            unsafe { out_value.write(value) };
+            (bytes.len() - rest.len()) as isize
+        }
+        Err(e) => -(ErrorCode::of(&e).code() as isize),
+    }
+}
+
+macro_rules! exports {
+    ($($ty:ty => $encode:ident, $decode:ident),*) => {
+        $(
+            /// # Safety
+            ///
+            /// `out` must be valid for writes of `cap` bytes.
+            #[no_mangle]
+            pub unsafe extern "C" fn $encode(value: $ty, out: *mut u8, cap: usize) -> usize {
+                unsafe { encode(value, out, cap) }
+            }
+
+            /// # Safety
+            ///
+            /// `buf` must be valid for reads of `len` bytes and `out_value`
+            /// for a write.
+            #[no_mangle]
+            pub unsafe extern "C" fn $decode(buf: *const u8, len: usize, out_value: *mut $ty) -> isize {
+                unsafe { decode(buf, len, out_value) }
+            }
+        )*
+    };
+}
+
+exports!(
+    u32 => leb128_encode_u32, leb128_decode_u32,
+    u64 => leb128_encode_u64, leb128_decode_u64,
+    i32 => leb128_encode_i32, leb128_decode_i32,
+    i64 => leb128_encode_i64, leb128_decode_i64
+);
+
+/// Returns a static, NUL-terminated description of a negated decoder result.
+#[no_mangle]
+pub extern "C" fn leb128_strerror(code: c_int) -> *const c_char {
+    let message: &'static [u8] = match ErrorCode::from_code(code.unsigned_abs()) {
+        Some(ErrorCode::UnexpectedEof) => b"unexpected end of input\0",
+        Some(ErrorCode::Overflow) => b"encoded value overflows the target type\0",
+        Some(ErrorCode::TooManyBytes) => b"encoding has too many bytes\0",
+        Some(ErrorCode::InvalidInput) => b"invalid argument\0",
+        Some(_) => b"invalid data\0",
+        None if code == 0 => b"success\0",
+        None => b"unknown error\0",
+    };
+    message.as_ptr().cast()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use super::*;
+
+    #[test]
+    fn round_trips_through_the_abi() {
+        let mut buf = [0u8; 10];
+        let len = unsafe { leb128_encode_u64(624485, buf.as_mut_ptr(), buf.len()) };
+        assert_eq!(&buf[..len], [0xE5, 0x8E, 0x26]);
+        let mut value = 0u64;
+        assert_eq!(
+            unsafe { leb128_decode_u64(buf.as_ptr(), len, &mut value) },
+            3
+        );
+        assert_eq!(value, 624485);
+
+        let len = unsafe { leb128_encode_i32(-123456, buf.as_mut_ptr(), buf.len()) };
+        let mut signed = 0i32;
+        assert_eq!(
+            unsafe { leb128_decode_i32(buf.as_ptr(), len, &mut signed) },
+            len as isize
+        );
+        assert_eq!(signed, -123456);
+    }
+
+    #[test]
+    fn reports_failures() {
+        let mut buf = [0u8; 2];
+        assert_eq!(
+            unsafe { leb128_encode_u32(u32::MAX, buf.as_mut_ptr(), buf.len()) },
+            0
+        );
+        assert_eq!(buf, [0, 0]);
+
+        let mut value = 0u32;
+        let truncated = [0x80u8, 0x80];
+        let code = unsafe { leb128_decode_u32(truncated.as_ptr(), 2, &mut value) };
+        assert_eq!(code, -(ErrorCode::UnexpectedEof.code() as isize));
+        let code = unsafe { leb128_decode_u32(ptr::null(), 0, &mut value) };
+        assert_eq!(code, -(ErrorCode::InvalidInput.code() as isize));
+        assert!(!leb128_strerror(code as c_int).is_null());
+    }
+}
@@ -0,0 +1,294 @@
+//! Decoding with a check on the decoded value.
+
+use std::{any, fmt, io, ops::RangeBounds};
+
+use num_traits::PrimInt;
+
+use crate::{from_group, is_signed, Error, LEB128Codec, CONTINUATION};
+
+/// Decodes a value and fails with [`Error::OutOfRange`] unless it lies in
+/// `range`, for counts, versions and discriminants that must be rejected
+/// before use. The value's bytes are consumed either way.
+pub fn decode_in_range<N, R>(reader: &mut R, range: impl RangeBounds<N>) -> Result<N, io::Error>
+where
+    N: LEB128Codec + PartialOrd,
+    R: Sized + io::Read,
+{
+    let value = N::leb128_decode(reader)?;
+    if !range.contains(&value) {
+        return Err(Error::OutOfRange.into());
+    }
+    Ok(value)
+}
+
+/// A value decoded by [`decode_as`] that does not fit the requested type,
+/// carried inside the returned `InvalidData` error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NarrowingError<W> {
+    pub value: W,
+    /// Name of the type the value was converted to.
+    pub target: &'static str,
+}
+
+impl<W> NarrowingError<W>
+where
+    W: fmt::Debug + fmt::Display + Send + Sync + 'static,
+{
+    pub fn from_io_error(err: &io::Error) -> Option<&NarrowingError<W>> {
+        err.get_ref()?.downcast_ref()
+    }
+}
+
+impl<W: fmt::Display> fmt::Display for NarrowingError<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "decoded value {} does not fit {}",
+            self.value, self.target
+        )
+    }
+}
+
+impl<W: fmt::Debug + fmt::Display> std::error::Error for NarrowingError<W> {}
+
+/// Decodes a `W` and converts it to `N`, so that a value too large for `N`
+/// is reported as a [`NarrowingError`] holding the decoded value.
+///
+/// ```
+/// use lebase::checked::{decode_as, NarrowingError};
+///
+/// let err = decode_as::<u64, u8, _>(&mut &[0xAC, 0x02][..]).unwrap_err();
+/// let narrowing = NarrowingError::<u64>::from_io_error(&err).unwrap();
+/// assert_eq!(narrowing.value, 300);
+/// assert_eq!(err.to_string(), "decoded value 300 does not fit u8");
+/// ```
+pub fn decode_as<W, N, R>(reader: &mut R) -> Result<N, io::Error>
+where
+    W: LEB128Codec + Copy + fmt::Debug + fmt::Display + Send + Sync + 'static,
+    N: TryFrom<W>,
+    R: Sized + io::Read,
+{
+    let value = W::leb128_decode(reader)?;
+    N::try_from(value).map_err(|_| {
+        let target = any::type_name::<N>();
+        io::Error::new(io::ErrorKind::InvalidData, NarrowingError { value, target })
+    })
+}
+
+/// Decodes a value of any length, clamping it to `N::MIN` or `N::MAX` if it
+/// does not fit instead of failing. Only I/O errors, including running out
+/// of input inside the value, are reported.
+pub fn decode_saturating<N, R>(reader: &mut R) -> Result<N, io::Error>
+where
+    N: PrimInt,
+    R: Sized + io::Read,
+{
+    let bits = N::zero().count_zeros() as usize;
+    let signed = is_signed::<N>();
+    // Bits from here up are the sign for signed types and must be clear for
+    // unsigned ones.
+    let top = bits - signed as usize;
+    let mut num = N::zero();
+    let mut any_high_one = false;
+    let mut any_high_zero = false;
+    let mut shift = 0;
+    let mut buffer = [0u8; 1];
+    let group = loop {
+        reader.read_exact(&mut buffer)?;
+        let group = buffer[0] & !CONTINUATION;
+        let low_bits = top.saturating_sub(shift).min(7);
+        if low_bits > 0 {
+            let low = group & ((1 << low_bits) - 1);
+            num = num | from_group::<N>(low) << shift;
+        }
+        if low_bits < 7 {
+            let high = group >> low_bits;
+            any_high_one |= high != 0;
+            any_high_zero |= high != 0x7F >> low_bits;
+        }
+        shift += 7;
+        if buffer[0] & CONTINUATION == 0 {
+            break group;
+        }
+    };
+    let negative = signed && group & 0x40 != 0;
+    if negative {
+        if any_high_zero {
+            return Ok(N::min_value());
+        }
+        return Ok(num | !N::zero() << shift.min(top));
+    }
+    if any_high_one {
+        return Ok(N::max_value());
+    }
+    Ok(num)
+}
+
+/// Decodes a value of logical width `BITS` into `N`, such as the wasm
+/// `varuint1`, `varint7` and `varint33` immediates. The encoding may take at
+/// most `BITS.div_ceil(7)` bytes, failing with [`Error::TooManyBytes`], and
+/// the value must fit `BITS` bits, signed if `N` is, failing with
+/// [`Error::Overflow`].
+///
+/// ```
+/// use lebase::checked::decode_bits;
+///
+/// let block_type = decode_bits::<33, i64, _>(&mut &[0x40][..]).unwrap();
+/// assert_eq!(block_type, -64);
+/// assert!(decode_bits::<1, u8, _>(&mut &[0x02][..]).is_err());
+/// ```
+///
+/// # Panics
+///
+/// If `BITS` is zero or wider than `N`.
+pub fn decode_bits<const BITS: usize, N, R>(reader: &mut R) -> Result<N, io::Error>
+where
+    N: PrimInt,
+    R: Sized + io::Read,
+{
+    let bits = N::zero().count_zeros() as usize;
+    assert!(
+        BITS > 0 && BITS <= bits,
+        "{BITS} bits do not fit the target type"
+    );
+    let signed = is_signed::<N>();
+    let mut num = N::zero();
+    let mut buffer = [0u8; 1];
+    for index in 0..BITS.div_ceil(7) {
+        reader.read_exact(&mut buffer)?;
+        let ends = buffer[0] & CONTINUATION == 0;
+        let group = buffer[0] & !CONTINUATION;
+        let shift = index * 7;
+        if shift + 7 >= BITS {
+            if !ends {
+                return Err(Error::TooManyBytes.at(index, shift));
+            }
+            // The bits above the value, and the sign bit for signed types,
+            // must all match.
+            let used = BITS - shift - signed as usize;
+            let high = group >> used;
+            if high != 0 && !(signed && high == 0x7F >> used) {
+                return Err(Error::Overflow.at(index, shift));
+            }
+        }
+        num = num | from_group::<N>(group) << shift;
+        if ends {
+            if signed && group & 0x40 != 0 && shift + 7 < bits {
+                num = num | !N::zero() << (shift + 7);
+            }
+            return Ok(num);
+        }
+    }
+    unreachable!("the last group either ends the value or fails")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_as, decode_bits, decode_in_range, decode_saturating, NarrowingError};
+    use crate::{Error, LEB128Encode};
+
+    #[test]
+    fn rejects_values_outside_the_range() {
+        let bytes = [0x03, 0x7F, 0xE5, 0x8E, 0x26];
+        let mut readable = &bytes[..];
+        assert_eq!(decode_in_range::<u8, _>(&mut readable, 1..=3).unwrap(), 3);
+        let err = decode_in_range::<i8, _>(&mut readable, 0..).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::OutOfRange));
+        assert_eq!(
+            decode_in_range::<u32, _>(&mut readable, ..).unwrap(),
+            624485
+        );
+    }
+
+    #[test]
+    fn saturates_out_of_range_values() {
+        let mut bytes = Vec::new();
+        for value in [300i64, -300, 200, -1, 127, -128, 0, i64::MIN] {
+            value.leb128_encode(&mut bytes).unwrap();
+        }
+        let mut readable = &bytes[..];
+        let decoded: Vec<i8> = (0..8)
+            .map(|_| decode_saturating(&mut readable).unwrap())
+            .collect();
+        assert_eq!(decoded, [127, -128, 127, -1, 127, -128, 0, -128]);
+
+        let mut readable = &[0xAC, 0x02, 0x80, 0x80, 0x80, 0x00, 0x05][..];
+        assert_eq!(decode_saturating::<u8, _>(&mut readable).unwrap(), 255);
+        assert_eq!(decode_saturating::<u8, _>(&mut readable).unwrap(), 0);
+        assert_eq!(readable, [0x05]);
+
+        for value in [0i32, -1, 64, -65, i32::MAX, i32::MIN, 624485, -624485] {
+            let mut bytes = Vec::new();
+            value.leb128_encode(&mut bytes).unwrap();
+            assert_eq!(decode_saturating::<i32, _>(&mut &bytes[..]).unwrap(), value);
+            let clamped = value.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+            assert_eq!(
+                decode_saturating::<i16, _>(&mut &bytes[..]).unwrap(),
+                clamped
+            );
+        }
+        for value in [0u128, u128::MAX, 1 << 70] {
+            let mut bytes = Vec::new();
+            value.leb128_encode(&mut bytes).unwrap();
+            assert_eq!(
+                decode_saturating::<u128, _>(&mut &bytes[..]).unwrap(),
+                value
+            );
+            let clamped = value.min(u64::MAX as u128) as u64;
+            assert_eq!(
+                decode_saturating::<u64, _>(&mut &bytes[..]).unwrap(),
+                clamped
+            );
+        }
+    }
+
+    #[test]
+    fn narrows_with_the_original_value() {
+        let bytes = [0x7F, 0x80, 0x7E];
+        let mut readable = &bytes[..];
+        assert_eq!(decode_as::<i64, i8, _>(&mut readable).unwrap(), -1);
+        let err = decode_as::<i64, i8, _>(&mut readable).unwrap_err();
+        assert_eq!(
+            NarrowingError::<i64>::from_io_error(&err),
+            Some(&NarrowingError {
+                value: -256,
+                target: "i8"
+            })
+        );
+        assert!(NarrowingError::<u64>::from_io_error(&err).is_none());
+        assert_eq!(Error::from_io_error(&err), None);
+    }
+
+    #[test]
+    fn validates_logical_widths() {
+        assert_eq!(decode_bits::<1, u8, _>(&mut &[0x00][..]).unwrap(), 0);
+        assert_eq!(decode_bits::<1, u32, _>(&mut &[0x01][..]).unwrap(), 1);
+        let err = decode_bits::<1, u32, _>(&mut &[0x81, 0x00][..]).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::TooManyBytes));
+
+        assert_eq!(decode_bits::<7, i8, _>(&mut &[0x7F][..]).unwrap(), -1);
+        assert_eq!(decode_bits::<7, i8, _>(&mut &[0x40][..]).unwrap(), -64);
+        assert_eq!(decode_bits::<7, i32, _>(&mut &[0x3F][..]).unwrap(), 63);
+        let err = decode_bits::<7, i8, _>(&mut &[0x80, 0x00][..]).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::TooManyBytes));
+
+        for value in [0i64, -1, (1 << 32) - 1, -(1 << 32), 1 << 20] {
+            let bytes = value.to_leb128_vec();
+            assert_eq!(decode_bits::<33, i64, _>(&mut &bytes[..]).unwrap(), value);
+        }
+        for value in [1i64 << 32, -(1 << 32) - 1] {
+            let err = decode_bits::<33, i64, _>(&mut &value.to_leb128_vec()[..]).unwrap_err();
+            assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+        }
+        for value in [u32::MAX, 0] {
+            let bytes = value.to_leb128_vec();
+            assert_eq!(decode_bits::<32, u32, _>(&mut &bytes[..]).unwrap(), value);
+        }
+        assert_eq!(
+            decode_bits::<128, u128, _>(&mut &u128::MAX.to_leb128_vec()[..]).unwrap(),
+            u128::MAX
+        );
+        let err = decode_bits::<31, u32, _>(&mut &(1u32 << 31).to_leb128_vec()[..]).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+    }
+}
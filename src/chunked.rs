@@ -0,0 +1,133 @@
+//! LEB128 generalized to multi-byte chunks. Each chunk is `BYTES` bytes
+//! stored little endian; its top bit is the continuation flag and the other
+//! `8 * BYTES - 1` bits hold the next group of the value, low group first.
+//! `ChunkedLeb<1>` is plain ULEB128.
+
+use std::io;
+
+use crate::{varint::Varint, Error};
+
+pub struct ChunkedLeb<const BYTES: usize>;
+
+/// 15-bit groups in two-byte chunks.
+pub type Leb16 = ChunkedLeb<2>;
+/// 31-bit groups in four-byte chunks.
+pub type Leb32 = ChunkedLeb<4>;
+/// 63-bit groups in eight-byte chunks.
+pub type Leb64 = ChunkedLeb<8>;
+
+impl<const BYTES: usize> ChunkedLeb<BYTES> {
+    const PAYLOAD_BITS: u32 = 8 * BYTES as u32 - 1;
+    const PAYLOAD_MASK: u64 = (1 << Self::PAYLOAD_BITS) - 1;
+}
+
+impl<const BYTES: usize> Varint for ChunkedLeb<BYTES> {
+    const MAX_LEN: usize = 64usize.div_ceil(8 * BYTES - 1) * BYTES;
+
+    fn encoded_len(value: u64) -> usize {
+        let bits = 64 - value.leading_zeros();
+        bits.div_ceil(Self::PAYLOAD_BITS).max(1) as usize * BYTES
+    }
+
+    fn encode<W>(value: u64, writer: &mut W) -> Result<usize, io::Error>
+    where
+        W: Sized + io::Write,
+    {
+        const { assert!(BYTES >= 1 && BYTES <= 8, "chunks are 1 to 8 bytes") };
+        let mut value = value;
+        let mut written = 0;
+        loop {
+            let mut chunk = value & Self::PAYLOAD_MASK;
+            value >>= Self::PAYLOAD_BITS;
+            if value != 0 {
+                chunk |= 1 << Self::PAYLOAD_BITS;
+            }
+            writer.write_all(&chunk.to_le_bytes()[..BYTES])?;
+            written += BYTES;
+            if value == 0 {
+                break Ok(written);
+            }
+        }
+    }
+
+    /// Fails with [`Error::Overflow`] once the value no longer fits a `u64`.
+    fn decode<R>(reader: &mut R) -> Result<u64, io::Error>
+    where
+        R: Sized + io::Read,
+    {
+        const { assert!(BYTES >= 1 && BYTES <= 8, "chunks are 1 to 8 bytes") };
+        let mut value = 0u64;
+        let mut shift = 0;
+        loop {
+            if shift >= 64 {
+                return Err(Error::Overflow.into());
+            }
+            let mut bytes = [0u8; 8];
+            reader.read_exact(&mut bytes[..BYTES])?;
+            let chunk = u64::from_le_bytes(bytes);
+            let payload = chunk & Self::PAYLOAD_MASK;
+            if payload != 0 && payload.leading_zeros() < shift {
+                return Err(Error::Overflow.into());
+            }
+            value |= payload << shift;
+            if chunk >> Self::PAYLOAD_BITS == 0 {
+                break Ok(value);
+            }
+            shift += Self::PAYLOAD_BITS;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChunkedLeb, Leb16, Leb32, Leb64};
+    use crate::{varint::Varint, Error, LEB128Encode};
+
+    fn round_trip<V: Varint>() {
+        for value in [
+            0,
+            1,
+            0x7FFF,
+            0x8000,
+            1 << 31,
+            (1 << 63) - 1,
+            1 << 63,
+            u64::MAX,
+        ] {
+            let mut bytes = Vec::new();
+            let len = V::encode(value, &mut bytes).unwrap();
+            assert_eq!((len, V::encoded_len(value)), (bytes.len(), len));
+            assert!(len <= V::MAX_LEN);
+            assert_eq!(V::decode(&mut &bytes[..]).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn chunk_sizes_round_trip() {
+        round_trip::<ChunkedLeb<1>>();
+        round_trip::<Leb16>();
+        round_trip::<ChunkedLeb<3>>();
+        round_trip::<Leb32>();
+        round_trip::<Leb64>();
+        assert_eq!(Leb16::MAX_LEN, 10);
+        assert_eq!(Leb64::MAX_LEN, 16);
+    }
+
+    #[test]
+    fn layout() {
+        let mut bytes = Vec::new();
+        Leb16::encode(0x8000, &mut bytes).unwrap();
+        assert_eq!(bytes, [0x00, 0x80, 0x01, 0x00]);
+        bytes.clear();
+        ChunkedLeb::<1>::encode(624485, &mut bytes).unwrap();
+        let mut leb = Vec::new();
+        624485u64.leb128_encode(&mut leb).unwrap();
+        assert_eq!(bytes, leb);
+
+        let overflow = [
+            0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x02, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        let err = Leb64::decode(&mut &overflow[..]).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+    }
+}
@@ -0,0 +1,299 @@
+//! Count-prefixed collections: a ULEB128 element count followed by each element.
+//! Map entries are a key followed by its value, in ascending key order.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::{BuildHasher, Hash},
+    io,
+};
+
+use crate::{bytes::decode_len, Error, LEB128Codec, LEB128Encode};
+
+/// Cap on the capacity reserved from an untrusted count before any elements
+/// have been decoded.
+const MAX_PREALLOCATE: usize = 1024;
+
+pub fn encode_slice<N, W>(values: &[N], writer: &mut W) -> Result<usize, io::Error>
+where
+    N: LEB128Encode + Copy,
+    W: Sized + io::Write,
+{
+    let mut written = values.len().leb128_encode(writer)?;
+    for &num in values {
+        written += num.leb128_encode(writer)?;
+    }
+    Ok(written)
+}
+
+fn decode_elements<N, R>(reader: &mut R, count: usize) -> Result<Vec<N>, io::Error>
+where
+    N: LEB128Codec,
+    R: Sized + io::Read,
+{
+    let mut values = Vec::with_capacity(count.min(MAX_PREALLOCATE));
+    for _ in 0..count {
+        values.push(N::leb128_decode(reader)?);
+    }
+    Ok(values)
+}
+
+/// Decodes a count-prefixed sequence of at most `max_count` elements, failing
+/// with [`Error::TooLong`](crate::Error::TooLong) on larger counts.
+pub fn decode_vec<N, R>(reader: &mut R, max_count: usize) -> Result<Vec<N>, io::Error>
+where
+    N: LEB128Codec,
+    R: Sized + io::Read,
+{
+    let count = decode_len(reader, max_count)?;
+    decode_elements(reader, count)
+}
+
+/// Decoding does not limit the element count; use [`decode_vec`] for
+/// untrusted input.
+impl<N: LEB128Codec> LEB128Codec for Vec<N> {
+    fn leb128_decode<R>(reader: &mut R) -> Result<Self, io::Error>
+    where
+        R: Sized + io::Read,
+        Self: Sized,
+    {
+        let count = usize::leb128_decode(reader)?;
+        decode_elements(reader, count)
+    }
+}
+
+impl<N: LEB128Encode> LEB128Encode for Vec<N> {
+    fn leb128_encode<W>(self, writer: &mut W) -> Result<usize, io::Error>
+    where
+        W: Sized + io::Write,
+        Self: Sized,
+    {
+        let mut written = self.len().leb128_encode(writer)?;
+        for num in self {
+            written += num.leb128_encode(writer)?;
+        }
+        Ok(written)
+    }
+}
+
+fn encode_entries<K, V, W, I>(len: usize, entries: I, writer: &mut W) -> Result<usize, io::Error>
+where
+    K: LEB128Encode,
+    V: LEB128Encode,
+    W: Sized + io::Write,
+    I: IntoIterator<Item = (K, V)>,
+{
+    let mut written = len.leb128_encode(writer)?;
+    for (key, value) in entries {
+        written += key.leb128_encode(writer)?;
+        written += value.leb128_encode(writer)?;
+    }
+    Ok(written)
+}
+
+/// Decodes `count` entries, calling `insert` for each; `insert` returns
+/// whether the key was new.
+fn decode_entries<K, V, R, F>(reader: &mut R, count: usize, mut insert: F) -> Result<(), io::Error>
+where
+    K: LEB128Codec,
+    V: LEB128Codec,
+    R: Sized + io::Read,
+    F: FnMut(K, V) -> bool,
+{
+    for _ in 0..count {
+        let key = K::leb128_decode(reader)?;
+        let value = V::leb128_decode(reader)?;
+        if !insert(key, value) {
+            return Err(Error::DuplicateKey.into());
+        }
+    }
+    Ok(())
+}
+
+fn decode_btree_entries<K, V, R>(reader: &mut R, count: usize) -> Result<BTreeMap<K, V>, io::Error>
+where
+    K: LEB128Codec + Ord,
+    V: LEB128Codec,
+    R: Sized + io::Read,
+{
+    let mut map = BTreeMap::new();
+    decode_entries(reader, count, |k, v| map.insert(k, v).is_none())?;
+    Ok(map)
+}
+
+fn decode_hash_entries<K, V, S, R>(
+    reader: &mut R,
+    count: usize,
+) -> Result<HashMap<K, V, S>, io::Error>
+where
+    K: LEB128Codec + Eq + Hash,
+    V: LEB128Codec,
+    S: BuildHasher + Default,
+    R: Sized + io::Read,
+{
+    let mut map = HashMap::with_capacity_and_hasher(count.min(MAX_PREALLOCATE), S::default());
+    decode_entries(reader, count, |k, v| map.insert(k, v).is_none())?;
+    Ok(map)
+}
+
+/// Decodes a map of at most `max_count` entries. Repeated keys fail with
+/// [`Error::DuplicateKey`].
+pub fn decode_btree_map<K, V, R>(
+    reader: &mut R,
+    max_count: usize,
+) -> Result<BTreeMap<K, V>, io::Error>
+where
+    K: LEB128Codec + Ord,
+    V: LEB128Codec,
+    R: Sized + io::Read,
+{
+    let count = decode_len(reader, max_count)?;
+    decode_btree_entries(reader, count)
+}
+
+/// Decodes a map of at most `max_count` entries. Repeated keys fail with
+/// [`Error::DuplicateKey`].
+pub fn decode_hash_map<K, V, S, R>(
+    reader: &mut R,
+    max_count: usize,
+) -> Result<HashMap<K, V, S>, io::Error>
+where
+    K: LEB128Codec + Eq + Hash,
+    V: LEB128Codec,
+    S: BuildHasher + Default,
+    R: Sized + io::Read,
+{
+    let count = decode_len(reader, max_count)?;
+    decode_hash_entries(reader, count)
+}
+
+impl<K: LEB128Codec + Ord, V: LEB128Codec> LEB128Codec for BTreeMap<K, V> {
+    fn leb128_decode<R>(reader: &mut R) -> Result<Self, io::Error>
+    where
+        R: Sized + io::Read,
+        Self: Sized,
+    {
+        let count = usize::leb128_decode(reader)?;
+        decode_btree_entries(reader, count)
+    }
+}
+
+impl<K: LEB128Encode + Ord, V: LEB128Encode> LEB128Encode for BTreeMap<K, V> {
+    fn leb128_encode<W>(self, writer: &mut W) -> Result<usize, io::Error>
+    where
+        W: Sized + io::Write,
+        Self: Sized,
+    {
+        encode_entries(self.len(), self, writer)
+    }
+}
+
+/// Entries are sorted by key before encoding, so equal maps always produce
+/// the same bytes regardless of hasher state.
+impl<K, V, S> LEB128Codec for HashMap<K, V, S>
+where
+    K: LEB128Codec + Ord + Hash,
+    V: LEB128Codec,
+    S: BuildHasher + Default,
+{
+    fn leb128_decode<R>(reader: &mut R) -> Result<Self, io::Error>
+    where
+        R: Sized + io::Read,
+        Self: Sized,
+    {
+        let count = usize::leb128_decode(reader)?;
+        decode_hash_entries(reader, count)
+    }
+}
+
+impl<K, V, S> LEB128Encode for HashMap<K, V, S>
+where
+    K: LEB128Encode + Ord + Hash,
+    V: LEB128Encode,
+{
+    fn leb128_encode<W>(self, writer: &mut W) -> Result<usize, io::Error>
+    where
+        W: Sized + io::Write,
+        Self: Sized,
+    {
+        let mut entries: Vec<(K, V)> = self.into_iter().collect();
+        entries.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        encode_entries(entries.len(), entries, writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{BTreeMap, HashMap},
+        hash::{BuildHasher, DefaultHasher, RandomState},
+        io,
+    };
+
+    use super::{decode_btree_map, decode_hash_map, decode_vec, encode_slice};
+    use crate::{Error, LEB128Codec, LEB128Encode};
+
+    #[test]
+    fn vec_trips() {
+        let values = vec![-1i32, 624485, 0, i32::MIN];
+        let mut buf = Vec::new();
+        values.clone().leb128_encode(&mut buf).unwrap();
+        let mut from_slice = Vec::new();
+        encode_slice(&values, &mut from_slice).unwrap();
+        assert_eq!(buf, from_slice);
+        assert_eq!(buf[0], 4);
+        assert_eq!(Vec::<i32>::leb128_decode(&mut &buf[..]).unwrap(), values);
+        assert_eq!(decode_vec::<i32, _>(&mut &buf[..], 4).unwrap(), values);
+    }
+
+    #[test]
+    fn count_cap() {
+        let err = decode_vec::<u8, _>(&mut &[5, 1, 2, 3, 4, 5][..], 4).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::TooLong));
+        let huge = [0xFF, 0xFF, 0xFF, 0xFF, 0x0F, 1];
+        let err = Vec::<u8>::leb128_decode(&mut &huge[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn maps_encode_sorted() {
+        let pairs = [(300u32, -1i8), (1, 2), (70000, 0)];
+        let hash: HashMap<u32, i8> = pairs.into_iter().collect();
+        let btree: BTreeMap<u32, i8> = pairs.into_iter().collect();
+        let mut from_hash = Vec::new();
+        hash.clone().leb128_encode(&mut from_hash).unwrap();
+        let mut from_btree = Vec::new();
+        btree.clone().leb128_encode(&mut from_btree).unwrap();
+        assert_eq!(from_hash, from_btree);
+        assert_eq!(from_hash, [3, 1, 2, 0xAC, 0x02, 0x7F, 0xF0, 0xA2, 0x04, 0]);
+        assert_eq!(
+            HashMap::<u32, i8>::leb128_decode(&mut &from_hash[..]).unwrap(),
+            hash
+        );
+        assert_eq!(
+            decode_btree_map::<u32, i8, _>(&mut &from_hash[..], 3).unwrap(),
+            btree
+        );
+
+        // Encoding needs no way to build a fresh hasher.
+        struct Seeded(RandomState);
+        impl BuildHasher for Seeded {
+            type Hasher = DefaultHasher;
+            fn build_hasher(&self) -> DefaultHasher {
+                self.0.build_hasher()
+            }
+        }
+        let mut keyed = HashMap::with_hasher(Seeded(RandomState::new()));
+        keyed.extend(pairs);
+        let mut from_keyed = Vec::new();
+        keyed.leb128_encode(&mut from_keyed).unwrap();
+        assert_eq!(from_keyed, from_btree);
+    }
+
+    #[test]
+    fn map_limits() {
+        let err = decode_btree_map::<u8, u8, _>(&mut &[2, 1, 1, 1, 2][..], 2).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::DuplicateKey));
+        let err = decode_hash_map::<u8, u8, RandomState, _>(&mut &[2][..], 1).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::TooLong));
+    }
+}
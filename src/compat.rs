@@ -0,0 +1,249 @@
+//! Drop-in replacements for other crates' APIs, so existing code can switch
+//! to this implementation by changing an import path.
+
+/// Mirrors the `leb128` crate: replace `use leb128;` with
+/// `use lebase::compat::leb128;`.
+///
+/// ```
+/// use lebase::compat::leb128;
+///
+/// let mut buf = Vec::new();
+/// leb128::write::unsigned(&mut buf, 624485).unwrap();
+/// leb128::write::signed(&mut buf, -2).unwrap();
+/// let mut readable = &buf[..];
+/// assert_eq!(leb128::read::unsigned(&mut readable).unwrap(), 624485);
+/// assert_eq!(leb128::read::signed(&mut readable).unwrap(), -2);
+/// ```
+pub mod leb128 {
+    pub const CONTINUATION_BIT: u8 = crate::CONTINUATION;
+    pub const SIGN_BIT: u8 = 1 << 6;
+
+    #[inline]
+    pub fn low_bits_of_byte(byte: u8) -> u8 {
+        byte & !CONTINUATION_BIT
+    }
+
+    #[inline]
+    pub fn low_bits_of_u64(val: u64) -> u8 {
+        low_bits_of_byte(val as u8)
+    }
+
+    pub mod read {
+        use std::{fmt, io};
+
+        use crate::LEB128Codec;
+
+        #[derive(Debug)]
+        pub enum Error {
+            IoError(io::Error),
+            /// The value does not fit 64 bits.
+            Overflow,
+        }
+
+        impl From<io::Error> for Error {
+            fn from(e: io::Error) -> Self {
+                match crate::Error::from_io_error(&e) {
+                    Some(crate::Error::Overflow) => Error::Overflow,
+                    _ => Error::IoError(e),
+                }
+            }
+        }
+
+        impl fmt::Display for Error {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self {
+                    Error::IoError(e) => e.fmt(f),
+                    Error::Overflow => write!(f, "the LEB128 value overflowed"),
+                }
+            }
+        }
+
+        impl std::error::Error for Error {
+            fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                match self {
+                    Error::IoError(e) => Some(e),
+                    Error::Overflow => None,
+                }
+            }
+        }
+
+        pub fn unsigned<R>(r: &mut R) -> Result<u64, Error>
+        where
+            R: ?Sized + io::Read,
+        {
+            Ok(u64::leb128_decode(&mut &mut *r)?)
+        }
+
+        pub fn signed<R>(r: &mut R) -> Result<i64, Error>
+        where
+            R: ?Sized + io::Read,
+        {
+            Ok(i64::leb128_decode(&mut &mut *r)?)
+        }
+    }
+
+    pub mod write {
+        use std::io;
+
+        use crate::LEB128Encode;
+
+        /// Returns the number of bytes written.
+        pub fn unsigned<W>(w: &mut W, val: u64) -> Result<usize, io::Error>
+        where
+            W: ?Sized + io::Write,
+        {
+            val.leb128_encode(&mut &mut *w)
+        }
+
+        /// Returns the number of bytes written.
+        pub fn signed<W>(w: &mut W, val: i64) -> Result<usize, io::Error>
+        where
+            W: ?Sized + io::Write,
+        {
+            val.leb128_encode(&mut &mut *w)
+        }
+    }
+}
+
+/// Mirrors the `VarInt`, `VarIntReader` and `VarIntWriter` traits of the
+/// `integer-encoding` crate, for code generic over them. Unlike that crate,
+/// signed values use SLEB128 rather than zigzag encoding.
+///
+/// ```
+/// use lebase::compat::integer_encoding::{VarInt, VarIntReader, VarIntWriter};
+///
+/// let mut buf = Vec::new();
+/// buf.write_varint(624485u32).unwrap();
+/// assert_eq!(624485u32.required_space(), buf.len());
+/// assert_eq!(u32::decode_var(&buf), Some((624485, 3)));
+/// assert_eq!((&buf[..]).read_varint::<u64>().unwrap(), 624485);
+/// ```
+#[cfg(feature = "integer_encoding")]
+pub mod integer_encoding {
+    use std::io;
+
+    use crate::{LEB128Codec, LEB128Encode};
+
+    pub trait VarInt: Sized + Copy + LEB128Codec {
+        /// Length of the encoding.
+        fn required_space(self) -> usize;
+
+        /// Decodes a value from the front of `src`, returning it and its
+        /// length, or `None` if `src` does not start with a valid encoding.
+        fn decode_var(src: &[u8]) -> Option<(Self, usize)>;
+
+        /// Encodes into the front of `dst`, returning the encoding's length.
+        ///
+        /// # Panics
+        ///
+        /// If `dst` is shorter than [`required_space`](Self::required_space).
+        fn encode_var(self, dst: &mut [u8]) -> usize;
+
+        fn encode_var_vec(self) -> Vec<u8> {
+            self.to_leb128_vec()
+        }
+    }
+
+    macro_rules! impl_var_int {
+        ($($ty:ty),*) => {
+            $(
+                impl VarInt for $ty {
+                    fn required_space(self) -> usize {
+                        self.leb128_bytes().len()
+                    }
+
+                    fn decode_var(src: &[u8]) -> Option<(Self, usize)> {
+                        let mut rest = src;
+                        let value = <$ty>::leb128_decode(&mut rest).ok()?;
+                        Some((value, src.len() - rest.len()))
+                    }
+
+                    fn encode_var(self, dst: &mut [u8]) -> usize {
+                        assert!(dst.len() >= self.required_space());
+                        let mut out = &mut dst[..];
+                        self.leb128_encode(&mut out)
+                            .expect("checked the space above")
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_var_int!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+    pub trait VarIntReader {
+        fn read_varint<VI: VarInt>(&mut self) -> Result<VI, io::Error>;
+    }
+
+    impl<R: io::Read> VarIntReader for R {
+        fn read_varint<VI: VarInt>(&mut self) -> Result<VI, io::Error> {
+            VI::leb128_decode(self)
+        }
+    }
+
+    pub trait VarIntWriter {
+        /// Returns the number of bytes written.
+        fn write_varint<VI: VarInt>(&mut self, n: VI) -> Result<usize, io::Error>;
+    }
+
+    impl<W: io::Write> VarIntWriter for W {
+        fn write_varint<VI: VarInt>(&mut self, n: VI) -> Result<usize, io::Error> {
+            n.leb128_encode(self)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::leb128;
+
+    #[test]
+    fn mirrors_the_leb128_crate() {
+        let mut buf = Vec::new();
+        assert_eq!(leb128::write::unsigned(&mut buf, u64::MAX).unwrap(), 10);
+        assert_eq!(leb128::write::signed(&mut buf, i64::MIN).unwrap(), 10);
+        let reader: &mut dyn io::Read = &mut &buf[..];
+        assert_eq!(leb128::read::unsigned(reader).unwrap(), u64::MAX);
+        assert_eq!(leb128::read::signed(reader).unwrap(), i64::MIN);
+        assert!(matches!(
+            leb128::read::unsigned(reader),
+            Err(leb128::read::Error::IoError(e)) if e.kind() == io::ErrorKind::UnexpectedEof
+        ));
+
+        let mut overflowing = &[0xFF; 10][..];
+        assert!(matches!(
+            leb128::read::unsigned(&mut overflowing),
+            Err(leb128::read::Error::Overflow)
+        ));
+        assert_eq!(leb128::low_bits_of_u64(0x1FF), 0x7F);
+    }
+
+    #[cfg(feature = "integer_encoding")]
+    #[test]
+    fn mirrors_integer_encoding() {
+        use super::integer_encoding::{VarInt, VarIntReader, VarIntWriter};
+
+        fn round_trip<V: VarInt + PartialEq + std::fmt::Debug>(value: V) {
+            let mut buf = [0u8; 19];
+            let len = value.encode_var(&mut buf);
+            assert_eq!(len, value.required_space());
+            assert_eq!(V::decode_var(&buf[..len]), Some((value, len)));
+            assert_eq!(value.encode_var_vec(), &buf[..len]);
+        }
+
+        round_trip(u64::MAX);
+        round_trip(-1i32);
+        round_trip(i128::MIN);
+        assert_eq!(u8::decode_var(&[0x80, 0x02]), None);
+        assert_eq!(u8::decode_var(&[0x80]), None);
+
+        let mut buf = Vec::new();
+        assert_eq!(buf.write_varint(-65i64).unwrap(), 2);
+        let mut readable = &buf[..];
+        assert_eq!(readable.read_varint::<i16>().unwrap(), -65);
+        let err = readable.read_varint::<u32>().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,195 @@
+//! `const` evaluable encoders and decoders for the common fixed-width types.
+//!
+//! ```
+//! use lebase::const_fn::{decode_u32, encode_u32, ConstEncoded};
+//!
+//! const SIZE: ConstEncoded<5> = encode_u32(624485);
+//! const BYTES: &[u8] = SIZE.as_slice();
+//! assert_eq!(BYTES, &[0xE5, 0x8E, 0x26]);
+//! assert_eq!(decode_u32(BYTES), Some((624485, 3)));
+//! ```
+
+use crate::CONTINUATION;
+
+/// A fixed capacity buffer holding a single encoded value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConstEncoded<const CAP: usize> {
+    bytes: [u8; CAP],
+    len: usize,
+}
+
+impl<const CAP: usize> ConstEncoded<CAP> {
+    pub const fn as_slice(&self) -> &[u8] {
+        self.bytes.split_at(self.len).0
+    }
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+macro_rules! const_unsigned {
+    ($encode:ident, $decode:ident, $ty:ty, $cap:literal) => {
+        pub const fn $encode(value: $ty) -> ConstEncoded<$cap> {
+            let mut bytes = [0u8; $cap];
+            let mut len = 0;
+            let mut num = value;
+            loop {
+                let byte = (num & 0x7F) as u8;
+                num >>= 7;
+                if num == 0 {
+                    bytes[len] = byte;
+                    len += 1;
+                    break;
+                }
+                bytes[len] = byte | CONTINUATION;
+                len += 1;
+            }
+            ConstEncoded { bytes, len }
+        }
+
+        /// Returns the value and the number of bytes read, or `None` if the
+        /// input is truncated or does not fit the type.
+        pub const fn $decode(bytes: &[u8]) -> Option<($ty, usize)> {
+            let bits = <$ty>::BITS;
+            let mut num: $ty = 0;
+            let mut shift = 0;
+            let mut i = 0;
+            while i < bytes.len() {
+                let byte = bytes[i];
+                let low = (byte & !CONTINUATION) as $ty;
+                if shift >= bits || (shift > 0 && (low << shift) >> shift != low) {
+                    return None;
+                }
+                num |= low << shift;
+                shift += 7;
+                i += 1;
+                if byte & CONTINUATION == 0 {
+                    return Some((num, i));
+                }
+            }
+            None
+        }
+    };
+}
+
+macro_rules! const_signed {
+    ($encode:ident, $decode:ident, $ty:ty, $cap:literal) => {
+        pub const fn $encode(value: $ty) -> ConstEncoded<$cap> {
+            let mut bytes = [0u8; $cap];
+            let mut len = 0;
+            let mut num = value;
+            loop {
+                let byte = (num & 0x7F) as u8;
+                num >>= 7;
+                let ends = (num == 0 && byte & 0x40 == 0) || (num == -1 && byte & 0x40 != 0);
+                if ends {
+                    bytes[len] = byte;
+                    len += 1;
+                    break;
+                }
+                bytes[len] = byte | CONTINUATION;
+                len += 1;
+            }
+            ConstEncoded { bytes, len }
+        }
+
+        /// Returns the value and the number of bytes read, or `None` if the
+        /// input is truncated or does not fit the type.
+        pub const fn $decode(bytes: &[u8]) -> Option<($ty, usize)> {
+            let bits = <$ty>::BITS;
+            let mut num: $ty = 0;
+            let mut shift = 0;
+            let mut i = 0;
+            while i < bytes.len() {
+                let byte = bytes[i];
+                let low = (byte & !CONTINUATION) as $ty;
+                if shift >= bits {
+                    return None;
+                }
+                if shift + 7 > bits {
+                    // The bits that do not fit must all be copies of the sign bit.
+                    let sign_extended = ((low << (bits - 7)) >> (bits - 7)) >> (bits - shift - 1);
+                    if sign_extended != 0 && sign_extended != -1 {
+                        return None;
+                    }
+                }
+                num |= low << shift;
+                shift += 7;
+                i += 1;
+                if byte & CONTINUATION == 0 {
+                    if shift < bits && byte & 0x40 != 0 {
+                        num |= -1 << shift;
+                    }
+                    return Some((num, i));
+                }
+            }
+            None
+        }
+    };
+}
+
+const_unsigned!(encode_u32, decode_u32, u32, 5);
+const_unsigned!(encode_u64, decode_u64, u64, 10);
+const_signed!(encode_i32, decode_i32, i32, 5);
+const_signed!(encode_i64, decode_i64, i64, 10);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "std")]
+    use crate::LEB128Codec;
+
+    #[cfg(feature = "std")]
+    fn reference<N: LEB128Codec>(num: N) -> Vec<u8> {
+        let mut buf = Vec::new();
+        num.leb128_encode(&mut buf).unwrap();
+        buf
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn matches_runtime_codec() {
+        for x in [0u64, 1, 0x7F, 0x80, 624485, u32::MAX as u64, u64::MAX] {
+            assert_eq!(encode_u64(x).as_slice(), &reference(x)[..]);
+            assert_eq!(decode_u64(&reference(x)), Some((x, reference(x).len())));
+        }
+        for x in [
+            0i64,
+            -1,
+            63,
+            64,
+            -64,
+            -65,
+            i32::MIN as i64,
+            i64::MIN,
+            i64::MAX,
+        ] {
+            assert_eq!(encode_i64(x).as_slice(), &reference(x)[..]);
+            assert_eq!(decode_i64(&reference(x)), Some((x, reference(x).len())));
+        }
+        for x in [0i32, -1, -128, i32::MIN, i32::MAX] {
+            assert_eq!(encode_i32(x).as_slice(), &reference(x)[..]);
+            assert_eq!(decode_i32(&reference(x)), Some((x, reference(x).len())));
+        }
+        assert_eq!(encode_u32(u32::MAX).as_slice(), &reference(u32::MAX)[..]);
+    }
+
+    #[test]
+    fn rejects_truncated_and_overflowing() {
+        assert_eq!(decode_u32(&[0x80]), None);
+        assert_eq!(decode_u32(&[0xFF, 0xFF, 0xFF, 0xFF, 0x1F]), None);
+        assert_eq!(
+            decode_u32(&[0xFF, 0xFF, 0xFF, 0xFF, 0x0F]),
+            Some((u32::MAX, 5))
+        );
+        assert_eq!(
+            decode_i32(&[0x80, 0x80, 0x80, 0x80, 0x78]),
+            Some((i32::MIN, 5))
+        );
+        assert_eq!(decode_i32(&[0x80, 0x80, 0x80, 0x80, 0x70]), None);
+        assert_eq!(decode_i32(&[0xFF, 0xFF, 0xFF, 0xFF, 0x08]), None);
+    }
+}
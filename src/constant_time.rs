@@ -0,0 +1,158 @@
+//! Encoding and decoding whose running time and memory accesses depend only
+//! on the type's width, for serializing secret values.
+//!
+//! Both functions always process the longest encoding of the type, combining
+//! per-byte results with masks instead of branching on the value. The length
+//! of an encoding still reveals roughly how large the value is once it is
+//! sent; use [`LEB128Padded`](crate::LEB128Padded) with the longest width if
+//! that matters too. As with any constant-time code written in Rust, this is
+//! a best effort that the compiler does not guarantee.
+//!
+//! ```
+//! use lebase::constant_time::{decode_ct, encode_ct};
+//!
+//! let mut out = [0u8; 5];
+//! let len = encode_ct(624485u32, &mut out);
+//! assert_eq!(&out[..len], [0xE5, 0x8E, 0x26]);
+//! assert_eq!(decode_ct::<u32>(&out).unwrap(), (624485, 3));
+//! ```
+
+use std::{hint::black_box, io};
+
+use num_traits::PrimInt;
+
+use crate::{from_group, get_7bits, get_shr, is_signed, max_encoded_len, Error, CONTINUATION};
+
+/// All ones if `bit` is 1, zero if it is 0.
+fn mask<N: PrimInt>(bit: u8) -> N {
+    !N::zero() * from_group::<N>(bit)
+}
+
+/// Encodes `value` into the front of `out`, returning its length. Every
+/// byte `out` has room for up to the type's longest encoding is written,
+/// with zeros after the value.
+///
+/// # Panics
+///
+/// If `out` is shorter than the type's longest encoding.
+pub fn encode_ct<N: PrimInt>(value: N, out: &mut [u8]) -> usize {
+    let max_len = max_encoded_len::<N>();
+    assert!(
+        out.len() >= max_len,
+        "output shorter than the longest encoding"
+    );
+    let shr = get_shr::<N>();
+    let signed = is_signed::<N>();
+    let mut num = value;
+    let mut done = 0u8;
+    let mut len = 0;
+    for slot in out[..max_len].iter_mut() {
+        let group = get_7bits(num);
+        let rest = shr(num, 6 + !signed as u32);
+        num = shr(num, 7);
+        let ends = (rest.is_zero() | (signed & (rest == !N::zero()))) as u8;
+        let active = black_box(done) ^ 1;
+        *slot = (group | (CONTINUATION * (ends ^ 1))) * active;
+        len += active as usize;
+        done |= ends;
+    }
+    len
+}
+
+/// Decodes the value at the start of `bytes`, returning it and its length.
+/// Fails with `UnexpectedEof` if `bytes` ends inside the value and
+/// [`Error::Overflow`] if it does not fit `N`.
+pub fn decode_ct<N: PrimInt>(bytes: &[u8]) -> Result<(N, usize), io::Error> {
+    let bits = N::zero().count_zeros() as usize;
+    let max_len = max_encoded_len::<N>();
+    let signed = is_signed::<N>();
+    let mut num = N::zero();
+    let mut done = 0u8;
+    let mut eof = 0u8;
+    let mut overflow = 0u8;
+    let mut len = 0;
+    for index in 0..max_len {
+        let present = (index < bytes.len()) as u8;
+        let byte = bytes.get(index).copied().unwrap_or(0);
+        let active = black_box(done) ^ 1;
+        eof |= active & (present ^ 1);
+        let group = byte & !CONTINUATION;
+        let ends = (byte >> 7 ^ 1) & active;
+        let shift = index * 7;
+        if index == max_len - 1 {
+            // The bits above the type, and its sign bit if signed, must match.
+            let used = bits - shift - signed as usize;
+            let high = group >> used;
+            let valid = (high == 0) as u8 | (signed & (high == 0x7F >> used)) as u8;
+            overflow |= active & (valid ^ 1 | ends ^ 1);
+        }
+        num = num | (from_group::<N>(group) & mask(active)) << shift;
+        if signed && shift + 7 < bits {
+            let negative = group >> 6 & 1;
+            num = num | !N::zero() << (shift + 7) & mask(ends & negative);
+        }
+        len += active as usize;
+        done |= ends;
+    }
+    if eof != 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    if overflow != 0 {
+        return Err(Error::Overflow.into());
+    }
+    Ok((num, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fmt::Debug, io, mem};
+
+    use num_traits::PrimInt;
+
+    use super::{decode_ct, encode_ct};
+    use crate::{Error, LEB128Codec};
+
+    fn assert_matches_io<N: PrimInt + LEB128Codec + Debug>(value: N) {
+        let expected = value.to_leb128_vec();
+        let mut out = [0xAA; 20];
+        let len = encode_ct(value, &mut out);
+        assert_eq!(&out[..len], expected, "{value:?}");
+        let max_len = (mem::size_of::<N>() * 8).div_ceil(7);
+        assert!(out[len..max_len].iter().all(|&b| b == 0));
+        assert_eq!(out[max_len], 0xAA);
+        assert_eq!(decode_ct::<N>(&out).unwrap(), (value, len));
+        assert_eq!(decode_ct::<N>(&expected).unwrap(), (value, len));
+    }
+
+    #[test]
+    fn matches_the_io_codec() {
+        for value in [0u8, 1, 127, 128, u8::MAX] {
+            assert_matches_io(value);
+        }
+        for value in [0i8, -1, 63, 64, -64, -65, i8::MIN, i8::MAX] {
+            assert_matches_io(value);
+        }
+        for value in [0u32, 624485, u32::MAX] {
+            assert_matches_io(value);
+        }
+        for value in [0i64, -624485, i64::MIN, i64::MAX, 1 << 62] {
+            assert_matches_io(value);
+        }
+        assert_matches_io(u128::MAX);
+        assert_matches_io(i128::MIN);
+    }
+
+    #[test]
+    fn rejects_what_the_io_codec_rejects() {
+        for input in [&[0x80, 0x02][..], &[0xFF, 0x7E], &[0xFF, 0x40]] {
+            let err = decode_ct::<i8>(input).unwrap_err();
+            let expected = i8::leb128_decode(&mut &input[..]).unwrap_err();
+            assert_eq!(Error::from_io_error(&err), Error::from_io_error(&expected));
+        }
+        let err = decode_ct::<u32>(&[0x80, 0x80]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        let overlong = [0x80, 0x80, 0x80, 0x80, 0x80, 0x00];
+        let err = decode_ct::<u32>(&overlong).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+    }
+}
@@ -0,0 +1,75 @@
+//! Delta coding for sorted sequences: each value is stored as its difference
+//! from the previous one, the first as its difference from zero.
+
+use std::io;
+
+use crate::{
+    bulk::{decode_many_into, encode_many},
+    Error,
+};
+
+/// Encodes non-decreasing `values` as deltas, returning the bytes written.
+/// A decreasing step fails with `InvalidInput` before anything is written.
+pub fn encode_deltas<W>(values: &[u64], writer: &mut W) -> Result<usize, io::Error>
+where
+    W: io::Write,
+{
+    let mut previous = 0;
+    let mut deltas = Vec::with_capacity(values.len());
+    for &value in values {
+        let delta = value
+            .checked_sub(previous)
+            .ok_or(io::Error::from(io::ErrorKind::InvalidInput))?;
+        deltas.push(delta);
+        previous = value;
+    }
+    encode_many(&deltas, writer)
+}
+
+/// Fills `out` with the next `out.len()` delta-coded values, returning the
+/// bytes consumed. A running sum past `u64::MAX` fails with
+/// [`Error::Overflow`].
+pub fn decode_deltas<R>(reader: &mut R, out: &mut [u64]) -> Result<usize, io::Error>
+where
+    R: io::BufRead,
+{
+    let consumed = decode_many_into(reader, out)?;
+    let mut sum: u64 = 0;
+    for value in out.iter_mut() {
+        sum = sum.checked_add(*value).ok_or(Error::Overflow)?;
+        *value = sum;
+    }
+    Ok(consumed)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{decode_deltas, encode_deltas};
+    use crate::Error;
+
+    #[test]
+    fn round_trips_sorted_ids() {
+        let ids: Vec<u64> = (0..1000).map(|x| 1_700_000_000_000 + x * 37).collect();
+        let mut bytes = Vec::new();
+        let written = encode_deltas(&ids, &mut bytes).unwrap();
+        assert_eq!(written, bytes.len());
+        assert!(written < 6 + 1000);
+        let mut out = vec![0; ids.len()];
+        assert_eq!(decode_deltas(&mut &bytes[..], &mut out).unwrap(), written);
+        assert_eq!(out, ids);
+    }
+
+    #[test]
+    fn rejects_decreasing_and_overflowing() {
+        let err = encode_deltas(&[5, 4], &mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        let mut bytes = Vec::new();
+        encode_deltas(&[u64::MAX], &mut bytes).unwrap();
+        bytes.push(1);
+        let err = decode_deltas(&mut &bytes[..], &mut [0; 2]).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+    }
+}
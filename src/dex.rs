@@ -0,0 +1,75 @@
+//! The `uleb128p1` variant from Android DEX files: a 32-bit ULEB128 holding
+//! the value plus one, so the `NO_INDEX` sentinel `-1` encodes as a single
+//! zero byte.
+
+use std::io;
+
+use crate::{format::VarintFormat, Error, LEB128Codec, LEB128Encode};
+
+pub const NO_INDEX: i32 = -1;
+
+/// Encodes `value`, failing with `InvalidInput` if it is below `-1`.
+pub fn encode_uleb128p1<W>(value: i32, writer: &mut W) -> Result<usize, io::Error>
+where
+    W: Sized + io::Write,
+{
+    let biased = u32::try_from(value as i64 + 1).map_err(|_| io::ErrorKind::InvalidInput)?;
+    biased.leb128_encode(writer)
+}
+
+/// Decodes a value, failing with [`Error::Overflow`] if it exceeds `i32::MAX`.
+pub fn decode_uleb128p1<R>(reader: &mut R) -> Result<i32, io::Error>
+where
+    R: Sized + io::Read,
+{
+    let biased = u32::leb128_decode(reader)?;
+    i32::try_from(biased as i64 - 1).map_err(|_| Error::Overflow.into())
+}
+
+/// [`VarintFormat`] for `uleb128p1` values from `-1` to `i32::MAX`.
+pub struct Uleb128p1;
+
+impl VarintFormat for Uleb128p1 {
+    fn name(&self) -> &str {
+        "uleb128p1"
+    }
+
+    fn encode(&self, value: i128, out: &mut Vec<u8>) -> Result<usize, io::Error> {
+        let value = i32::try_from(value).map_err(|_| io::ErrorKind::InvalidInput)?;
+        encode_uleb128p1(value, out)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<(i128, usize), io::Error> {
+        let mut readable = bytes;
+        let num = decode_uleb128p1(&mut readable)?;
+        Ok((num.into(), bytes.len() - readable.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{decode_uleb128p1, encode_uleb128p1, NO_INDEX};
+    use crate::Error;
+
+    #[test]
+    fn biased_by_one() {
+        for (value, bytes) in [
+            (NO_INDEX, &[0x00][..]),
+            (0, &[0x01]),
+            (126, &[0x7F]),
+            (127, &[0x80, 0x01]),
+            (i32::MAX, &[0x80, 0x80, 0x80, 0x80, 0x08]),
+        ] {
+            let mut out = Vec::new();
+            assert_eq!(encode_uleb128p1(value, &mut out).unwrap(), bytes.len());
+            assert_eq!(out, bytes);
+            assert_eq!(decode_uleb128p1(&mut &bytes[..]).unwrap(), value);
+        }
+        let err = encode_uleb128p1(-2, &mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let err = decode_uleb128p1(&mut &[0x81, 0x80, 0x80, 0x80, 0x08][..]).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+    }
+}
@@ -0,0 +1,149 @@
+//! Fieldless enums encoded as their discriminant.
+//!
+//! The enum converts to its `#[repr]` type with `From` for encoding and back
+//! with `TryFrom` for decoding, so unknown discriminants are rejected in one
+//! place.
+//!
+//! ```
+//! use lebase::discriminant::{decode_enum, encode_enum, InvalidDiscriminant};
+//!
+//! #[derive(Clone, Copy, Debug, PartialEq)]
+//! #[repr(u8)]
+//! enum Kind {
+//!     Counter = 1,
+//!     Gauge = 2,
+//! }
+//!
+//! impl From<Kind> for u8 {
+//!     fn from(kind: Kind) -> u8 {
+//!         kind as u8
+//!     }
+//! }
+//!
+//! impl TryFrom<u8> for Kind {
+//!     type Error = ();
+//!
+//!     fn try_from(value: u8) -> Result<Kind, ()> {
+//!         match value {
+//!             1 => Ok(Kind::Counter),
+//!             2 => Ok(Kind::Gauge),
+//!             _ => Err(()),
+//!         }
+//!     }
+//! }
+//!
+//! let mut buf = Vec::new();
+//! encode_enum::<u8, _, _>(Kind::Gauge, &mut buf).unwrap();
+//! assert_eq!(decode_enum::<u8, Kind, _>(&mut &buf[..]).unwrap(), Kind::Gauge);
+//! let err = decode_enum::<u8, Kind, _>(&mut &[7][..]).unwrap_err();
+//! assert_eq!(InvalidDiscriminant::<u8>::from_io_error(&err).unwrap().value, 7);
+//! ```
+
+use std::{fmt, io};
+
+use crate::{LEB128Codec, LEB128Encode};
+
+/// A decoded discriminant that names no variant, carried inside the
+/// `InvalidData` error returned by [`decode_enum`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidDiscriminant<D> {
+    pub value: D,
+}
+
+impl<D> InvalidDiscriminant<D>
+where
+    D: fmt::Debug + fmt::Display + Send + Sync + 'static,
+{
+    pub fn from_io_error(err: &io::Error) -> Option<&InvalidDiscriminant<D>> {
+        err.get_ref()?.downcast_ref()
+    }
+}
+
+impl<D: fmt::Display> fmt::Display for InvalidDiscriminant<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no variant has discriminant {}", self.value)
+    }
+}
+
+impl<D: fmt::Debug + fmt::Display> std::error::Error for InvalidDiscriminant<D> {}
+
+/// Encodes `value` as its discriminant of type `D`.
+pub fn encode_enum<D, E, W>(value: E, writer: &mut W) -> Result<usize, io::Error>
+where
+    D: LEB128Encode + From<E>,
+    W: Sized + io::Write,
+{
+    D::from(value).leb128_encode(writer)
+}
+
+/// Decodes a discriminant of type `D` and converts it to `E`, failing with
+/// [`InvalidDiscriminant`] if the conversion does.
+pub fn decode_enum<D, E, R>(reader: &mut R) -> Result<E, io::Error>
+where
+    D: LEB128Codec + Copy + fmt::Debug + fmt::Display + Send + Sync + 'static,
+    E: TryFrom<D>,
+    R: Sized + io::Read,
+{
+    let value = D::leb128_decode(reader)?;
+    E::try_from(value)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, InvalidDiscriminant { value }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{decode_enum, encode_enum, InvalidDiscriminant};
+    use crate::Error;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    #[repr(u16)]
+    enum Opcode {
+        Nop = 0,
+        Call = 0x10,
+        Wide = 0x1000,
+    }
+
+    impl From<Opcode> for u16 {
+        fn from(op: Opcode) -> u16 {
+            op as u16
+        }
+    }
+
+    impl TryFrom<u16> for Opcode {
+        type Error = u16;
+
+        fn try_from(value: u16) -> Result<Opcode, u16> {
+            [Opcode::Nop, Opcode::Call, Opcode::Wide]
+                .into_iter()
+                .find(|&op| op as u16 == value)
+                .ok_or(value)
+        }
+    }
+
+    #[test]
+    fn round_trips_through_the_discriminant() {
+        let mut buf = Vec::new();
+        for op in [Opcode::Nop, Opcode::Call, Opcode::Wide] {
+            encode_enum::<u16, _, _>(op, &mut buf).unwrap();
+        }
+        assert_eq!(buf, [0x00, 0x10, 0x80, 0x20]);
+        let mut reader = &buf[..];
+        for op in [Opcode::Nop, Opcode::Call, Opcode::Wide] {
+            assert_eq!(decode_enum::<u16, Opcode, _>(&mut reader).unwrap(), op);
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_discriminants() {
+        let err = decode_enum::<u16, Opcode, _>(&mut &[0x11][..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let invalid = InvalidDiscriminant::<u16>::from_io_error(&err).unwrap();
+        assert_eq!(invalid.value, 0x11);
+        assert_eq!(err.to_string(), "no variant has discriminant 17");
+
+        // A discriminant too wide for the repr type fails as the integer would.
+        let err = decode_enum::<u16, Opcode, _>(&mut &[0x80, 0x80, 0x04][..]).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+    }
+}
@@ -0,0 +1,90 @@
+//! An annotated table of the values in an encoded buffer, like a hex dump
+//! that understands LEB128.
+//!
+//! ```text
+//! offset    bytes                          value                minimal
+//! 00000000  e5 8e 26                       624485               yes
+//! 00000003  80 00                          0                    no
+//! 00000005  80                             error: failed to fill whole buffer
+//! ```
+
+use std::{fmt, io};
+
+use num_traits::PrimInt;
+
+use crate::{
+    canonical::is_canonical, is_signed, scan::leb128_peek_slice, LEB128Codec, CONTINUATION,
+};
+
+/// Writes one row per value in `bytes`, decoded as `N`. A value that fails to
+/// decode ends the table with a row describing the error; only errors from
+/// `out` are returned.
+pub fn dump<N, W>(bytes: &[u8], out: &mut W) -> Result<(), io::Error>
+where
+    N: PrimInt + LEB128Codec + fmt::Display,
+    W: Sized + io::Write,
+{
+    writeln!(out, "offset    {:<30} {:<20} minimal", "bytes", "value")?;
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let rest = &bytes[offset..];
+        match leb128_peek_slice::<N>(rest) {
+            Ok((value, len)) => {
+                let encoding = &rest[..len];
+                let minimal = if is_canonical(encoding, is_signed::<N>()) {
+                    "yes"
+                } else {
+                    "no"
+                };
+                writeln!(
+                    out,
+                    "{offset:08x}  {:<30} {:<20} {minimal}",
+                    hex_bytes(encoding),
+                    value.to_string()
+                )?;
+                offset += len;
+            }
+            Err(e) => {
+                // Show the bytes of the damaged value, up to its end if any.
+                let len = rest
+                    .iter()
+                    .position(|byte| byte & CONTINUATION == 0)
+                    .map_or(rest.len(), |i| i + 1);
+                writeln!(
+                    out,
+                    "{offset:08x}  {:<30} error: {e}",
+                    hex_bytes(&rest[..len])
+                )?;
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn hex_bytes(bytes: &[u8]) -> String {
+    let pairs: Vec<String> = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    pairs.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::dump;
+
+    #[test]
+    fn renders_values_and_errors() {
+        let mut out = Vec::new();
+        dump::<i32, _>(&[0xE5, 0x8E, 0x26, 0xFF, 0x7F, 0x80], &mut out).unwrap();
+        let table = String::from_utf8(out).unwrap();
+        let rows: Vec<&str> = table.lines().map(str::trim_end).collect();
+        assert_eq!(
+            rows,
+            [
+                "offset    bytes                          value                minimal",
+                "00000000  e5 8e 26                       624485               yes",
+                "00000003  ff 7f                          -1                   no",
+                "00000005  80                             error: failed to fill whole buffer",
+            ]
+        );
+    }
+}
@@ -0,0 +1,277 @@
+//! The recurring varint idioms of DWARF debug information.
+
+use std::io;
+
+use crate::{Error, LEB128Codec, CONTINUATION};
+
+const DW_FORM_IMPLICIT_CONST: u64 = 0x21;
+
+/// The longest ULEB128 or SLEB128 encoding of a 64-bit value.
+pub const MAX_LEB128_LEN: usize = 10;
+
+/// Collects one encoding, failing with [`Error::TooManyBytes`] without
+/// reading further if it runs past [`MAX_LEB128_LEN`] bytes.
+fn read_encoding<R: io::Read>(reader: &mut R) -> Result<([u8; MAX_LEB128_LEN], usize), io::Error> {
+    let mut bytes = [0u8; MAX_LEB128_LEN];
+    for len in 0..MAX_LEB128_LEN {
+        reader.read_exact(&mut bytes[len..=len])?;
+        if bytes[len] & CONTINUATION == 0 {
+            return Ok((bytes, len + 1));
+        }
+    }
+    Err(Error::TooManyBytes.into())
+}
+
+/// Reads an unsigned LEB128 number capped at 64 bits. Encodings longer than
+/// [`MAX_LEB128_LEN`] bytes fail with [`Error::TooManyBytes`]; a tenth byte
+/// carrying bits past bit 63 fails with [`Error::Overflow`].
+pub fn read_uleb128<R: io::Read>(reader: &mut R) -> Result<u64, io::Error> {
+    let (bytes, len) = read_encoding(reader)?;
+    u64::leb128_decode(&mut &bytes[..len])
+}
+
+/// Reads a signed LEB128 number capped at 64 bits, with the same errors as
+/// [`read_uleb128`].
+pub fn read_sleb128<R: io::Read>(reader: &mut R) -> Result<i64, io::Error> {
+    let (bytes, len) = read_encoding(reader)?;
+    i64::leb128_decode(&mut &bytes[..len])
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endian {
+    Little,
+    Big,
+}
+
+/// Whether a unit uses 32-bit or 64-bit offsets, as selected by its initial
+/// length field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Dwarf32,
+    Dwarf64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttributeSpec {
+    pub name: u64,
+    pub form: u64,
+    /// The value stored in the abbreviation for `DW_FORM_implicit_const`.
+    pub implicit_const: Option<i64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Abbreviation {
+    pub code: u64,
+    pub tag: u64,
+    pub has_children: bool,
+    pub attributes: Vec<AttributeSpec>,
+}
+
+/// A reader that tracks how many bytes it has consumed.
+pub struct DwarfReader<R> {
+    inner: R,
+    position: u64,
+    endian: Endian,
+}
+
+impl<R: io::Read> DwarfReader<R> {
+    pub fn new(inner: R, endian: Endian) -> Self {
+        DwarfReader {
+            inner,
+            position: 0,
+            endian,
+        }
+    }
+
+    /// Bytes consumed since the reader was created.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], io::Error> {
+        let mut bytes = [0; N];
+        io::Read::read_exact(self, &mut bytes)?;
+        Ok(bytes)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, io::Error> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, io::Error> {
+        let bytes = self.read_array()?;
+        Ok(match self.endian {
+            Endian::Little => u32::from_le_bytes(bytes),
+            Endian::Big => u32::from_be_bytes(bytes),
+        })
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, io::Error> {
+        let bytes = self.read_array()?;
+        Ok(match self.endian {
+            Endian::Little => u64::from_le_bytes(bytes),
+            Endian::Big => u64::from_be_bytes(bytes),
+        })
+    }
+
+    /// Reads a unit's initial length: a 32-bit length, or `0xffffffff`
+    /// followed by a 64-bit length. The reserved values `0xfffffff0` to
+    /// `0xfffffffe` are rejected.
+    pub fn read_initial_length(&mut self) -> Result<(u64, Format), io::Error> {
+        match self.read_u32()? {
+            0xffff_ffff => Ok((self.read_u64()?, Format::Dwarf64)),
+            len if len >= 0xffff_fff0 => Err(io::Error::from(io::ErrorKind::InvalidData)),
+            len => Ok((len as u64, Format::Dwarf32)),
+        }
+    }
+
+    pub fn read_uleb128(&mut self) -> Result<u64, io::Error> {
+        read_uleb128(self)
+    }
+
+    pub fn read_sleb128(&mut self) -> Result<i64, io::Error> {
+        read_sleb128(self)
+    }
+
+    /// Reads two consecutive ULEB128 values, such as an attribute's name and
+    /// form.
+    pub fn read_uleb_pair(&mut self) -> Result<(u64, u64), io::Error> {
+        Ok((self.read_uleb128()?, self.read_uleb128()?))
+    }
+
+    /// Reads one declaration from a `.debug_abbrev` table, or `None` at the
+    /// null entry ending the table.
+    pub fn read_abbreviation(&mut self) -> Result<Option<Abbreviation>, io::Error> {
+        let code = self.read_uleb128()?;
+        if code == 0 {
+            return Ok(None);
+        }
+        let tag = self.read_uleb128()?;
+        let has_children = match self.read_u8()? {
+            0 => false,
+            1 => true,
+            _ => return Err(io::Error::from(io::ErrorKind::InvalidData)),
+        };
+        let mut attributes = Vec::new();
+        loop {
+            let (name, form) = self.read_uleb_pair()?;
+            if name == 0 && form == 0 {
+                break;
+            }
+            let implicit_const = if form == DW_FORM_IMPLICIT_CONST {
+                Some(self.read_sleb128()?)
+            } else {
+                None
+            };
+            attributes.push(AttributeSpec {
+                name,
+                form,
+                implicit_const,
+            });
+        }
+        Ok(Some(Abbreviation {
+            code,
+            tag,
+            has_children,
+            attributes,
+        }))
+    }
+}
+
+impl<R: io::Read> io::Read for DwarfReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+        let read = self.inner.read(buf)?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_sleb128, read_uleb128, AttributeSpec, DwarfReader, Endian, Format};
+    use crate::Error;
+
+    #[test]
+    fn initial_lengths() {
+        let bytes = [
+            0x10, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0, 0, 0, 0x01,
+        ];
+        let mut reader = DwarfReader::new(&bytes[..], Endian::Little);
+        assert_eq!(reader.read_initial_length().unwrap(), (16, Format::Dwarf32));
+        assert_eq!(
+            reader.read_initial_length().unwrap(),
+            (0x0100_0000_0000_00FF, Format::Dwarf64)
+        );
+        assert_eq!(reader.position(), 16);
+        let reserved = [0xFF, 0xFF, 0xFF, 0xF0];
+        assert!(DwarfReader::new(&reserved[..], Endian::Big)
+            .read_initial_length()
+            .is_err());
+    }
+
+    #[test]
+    fn abbreviation_table() {
+        // DW_TAG_compile_unit with children: DW_AT_producer/DW_FORM_strp and
+        // DW_AT_language/DW_FORM_implicit_const 0x1c, then the null entry.
+        let table = [
+            0x01, 0x11, 0x01, 0x25, 0x0E, 0x13, 0x21, 0x1C, 0x00, 0x00, 0x00,
+        ];
+        let mut reader = DwarfReader::new(&table[..], Endian::Little);
+        let abbrev = reader.read_abbreviation().unwrap().unwrap();
+        assert_eq!(
+            (abbrev.code, abbrev.tag, abbrev.has_children),
+            (1, 0x11, true)
+        );
+        assert_eq!(
+            abbrev.attributes,
+            [
+                AttributeSpec {
+                    name: 0x25,
+                    form: 0x0E,
+                    implicit_const: None
+                },
+                AttributeSpec {
+                    name: 0x13,
+                    form: 0x21,
+                    implicit_const: Some(0x1C)
+                },
+            ]
+        );
+        assert_eq!(reader.read_abbreviation().unwrap(), None);
+        assert_eq!(reader.position(), table.len() as u64);
+    }
+
+    #[test]
+    fn spec_leb128_entry_points() {
+        // Examples from the DWARF 5 specification, section 7.6.
+        for (bytes, value) in [
+            (&[2][..], 2),
+            (&[127], 127),
+            (&[0x80, 1], 128),
+            (&[0x82, 1], 130),
+        ] {
+            assert_eq!(read_uleb128(&mut &bytes[..]).unwrap(), value);
+        }
+        for (bytes, value) in [
+            (&[0x7E][..], -2),
+            (&[0x81, 0x7F], -127),
+            (&[0x80, 0x7F], -128),
+        ] {
+            assert_eq!(read_sleb128(&mut &bytes[..]).unwrap(), value);
+        }
+
+        let mut reader = DwarfReader::new(&[0x80; 11][..], Endian::Little);
+        let err = reader.read_uleb128().unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::TooManyBytes));
+        assert_eq!(reader.position(), 10);
+        let overflow = [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x02];
+        let err = read_uleb128(&mut &overflow[..]).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+        let err = read_sleb128(&mut &overflow[..]).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+    }
+}
@@ -0,0 +1,161 @@
+//! Elias gamma and delta codes, written with [`BitWriter`] and read with
+//! [`BitReader`].
+//!
+//! Gamma writes the bit length `n + 1` of a value as `n` zero bits and a one
+//! bit, then the `n` bits below the value's leading one. Delta writes the bit
+//! length itself in gamma instead, which is shorter for large values. The
+//! low bits go out as one field, least significant first like every other
+//! field of the bitstream. Neither code can represent zero; offset values by
+//! one first if they can be zero.
+//!
+//! ```
+//! use std::num::NonZeroU64;
+//!
+//! use lebase::bitstream::{BitReader, BitWriter};
+//!
+//! let mut writer = BitWriter::new();
+//! writer.write_gamma(NonZeroU64::new(5).unwrap());
+//! writer.write_delta(NonZeroU64::new(1000).unwrap());
+//! assert_eq!(writer.bit_len(), 5 + 16);
+//! let bytes = writer.into_bytes();
+//! let mut reader = BitReader::new(&bytes);
+//! assert_eq!(reader.read_gamma().unwrap().get(), 5);
+//! assert_eq!(reader.read_delta().unwrap().get(), 1000);
+//! ```
+
+use std::{io, num::NonZeroU64};
+
+use crate::{
+    bitstream::{BitReader, BitWriter},
+    Error,
+};
+
+/// Bits after the leading one.
+fn low_width(value: NonZeroU64) -> u32 {
+    63 - value.leading_zeros()
+}
+
+fn low_bits(value: NonZeroU64) -> u64 {
+    value.get() ^ 1 << low_width(value)
+}
+
+impl BitWriter {
+    /// Writes `value` in Elias gamma, taking `2 * n + 1` bits for a value of
+    /// `n + 1` significant bits.
+    pub fn write_gamma(&mut self, value: NonZeroU64) {
+        let width = low_width(value);
+        self.write_bits(0, width);
+        self.write_bit(true);
+        self.write_bits(low_bits(value), width);
+    }
+
+    /// Writes `value` in Elias delta.
+    pub fn write_delta(&mut self, value: NonZeroU64) {
+        let width = low_width(value);
+        let len = NonZeroU64::new(width as u64 + 1).expect("lengths start at one");
+        self.write_gamma(len);
+        self.write_bits(low_bits(value), width);
+    }
+}
+
+impl BitReader<'_> {
+    /// Reads a gamma coded value, failing with [`Error::Overflow`] if it has
+    /// more than 64 significant bits.
+    pub fn read_gamma(&mut self) -> Result<NonZeroU64, io::Error> {
+        let mut width = 0;
+        while !self.read_bit()? {
+            width += 1;
+            if width > 63 {
+                return Err(Error::Overflow.into());
+            }
+        }
+        self.read_high_one(width)
+    }
+
+    /// Reads a delta coded value, failing with [`Error::Overflow`] if it has
+    /// more than 64 significant bits.
+    pub fn read_delta(&mut self) -> Result<NonZeroU64, io::Error> {
+        let len = self.read_gamma()?.get();
+        if len > 64 {
+            return Err(Error::Overflow.into());
+        }
+        self.read_high_one(len as u32 - 1)
+    }
+
+    /// Reads `width` low bits below an implied leading one.
+    fn read_high_one(&mut self, width: u32) -> Result<NonZeroU64, io::Error> {
+        let low = self.read_bits(width)?;
+        Ok(NonZeroU64::new(1 << width | low).expect("the leading one is set"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io, num::NonZeroU64};
+
+    use crate::{
+        bitstream::{BitReader, BitWriter},
+        Error,
+    };
+
+    fn values() -> impl Iterator<Item = NonZeroU64> {
+        [1, 2, 3, 4, 5, 17, 1000, 1 << 32, u64::MAX >> 1, u64::MAX]
+            .into_iter()
+            .map(|v| NonZeroU64::new(v).unwrap())
+    }
+
+    #[test]
+    fn gamma_round_trips() {
+        let mut writer = BitWriter::new();
+        for value in values() {
+            writer.write_gamma(value);
+        }
+        let bytes = writer.into_bytes();
+        // 1 is the single bit 1; 2 is 0, 1, then its low bit 0.
+        assert_eq!(bytes[0] & 0b1111, 0b0101);
+        let mut reader = BitReader::new(&bytes);
+        for value in values() {
+            assert_eq!(reader.read_gamma().unwrap(), value);
+        }
+        assert!(reader.remaining_bits() < 8);
+    }
+
+    #[test]
+    fn delta_round_trips() {
+        let mut writer = BitWriter::new();
+        for value in values() {
+            writer.write_delta(value);
+        }
+        let gamma_len = {
+            let mut gamma = BitWriter::new();
+            gamma.write_gamma(NonZeroU64::MAX);
+            gamma.bit_len()
+        };
+        let mut delta = BitWriter::new();
+        delta.write_delta(NonZeroU64::MAX);
+        assert!(delta.bit_len() < gamma_len);
+
+        let bytes = writer.into_bytes();
+        let mut reader = BitReader::new(&bytes);
+        for value in values() {
+            assert_eq!(reader.read_delta().unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_codes() {
+        let zeros = [0u8; 9];
+        let err = BitReader::new(&zeros).read_gamma().unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+        let err = BitReader::new(&zeros[..7]).read_gamma().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        // A delta length of 65 bits.
+        let mut writer = BitWriter::new();
+        writer.write_gamma(NonZeroU64::new(65).unwrap());
+        writer.write_bits(0, 64);
+        let bytes = writer.into_bytes();
+        let err = BitReader::new(&bytes).read_delta().unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+    }
+}
@@ -0,0 +1,307 @@
+//! Error conditions specific to this crate.
+//!
+//! Decoders report failures as [`io::Error`]; the conditions below are carried
+//! inside it with kind `InvalidData` and can be recovered with
+//! [`Error::from_io_error`]. Decoders that know where in their input a
+//! failure happened also attach a [`Location`].
+
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The encoded value does not fit the target type.
+    Overflow,
+    /// A zero was decoded into a `NonZero` type.
+    Zero,
+    /// A length prefix exceeds the caller's limit.
+    TooLong,
+    /// A length-prefixed string is not valid UTF-8.
+    InvalidUtf8(core::str::Utf8Error),
+    /// A map contains the same key twice.
+    DuplicateKey,
+    /// An encoding continues past the longest length the format allows.
+    TooManyBytes,
+    /// A frame's payload does not match its checksum.
+    ChecksumMismatch,
+    /// A decoded value is outside the range the caller allows.
+    OutOfRange,
+    /// A reader's total byte quota ran out before the input did.
+    QuotaExceeded,
+    /// Bytes remain after a value that was meant to fill its input.
+    TrailingBytes,
+}
+
+/// Where a decode failure was detected.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Location {
+    /// Offset of the offending byte from the start of the input.
+    pub offset: u64,
+    /// Bit position in the value that the offending byte's group would fill.
+    pub shift: u32,
+}
+
+#[cfg(feature = "std")]
+impl Location {
+    pub fn of(err: &io::Error) -> Option<Location> {
+        Some(err.get_ref()?.downcast_ref::<Located>()?.location)
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug)]
+struct Located {
+    error: Error,
+    location: Location,
+}
+
+/// Shifts the location attached to `err`, if any, by `base` bytes, for
+/// callers that know where the failing value started in a larger input.
+#[cfg(feature = "std")]
+pub(crate) fn relocate(mut err: io::Error, base: u64) -> io::Error {
+    if let Some(located) = err
+        .get_mut()
+        .and_then(|inner| inner.downcast_mut::<Located>())
+    {
+        located.location.offset += base;
+    }
+    err
+}
+
+impl Error {
+    #[cfg(feature = "std")]
+    pub fn from_io_error(err: &io::Error) -> Option<Error> {
+        let inner = err.get_ref()?;
+        match inner.downcast_ref::<Error>() {
+            Some(err) => Some(*err),
+            None => Some(inner.downcast_ref::<Located>()?.error),
+        }
+    }
+
+    /// Wraps the error with the location of the byte that caused it.
+    #[cfg(feature = "std")]
+    pub(crate) fn at(self, offset: usize, shift: usize) -> io::Error {
+        let location = Location {
+            offset: offset as u64,
+            shift: shift as u32,
+        };
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            Located {
+                error: self,
+                location,
+            },
+        )
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Error::Overflow => ErrorCode::Overflow,
+            Error::Zero => ErrorCode::Zero,
+            Error::TooLong => ErrorCode::TooLong,
+            Error::InvalidUtf8(_) => ErrorCode::InvalidUtf8,
+            Error::DuplicateKey => ErrorCode::DuplicateKey,
+            Error::TooManyBytes => ErrorCode::TooManyBytes,
+            Error::ChecksumMismatch => ErrorCode::ChecksumMismatch,
+            Error::OutOfRange => ErrorCode::OutOfRange,
+            Error::QuotaExceeded => ErrorCode::QuotaExceeded,
+            Error::TrailingBytes => ErrorCode::TrailingBytes,
+        }
+    }
+}
+
+/// Stable numeric identifiers for decode failures, for logs, metrics and the
+/// C API. Codes are never reused; zero is reserved for success.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    UnexpectedEof = 1,
+    Overflow = 2,
+    Zero = 3,
+    TooLong = 4,
+    InvalidUtf8 = 5,
+    DuplicateKey = 6,
+    /// Malformed input not covered by a more specific code.
+    InvalidData = 7,
+    /// The caller passed a value or argument the operation cannot accept.
+    InvalidInput = 8,
+    /// Any other I/O failure of the underlying reader or writer.
+    Io = 9,
+    TooManyBytes = 10,
+    ChecksumMismatch = 11,
+    OutOfRange = 12,
+    QuotaExceeded = 13,
+    TrailingBytes = 14,
+}
+
+impl ErrorCode {
+    /// Classifies any error returned by this crate.
+    #[cfg(feature = "std")]
+    pub fn of(err: &io::Error) -> ErrorCode {
+        if let Some(err) = Error::from_io_error(err) {
+            return err.code();
+        }
+        match err.kind() {
+            io::ErrorKind::UnexpectedEof => ErrorCode::UnexpectedEof,
+            io::ErrorKind::InvalidData => ErrorCode::InvalidData,
+            io::ErrorKind::InvalidInput => ErrorCode::InvalidInput,
+            _ => ErrorCode::Io,
+        }
+    }
+
+    pub fn code(self) -> u32 {
+        self as u32
+    }
+
+    pub fn from_code(code: u32) -> Option<ErrorCode> {
+        Some(match code {
+            1 => ErrorCode::UnexpectedEof,
+            2 => ErrorCode::Overflow,
+            3 => ErrorCode::Zero,
+            4 => ErrorCode::TooLong,
+            5 => ErrorCode::InvalidUtf8,
+            6 => ErrorCode::DuplicateKey,
+            7 => ErrorCode::InvalidData,
+            8 => ErrorCode::InvalidInput,
+            9 => ErrorCode::Io,
+            10 => ErrorCode::TooManyBytes,
+            11 => ErrorCode::ChecksumMismatch,
+            12 => ErrorCode::OutOfRange,
+            13 => ErrorCode::QuotaExceeded,
+            14 => ErrorCode::TrailingBytes,
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Overflow => write!(f, "encoded value overflows the target type"),
+            Error::Zero => write!(f, "decoded zero into a non-zero type"),
+            Error::TooLong => write!(f, "length prefix exceeds the limit"),
+            Error::InvalidUtf8(e) => write!(f, "invalid UTF-8 in string: {e}"),
+            Error::DuplicateKey => write!(f, "duplicate key in map"),
+            Error::TooManyBytes => write!(f, "encoding has too many bytes"),
+            Error::ChecksumMismatch => write!(f, "frame checksum mismatch"),
+            Error::OutOfRange => write!(f, "decoded value is outside the allowed range"),
+            Error::QuotaExceeded => write!(f, "byte quota exceeded"),
+            Error::TrailingBytes => write!(f, "trailing bytes after the value"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+#[cfg(feature = "std")]
+impl fmt::Display for Located {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at offset {} (shift {})",
+            self.error, self.location.offset, self.location.shift
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Located {}
+
+/// `Utf8Error` has no `defmt` support, so only where the valid prefix ends
+/// is logged for it.
+#[cfg(feature = "defmt")]
+impl defmt::Format for Error {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        match self {
+            Error::InvalidUtf8(e) => {
+                defmt::write!(f, "InvalidUtf8(valid_up_to: {})", e.valid_up_to())
+            }
+            other => defmt::write!(f, "{}", other.code()),
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Error {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(match u.int_in_range(0..=9)? {
+            0 => Error::Overflow,
+            1 => Error::Zero,
+            2 => Error::TooLong,
+            3 => {
+                // A valid prefix followed by a byte that never appears in
+                // UTF-8, so the error points somewhere inside the input.
+                let prefix: &str = u.arbitrary()?;
+                let bytes = [prefix.as_bytes(), &[0xFF]].concat();
+                std::str::from_utf8(&bytes)
+                    .err()
+                    .map_or(Error::Zero, Error::InvalidUtf8)
+            }
+            4 => Error::DuplicateKey,
+            5 => Error::TooManyBytes,
+            6 => Error::ChecksumMismatch,
+            7 => Error::OutOfRange,
+            8 => Error::QuotaExceeded,
+            _ => Error::TrailingBytes,
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::io;
+
+    use super::{relocate, Error, ErrorCode, Location};
+
+    #[test]
+    fn codes_round_trip() {
+        for code in 1..=14 {
+            assert_eq!(ErrorCode::from_code(code).unwrap().code(), code);
+        }
+        assert_eq!(ErrorCode::from_code(0), None);
+        assert_eq!(ErrorCode::from_code(15), None);
+    }
+
+    #[test]
+    fn classifies_io_errors() {
+        assert_eq!(ErrorCode::of(&Error::TooLong.into()), ErrorCode::TooLong);
+        let eof = io::Error::from(io::ErrorKind::UnexpectedEof);
+        assert_eq!(ErrorCode::of(&eof), ErrorCode::UnexpectedEof);
+        let other = io::Error::other("disk on fire");
+        assert_eq!(ErrorCode::of(&other).code(), 9);
+    }
+
+    #[test]
+    fn locations() {
+        let err = relocate(Error::Overflow.at(4, 28), 10);
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+        assert_eq!(ErrorCode::of(&err), ErrorCode::Overflow);
+        assert_eq!(
+            Location::of(&err),
+            Some(Location {
+                offset: 14,
+                shift: 28
+            })
+        );
+        assert_eq!(
+            err.to_string(),
+            "encoded value overflows the target type at offset 14 (shift 28)"
+        );
+        assert_eq!(Location::of(&Error::Overflow.into()), None);
+    }
+}
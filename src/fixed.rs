@@ -0,0 +1,149 @@
+//! Encoding into fixed capacity vectors, for building messages without an
+//! allocator. Each value is encoded in full before it is appended, so a value
+//! that does not fit leaves the buffer unchanged.
+//!
+//! ```
+//! # #[cfg(feature = "heapless")] {
+//! use lebase::fixed::{encode_bytes, encode_into, CapacityExceeded};
+//!
+//! let mut message = heapless::Vec::<u8, 4>::new();
+//! assert_eq!(encode_into(624485u32, &mut message), Ok(3));
+//! assert_eq!(encode_bytes(b"hi", &mut message), Err(CapacityExceeded));
+//! assert_eq!(message, [0xE5, 0x8E, 0x26]);
+//! # }
+//! ```
+
+use core::fmt;
+
+use num_traits::PrimInt;
+
+use crate::{get_7bits, get_shr, is_encode_end, max_encoded_len, CONTINUATION};
+
+/// Longest encoding of any primitive integer.
+const SCRATCH: usize = 19;
+
+/// Appending would exceed the buffer's capacity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CapacityExceeded;
+
+impl fmt::Display for CapacityExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("encoding exceeds the buffer's capacity")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CapacityExceeded {}
+
+/// A fixed capacity byte vector.
+pub trait FixedBuffer {
+    fn remaining_capacity(&self) -> usize;
+
+    /// Appends all of `bytes`, or nothing if they do not fit.
+    fn try_append(&mut self, bytes: &[u8]) -> Result<(), CapacityExceeded>;
+}
+
+#[cfg(feature = "heapless")]
+impl<const CAP: usize> FixedBuffer for heapless::Vec<u8, CAP> {
+    fn remaining_capacity(&self) -> usize {
+        self.capacity() - self.len()
+    }
+
+    fn try_append(&mut self, bytes: &[u8]) -> Result<(), CapacityExceeded> {
+        self.extend_from_slice(bytes).map_err(|_| CapacityExceeded)
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+impl<const CAP: usize> FixedBuffer for arrayvec::ArrayVec<u8, CAP> {
+    fn remaining_capacity(&self) -> usize {
+        arrayvec::ArrayVec::remaining_capacity(self)
+    }
+
+    fn try_append(&mut self, bytes: &[u8]) -> Result<(), CapacityExceeded> {
+        self.try_extend_from_slice(bytes)
+            .map_err(|_| CapacityExceeded)
+    }
+}
+
+#[cfg_attr(feature = "no_panic", no_panic::no_panic)]
+fn encode_scratch<N: PrimInt>(value: N) -> ([u8; SCRATCH], usize) {
+    debug_assert!(max_encoded_len::<N>() <= SCRATCH);
+    let mut scratch = [0u8; SCRATCH];
+    let shr = get_shr::<N>();
+    let mut num = value;
+    for (len, slot) in scratch.iter_mut().enumerate() {
+        let byte = get_7bits(num);
+        let ends = is_encode_end(num);
+        num = shr(num, 7);
+        if ends {
+            *slot = byte & !CONTINUATION;
+            return (scratch, len + 1);
+        }
+        *slot = byte | CONTINUATION;
+    }
+    (scratch, SCRATCH)
+}
+
+/// Appends the encoding of `value` to `out`, returning its length.
+#[cfg_attr(feature = "no_panic", no_panic::no_panic)]
+pub fn encode_into<N, B>(value: N, out: &mut B) -> Result<usize, CapacityExceeded>
+where
+    N: PrimInt,
+    B: FixedBuffer + ?Sized,
+{
+    let (scratch, len) = encode_scratch(value);
+    out.try_append(&scratch[..len])?;
+    Ok(len)
+}
+
+/// Appends `bytes` with a ULEB128 length prefix, returning the total length.
+/// Nothing is appended unless both fit.
+pub fn encode_bytes<B>(bytes: &[u8], out: &mut B) -> Result<usize, CapacityExceeded>
+where
+    B: FixedBuffer + ?Sized,
+{
+    let (scratch, prefix) = encode_scratch(bytes.len());
+    if prefix + bytes.len() > out.remaining_capacity() {
+        return Err(CapacityExceeded);
+    }
+    out.try_append(&scratch[..prefix])?;
+    out.try_append(bytes)?;
+    Ok(prefix + bytes.len())
+}
+
+pub fn encode_str<B>(s: &str, out: &mut B) -> Result<usize, CapacityExceeded>
+where
+    B: FixedBuffer + ?Sized,
+{
+    encode_bytes(s.as_bytes(), out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn heapless_vec() {
+        let mut out = heapless::Vec::<u8, 8>::new();
+        assert_eq!(encode_into(-1i64, &mut out), Ok(1));
+        assert_eq!(encode_str("hello", &mut out), Ok(6));
+        assert_eq!(encode_into(u32::MAX, &mut out), Err(CapacityExceeded));
+        assert_eq!(encode_into(624485u32, &mut out), Err(CapacityExceeded));
+        assert_eq!(out, [0x7F, 0x05, b'h', b'e', b'l', b'l', b'o']);
+        assert_eq!(encode_into(1u8, &mut out), Ok(1));
+        assert_eq!(out.len(), 8);
+    }
+
+    #[cfg(feature = "arrayvec")]
+    #[test]
+    fn arrayvec() {
+        let mut out = arrayvec::ArrayVec::<u8, 20>::new();
+        assert_eq!(encode_into(u128::MAX, &mut out), Ok(19));
+        assert_eq!(out[18], 0x03);
+        assert_eq!(encode_bytes(&[1, 2], &mut out), Err(CapacityExceeded));
+        assert_eq!(out.len(), 19);
+    }
+}
@@ -0,0 +1,155 @@
+//! Base-128 varints with a configurable flag bit, for legacy formats that
+//! mark the last byte instead of continuation bytes, or keep the flag in the
+//! least significant bit. Groups are always stored low group first;
+//! [`FlagConfig::default`] is plain ULEB128.
+
+use std::io;
+
+use crate::Error;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Polarity {
+    /// The flag is set on every byte except the last.
+    #[default]
+    Continue,
+    /// The flag is set only on the last byte.
+    Stop,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Position {
+    /// Bit 7 is the flag and bits 0 to 6 hold the group.
+    #[default]
+    Msb,
+    /// Bit 0 is the flag and bits 1 to 7 hold the group.
+    Lsb,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FlagConfig {
+    polarity: Polarity,
+    position: Position,
+}
+
+impl FlagConfig {
+    pub const fn new() -> Self {
+        FlagConfig {
+            polarity: Polarity::Continue,
+            position: Position::Msb,
+        }
+    }
+
+    pub const fn polarity(mut self, polarity: Polarity) -> Self {
+        self.polarity = polarity;
+        self
+    }
+
+    pub const fn position(mut self, position: Position) -> Self {
+        self.position = position;
+        self
+    }
+
+    fn pack(&self, group: u8, last: bool) -> u8 {
+        let flag = last == (self.polarity == Polarity::Stop);
+        match self.position {
+            Position::Msb => group | (flag as u8) << 7,
+            Position::Lsb => group << 1 | flag as u8,
+        }
+    }
+
+    /// Splits a byte into its group and whether it ends the value.
+    fn unpack(&self, byte: u8) -> (u8, bool) {
+        let (group, flag) = match self.position {
+            Position::Msb => (byte & 0x7F, byte & 0x80 != 0),
+            Position::Lsb => (byte >> 1, byte & 1 != 0),
+        };
+        (group, flag == (self.polarity == Polarity::Stop))
+    }
+
+    pub fn encode<W>(&self, value: u64, writer: &mut W) -> Result<usize, io::Error>
+    where
+        W: Sized + io::Write,
+    {
+        let mut bytes = [0u8; 10];
+        let mut len = 0;
+        let mut rest = value;
+        loop {
+            let group = (rest & 0x7F) as u8;
+            rest >>= 7;
+            bytes[len] = self.pack(group, rest == 0);
+            len += 1;
+            if rest == 0 {
+                break;
+            }
+        }
+        writer.write_all(&bytes[..len])?;
+        Ok(len)
+    }
+
+    /// Fails with [`Error::Overflow`] once the value no longer fits a `u64`.
+    pub fn decode<R>(&self, reader: &mut R) -> Result<u64, io::Error>
+    where
+        R: Sized + io::Read,
+    {
+        let mut value = 0u64;
+        let mut shift = 0;
+        let mut buffer = [0u8; 1];
+        loop {
+            reader.read_exact(&mut buffer)?;
+            let (group, last) = self.unpack(buffer[0]);
+            if shift > 63 || (shift == 63 && group > 1) {
+                return Err(Error::Overflow.into());
+            }
+            value |= (group as u64) << shift;
+            if last {
+                break Ok(value);
+            }
+            shift += 7;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FlagConfig, Polarity, Position};
+    use crate::{Error, LEB128Encode};
+
+    fn encode(config: FlagConfig, value: u64) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        config.encode(value, &mut bytes).unwrap();
+        assert_eq!(config.decode(&mut &bytes[..]).unwrap(), value);
+        bytes
+    }
+
+    #[test]
+    fn layouts() {
+        let leb = FlagConfig::default();
+        for value in [0, 300, u64::MAX] {
+            let mut expected = Vec::new();
+            value.leb128_encode(&mut expected).unwrap();
+            assert_eq!(encode(leb, value), expected);
+        }
+        let stop = FlagConfig::new().polarity(Polarity::Stop);
+        assert_eq!(encode(stop, 300), [0x2C, 0x82]);
+        let lsb = FlagConfig::new().position(Position::Lsb);
+        assert_eq!(encode(lsb, 300), [0x59, 0x04]);
+        let both = lsb.polarity(Polarity::Stop);
+        assert_eq!(encode(both, 300), [0x58, 0x05]);
+        assert_eq!(encode(both, u64::MAX).len(), 10);
+    }
+
+    #[test]
+    fn rejects_overflow() {
+        let stop = FlagConfig::new().polarity(Polarity::Stop);
+        let mut bytes = vec![0x7F; 9];
+        bytes.push(0x82);
+        let err = stop.decode(&mut &bytes[..]).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+    }
+}
@@ -0,0 +1,136 @@
+//! `f32` and `f64` values as the ULEB128 of their bit patterns, for streams
+//! that mix floats with integers.
+//!
+//! The plain bit pattern puts the sign and exponent in the top bits, so all
+//! but the tiniest floats take one of the two longest encodings. The reversed
+//! variants swap the pattern's bytes first, moving the low mantissa bytes,
+//! which are zero for integers, halves and other short binary fractions, to
+//! the top. Both keep the exact bits, NaN payloads included.
+//!
+//! ```
+//! use lebase::float::{decode_f64_reversed, encode_f64, encode_f64_reversed};
+//!
+//! let mut plain = Vec::new();
+//! assert_eq!(encode_f64(1.5, &mut plain).unwrap(), 9);
+//! let mut reversed = Vec::new();
+//! assert_eq!(encode_f64_reversed(1.5, &mut reversed).unwrap(), 3);
+//! assert_eq!(decode_f64_reversed(&mut &reversed[..]).unwrap(), 1.5);
+//! ```
+
+use std::io;
+
+use crate::{LEB128Codec, LEB128Encode};
+
+macro_rules! float_codec {
+    ($float:ty, $bits:ty, $encode:ident, $decode:ident, $encode_reversed:ident, $decode_reversed:ident) => {
+        pub fn $encode<W>(value: $float, writer: &mut W) -> Result<usize, io::Error>
+        where
+            W: Sized + io::Write,
+        {
+            value.to_bits().leb128_encode(writer)
+        }
+
+        pub fn $decode<R>(reader: &mut R) -> Result<$float, io::Error>
+        where
+            R: Sized + io::Read,
+        {
+            <$bits>::leb128_decode(reader).map(<$float>::from_bits)
+        }
+
+        /// Encodes the bit pattern with its bytes reversed.
+        pub fn $encode_reversed<W>(value: $float, writer: &mut W) -> Result<usize, io::Error>
+        where
+            W: Sized + io::Write,
+        {
+            value.to_bits().swap_bytes().leb128_encode(writer)
+        }
+
+        pub fn $decode_reversed<R>(reader: &mut R) -> Result<$float, io::Error>
+        where
+            R: Sized + io::Read,
+        {
+            <$bits>::leb128_decode(reader).map(|bits| <$float>::from_bits(bits.swap_bytes()))
+        }
+    };
+}
+
+float_codec!(
+    f32,
+    u32,
+    encode_f32,
+    decode_f32,
+    encode_f32_reversed,
+    decode_f32_reversed
+);
+float_codec!(
+    f64,
+    u64,
+    encode_f64,
+    decode_f64,
+    encode_f64_reversed,
+    decode_f64_reversed
+);
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        decode_f32, decode_f32_reversed, decode_f64, decode_f64_reversed, encode_f32,
+        encode_f32_reversed, encode_f64, encode_f64_reversed,
+    };
+    use crate::Error;
+
+    #[test]
+    fn keeps_exact_bits() {
+        let nan = f64::from_bits(0x7FF8_0000_0000_1234);
+        for value in [
+            0.0,
+            -0.0,
+            1.0,
+            -2.5,
+            0.1,
+            f64::MIN_POSITIVE,
+            f64::INFINITY,
+            nan,
+        ] {
+            let mut plain = Vec::new();
+            encode_f64(value, &mut plain).unwrap();
+            let decoded = decode_f64(&mut &plain[..]).unwrap();
+            assert_eq!(decoded.to_bits(), value.to_bits());
+            let mut reversed = Vec::new();
+            encode_f64_reversed(value, &mut reversed).unwrap();
+            let decoded = decode_f64_reversed(&mut &reversed[..]).unwrap();
+            assert_eq!(decoded.to_bits(), value.to_bits());
+        }
+        for value in [0.0f32, -1.0, 3.25, f32::MAX, f32::NAN] {
+            let mut plain = Vec::new();
+            encode_f32(value, &mut plain).unwrap();
+            assert_eq!(
+                decode_f32(&mut &plain[..]).unwrap().to_bits(),
+                value.to_bits()
+            );
+            let mut reversed = Vec::new();
+            encode_f32_reversed(value, &mut reversed).unwrap();
+            let decoded = decode_f32_reversed(&mut &reversed[..]).unwrap();
+            assert_eq!(decoded.to_bits(), value.to_bits());
+        }
+    }
+
+    #[test]
+    fn reversal_shortens_round_values() {
+        for (value, plain_len, reversed_len) in [(1.0, 9, 3), (-100.0, 10, 3), (0.1, 9, 10)] {
+            let mut plain = Vec::new();
+            assert_eq!(encode_f64(value, &mut plain).unwrap(), plain_len, "{value}");
+            let mut reversed = Vec::new();
+            assert_eq!(
+                encode_f64_reversed(value, &mut reversed).unwrap(),
+                reversed_len,
+                "{value}"
+            );
+        }
+        let mut reversed = Vec::new();
+        assert_eq!(encode_f32_reversed(2.0, &mut reversed).unwrap(), 1);
+
+        let err = decode_f32(&mut &[0xFF, 0xFF, 0xFF, 0xFF, 0x1F][..]).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+    }
+}
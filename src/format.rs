@@ -0,0 +1,186 @@
+//! A registry of varint formats, so tooling built on this crate can handle
+//! formats defined in other crates.
+//!
+//! ```
+//! use lebase::format::{self, VarintFormat};
+//!
+//! let sleb = format::lookup("sleb128").unwrap();
+//! let mut out = Vec::new();
+//! sleb.encode(-128, &mut out).unwrap();
+//! assert_eq!(out, [0x80, 0x7F]);
+//! assert_eq!(sleb.decode(&out).unwrap(), (-128, 2));
+//! ```
+
+use std::{
+    io,
+    sync::{OnceLock, RwLock},
+};
+
+use crate::{LEB128Codec, LEB128Encode};
+
+/// A variable-length integer format. Values are carried as `i128`; formats
+/// reject values they cannot represent with `InvalidInput`.
+pub trait VarintFormat: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Appends the encoding of `value` to `out`, returning its length.
+    fn encode(&self, value: i128, out: &mut Vec<u8>) -> Result<usize, io::Error>;
+
+    /// Decodes one value from the start of `bytes`, returning it and the
+    /// number of bytes it occupied.
+    fn decode(&self, bytes: &[u8]) -> Result<(i128, usize), io::Error>;
+}
+
+fn decode_prefix<N: LEB128Codec>(bytes: &[u8]) -> Result<(N, usize), io::Error> {
+    let mut readable = bytes;
+    let num = N::leb128_decode(&mut readable)?;
+    Ok((num, bytes.len() - readable.len()))
+}
+
+fn out_of_range() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "value out of range for format")
+}
+
+pub struct Uleb128;
+
+impl VarintFormat for Uleb128 {
+    fn name(&self) -> &str {
+        "uleb128"
+    }
+
+    fn encode(&self, value: i128, out: &mut Vec<u8>) -> Result<usize, io::Error> {
+        u128::try_from(value)
+            .map_err(|_| out_of_range())?
+            .leb128_encode(out)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<(i128, usize), io::Error> {
+        let (num, len) = decode_prefix::<u128>(bytes)?;
+        Ok((i128::try_from(num).map_err(|_| out_of_range())?, len))
+    }
+}
+
+pub struct Sleb128;
+
+impl VarintFormat for Sleb128 {
+    fn name(&self) -> &str {
+        "sleb128"
+    }
+
+    fn encode(&self, value: i128, out: &mut Vec<u8>) -> Result<usize, io::Error> {
+        value.leb128_encode(out)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<(i128, usize), io::Error> {
+        decode_prefix::<i128>(bytes)
+    }
+}
+
+fn registry() -> &'static RwLock<Vec<&'static dyn VarintFormat>> {
+    static REGISTRY: OnceLock<RwLock<Vec<&'static dyn VarintFormat>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        RwLock::new(vec![
+            &Uleb128,
+            &Sleb128,
+            &crate::vlq::Vlq,
+            &crate::vlq::GitOffset,
+            &crate::dex::Uleb128p1,
+            &crate::prefix::PrefixVarint,
+            &crate::ordered::OrderedVarint,
+        ])
+    })
+}
+
+/// Adds `format` to the registry. Returns `false`, leaving the registry
+/// unchanged, if a format with the same name is already registered or the
+/// name is anything but lowercase ASCII letters, digits and `-`, so that it
+/// can be written into CSV and JSON output as is.
+pub fn register(format: &'static dyn VarintFormat) -> bool {
+    let name = format.name();
+    if name.is_empty()
+        || !name
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+    {
+        return false;
+    }
+    let mut formats = registry().write().unwrap_or_else(|e| e.into_inner());
+    if formats.iter().any(|f| f.name() == name) {
+        return false;
+    }
+    formats.push(format);
+    true
+}
+
+pub fn lookup(name: &str) -> Option<&'static dyn VarintFormat> {
+    formats().into_iter().find(|f| f.name() == name)
+}
+
+/// All registered formats, built-in ones first.
+pub fn formats() -> Vec<&'static dyn VarintFormat> {
+    registry().read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{formats, lookup, register, VarintFormat};
+
+    /// Big-endian fixed four bytes, standing in for a downstream format.
+    struct Fixed32;
+
+    impl VarintFormat for Fixed32 {
+        fn name(&self) -> &str {
+            "test-fixed32"
+        }
+
+        fn encode(&self, value: i128, out: &mut Vec<u8>) -> Result<usize, io::Error> {
+            let value = u32::try_from(value).map_err(|_| io::ErrorKind::InvalidInput)?;
+            out.extend(value.to_be_bytes());
+            Ok(4)
+        }
+
+        fn decode(&self, bytes: &[u8]) -> Result<(i128, usize), io::Error> {
+            let head = bytes.get(..4).ok_or(io::ErrorKind::UnexpectedEof)?;
+            Ok((u32::from_be_bytes(head.try_into().unwrap()).into(), 4))
+        }
+    }
+
+    struct Quoted;
+
+    impl VarintFormat for Quoted {
+        fn name(&self) -> &str {
+            "test\",\"x"
+        }
+
+        fn encode(&self, value: i128, out: &mut Vec<u8>) -> Result<usize, io::Error> {
+            Fixed32.encode(value, out)
+        }
+
+        fn decode(&self, bytes: &[u8]) -> Result<(i128, usize), io::Error> {
+            Fixed32.decode(bytes)
+        }
+    }
+
+    #[test]
+    fn registers_downstream_formats() {
+        assert!(register(&Fixed32));
+        assert!(!register(&Fixed32));
+        assert!(!register(&Quoted));
+        assert!(lookup(Quoted.name()).is_none());
+        let format = lookup("test-fixed32").unwrap();
+        assert_eq!(format.decode(&[0, 0, 1, 0]).unwrap(), (256, 4));
+        let names: Vec<&str> = formats().iter().map(|f| f.name()).collect();
+        assert_eq!(&names[..2], ["uleb128", "sleb128"]);
+        assert!(names.contains(&"test-fixed32"));
+    }
+
+    #[test]
+    fn builtin_ranges() {
+        let uleb = lookup("uleb128").unwrap();
+        let err = uleb.encode(-1, &mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(uleb.decode(&[0xE5, 0x8E, 0x26, 0xFF]).unwrap(), (624485, 3));
+    }
+}
@@ -0,0 +1,153 @@
+//! Length-delimited framing: each frame is a ULEB128 payload length followed
+//! by the payload. Checksummed frames add a four byte little-endian checksum
+//! of the payload after it, not counted in the length.
+
+use std::io::{self, Read};
+
+use crate::{bytes, checksum, Error};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Checksum {
+    /// CRC-32 as used by zlib and gzip.
+    Crc32,
+    /// XXH32 with seed zero.
+    XxHash32,
+}
+
+impl Checksum {
+    fn compute(self, payload: &[u8]) -> u32 {
+        match self {
+            Checksum::Crc32 => checksum::crc32(payload),
+            Checksum::XxHash32 => checksum::xxh32(payload),
+        }
+    }
+}
+
+/// Writes one frame, returning the bytes written.
+pub fn write_frame<W>(payload: &[u8], writer: &mut W) -> Result<usize, io::Error>
+where
+    W: Sized + io::Write,
+{
+    bytes::encode_bytes(payload, writer)
+}
+
+/// Reads one frame, failing with [`Error::TooLong`](crate::Error::TooLong)
+/// if its payload is longer than `max_len`.
+pub fn read_frame<R>(reader: &mut R, max_len: usize) -> Result<Vec<u8>, io::Error>
+where
+    R: Sized + io::Read,
+{
+    bytes::decode_bytes(reader, max_len)
+}
+
+/// Like [`read_frame`], but returns `None` if the reader is already at its
+/// end, so a stream of frames can be read until it is exhausted. Running out
+/// of input inside a frame is still an `UnexpectedEof` error.
+pub fn try_read_frame<R>(reader: &mut R, max_len: usize) -> Result<Option<Vec<u8>>, io::Error>
+where
+    R: Sized + io::Read,
+{
+    let mut first = [0];
+    loop {
+        match reader.read(&mut first) {
+            Ok(0) => return Ok(None),
+            Ok(_) => break,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    read_frame(&mut (&first[..]).chain(reader), max_len).map(Some)
+}
+
+/// Writes one frame followed by the payload's checksum, returning the bytes
+/// written.
+pub fn write_checked_frame<W>(
+    payload: &[u8],
+    checksum: Checksum,
+    writer: &mut W,
+) -> Result<usize, io::Error>
+where
+    W: Sized + io::Write,
+{
+    let written = write_frame(payload, writer)?;
+    writer.write_all(&checksum.compute(payload).to_le_bytes())?;
+    Ok(written + 4)
+}
+
+/// Reads one checksummed frame, failing with [`Error::ChecksumMismatch`] if
+/// the payload does not match its checksum.
+pub fn read_checked_frame<R>(
+    reader: &mut R,
+    max_len: usize,
+    checksum: Checksum,
+) -> Result<Vec<u8>, io::Error>
+where
+    R: Sized + io::Read,
+{
+    let payload = read_frame(reader, max_len)?;
+    let mut stored = [0u8; 4];
+    reader.read_exact(&mut stored)?;
+    if u32::from_le_bytes(stored) != checksum.compute(&payload) {
+        return Err(Error::ChecksumMismatch.into());
+    }
+    Ok(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{
+        read_checked_frame, read_frame, try_read_frame, write_checked_frame, write_frame, Checksum,
+    };
+    use crate::Error;
+
+    #[test]
+    fn frames_round_trip() {
+        let mut stream = Vec::new();
+        assert_eq!(write_frame(b"hello", &mut stream).unwrap(), 6);
+        write_frame(&[], &mut stream).unwrap();
+        write_frame(&[7; 300], &mut stream).unwrap();
+
+        let mut readable = &stream[..];
+        assert_eq!(read_frame(&mut readable, 300).unwrap(), b"hello");
+        assert_eq!(try_read_frame(&mut readable, 300).unwrap(), Some(vec![]));
+        assert_eq!(
+            try_read_frame(&mut readable, 300).unwrap(),
+            Some(vec![7; 300])
+        );
+        assert_eq!(try_read_frame(&mut readable, 300).unwrap(), None);
+    }
+
+    #[test]
+    fn enforces_limits() {
+        let mut stream = Vec::new();
+        write_frame(&[0; 10], &mut stream).unwrap();
+        let err = read_frame(&mut &stream[..], 9).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::TooLong));
+        let err = try_read_frame(&mut &stream[..5], 10).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn detects_corruption() {
+        for checksum in [Checksum::Crc32, Checksum::XxHash32] {
+            let mut stream = Vec::new();
+            assert_eq!(
+                write_checked_frame(b"123456789", checksum, &mut stream).unwrap(),
+                14
+            );
+            let payload = read_checked_frame(&mut &stream[..], 64, checksum).unwrap();
+            assert_eq!(payload, b"123456789");
+
+            stream[3] ^= 0x04;
+            let err = read_checked_frame(&mut &stream[..], 64, checksum).unwrap_err();
+            assert_eq!(Error::from_io_error(&err), Some(Error::ChecksumMismatch));
+        }
+        let mut stream = Vec::new();
+        write_checked_frame(b"123456789", Checksum::Crc32, &mut stream).unwrap();
+        assert_eq!(&stream[10..], 0xCBF4_3926u32.to_le_bytes());
+    }
+}
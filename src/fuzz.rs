@@ -0,0 +1,95 @@
+//! Structured inputs for fuzz targets.
+//!
+//! ```no_run
+//! # use lebase::{fuzz::EncodedValues, iter::decode_iter};
+//! # fn fuzz_target(data: &[u8]) {
+//! let mut u = arbitrary::Unstructured::new(data);
+//! let Ok(case) = u.arbitrary::<EncodedValues<i64>>() else { return };
+//! let decoded: Vec<i64> = decode_iter(case.encoding()).map(Result::unwrap).collect();
+//! assert_eq!(decoded, case.values());
+//! # }
+//! ```
+
+use arbitrary::{Arbitrary, Unstructured};
+
+use crate::LEB128Codec;
+
+/// A sequence of values together with their concatenated canonical
+/// encodings.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EncodedValues<N> {
+    values: Vec<N>,
+    encoding: Vec<u8>,
+}
+
+impl<N: LEB128Codec + Copy> EncodedValues<N> {
+    pub fn new(values: Vec<N>) -> Self {
+        let mut encoding = Vec::new();
+        for &value in &values {
+            // Writing to a Vec cannot fail.
+            let _ = value.leb128_encode(&mut encoding);
+        }
+        EncodedValues { values, encoding }
+    }
+
+    pub fn values(&self) -> &[N] {
+        &self.values
+    }
+
+    pub fn encoding(&self) -> &[u8] {
+        &self.encoding
+    }
+
+    pub fn into_parts(self) -> (Vec<N>, Vec<u8>) {
+        (self.values, self.encoding)
+    }
+}
+
+impl<'a, N> Arbitrary<'a> for EncodedValues<N>
+where
+    N: Arbitrary<'a> + LEB128Codec + Copy,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self::new(u.arbitrary()?))
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <Vec<N> as Arbitrary>::size_hint(depth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+
+    use super::EncodedValues;
+    use crate::{flagged::FlagConfig, iter::decode_iter, Error, ErrorCode};
+
+    #[test]
+    fn encodings_decode_to_values() {
+        let data: Vec<u8> = (0..4096u32).map(|i| (i * 151 % 251) as u8).collect();
+        let mut u = Unstructured::new(&data);
+        for _ in 0..8 {
+            let case = EncodedValues::<i32>::arbitrary(&mut u).unwrap();
+            let decoded: Vec<i32> = decode_iter(case.encoding()).map(Result::unwrap).collect();
+            assert_eq!(decoded, case.values());
+            let case = EncodedValues::<u128>::arbitrary(&mut u).unwrap();
+            let decoded: Vec<u128> = decode_iter(case.encoding()).map(Result::unwrap).collect();
+            assert_eq!(decoded, case.values());
+        }
+    }
+
+    #[test]
+    fn config_and_error_types() {
+        let data = [0x03u8; 64];
+        let mut u = Unstructured::new(&data);
+        let config = FlagConfig::arbitrary(&mut u).unwrap();
+        let mut out = Vec::new();
+        config.encode(300, &mut out).unwrap();
+        assert_eq!(config.decode(&mut &out[..]).unwrap(), 300);
+        for _ in 0..8 {
+            let err = Error::arbitrary(&mut u).unwrap();
+            assert_eq!(ErrorCode::of(&err.into()), err.code());
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Golomb and Rice codes, written with [`BitWriter`] and read with
+//! [`BitReader`], for geometrically distributed values.
+//!
+//! A value is split by the parameter `m` into a quotient, written in unary as
+//! that many one bits and a zero bit, and a remainder in truncated binary,
+//! most significant bit first. Rice codes are the Golomb codes whose `m` is
+//! `2^k`, where the remainder is a plain `k` bit field. The unary quotient
+//! grows linearly with the value, so `m` should be close to the mean.
+//!
+//! ```
+//! use lebase::bitstream::{BitReader, BitWriter};
+//!
+//! let mut writer = BitWriter::new();
+//! writer.write_golomb(42, 10);
+//! writer.write_rice(42, 3);
+//! let bytes = writer.into_bytes();
+//! let mut reader = BitReader::new(&bytes);
+//! assert_eq!(reader.read_golomb(10).unwrap(), 42);
+//! assert_eq!(reader.read_rice(3).unwrap(), 42);
+//! ```
+
+use std::io;
+
+use crate::{
+    bitstream::{BitReader, BitWriter},
+    Error,
+};
+
+/// The remainder width `b` and the number of short remainders, which take
+/// only `b - 1` bits.
+fn remainder_layout(m: u64) -> (u32, u64) {
+    assert!(m > 0, "Golomb parameter must be positive");
+    let width = 64 - (m - 1).leading_zeros();
+    let short = ((1u128 << width) - m as u128) as u64;
+    (width, short)
+}
+
+fn rice_parameter(k: u32) -> u64 {
+    assert!(k < 64, "Rice parameter must be below 64");
+    1 << k
+}
+
+impl BitWriter {
+    /// Writes `value` in the Golomb code with parameter `m`.
+    ///
+    /// # Panics
+    ///
+    /// If `m` is zero.
+    pub fn write_golomb(&mut self, value: u64, m: u64) {
+        let (width, short) = remainder_layout(m);
+        let mut quotient = value / m;
+        let remainder = value % m;
+        while quotient >= 64 {
+            self.write_bits(u64::MAX, 64);
+            quotient -= 64;
+        }
+        self.write_bits((1 << quotient) - 1, quotient as u32);
+        self.write_bit(false);
+        if remainder < short {
+            self.write_msb_first(remainder, width - 1);
+        } else {
+            self.write_msb_first(remainder + short, width);
+        }
+    }
+
+    /// Writes `value` in the Rice code with parameter `k`, the Golomb code
+    /// with `m = 2^k`.
+    ///
+    /// # Panics
+    ///
+    /// If `k` is 64 or more.
+    pub fn write_rice(&mut self, value: u64, k: u32) {
+        self.write_golomb(value, rice_parameter(k));
+    }
+
+    fn write_msb_first(&mut self, value: u64, width: u32) {
+        if width > 0 {
+            self.write_bits(value.reverse_bits() >> (64 - width), width);
+        }
+    }
+}
+
+impl BitReader<'_> {
+    /// Reads a value in the Golomb code with parameter `m`, failing with
+    /// [`Error::Overflow`] if it does not fit a `u64`.
+    ///
+    /// # Panics
+    ///
+    /// If `m` is zero.
+    pub fn read_golomb(&mut self, m: u64) -> Result<u64, io::Error> {
+        let (width, short) = remainder_layout(m);
+        let max_quotient = u64::MAX / m;
+        let mut quotient = 0;
+        while self.read_bit()? {
+            if quotient == max_quotient {
+                return Err(Error::Overflow.into());
+            }
+            quotient += 1;
+        }
+        let mut remainder = self.read_msb_first(width.saturating_sub(1))?;
+        if width > 0 && remainder >= short {
+            remainder = (remainder << 1 | self.read_bits(1)?) - short;
+        }
+        (quotient * m)
+            .checked_add(remainder)
+            .ok_or(Error::Overflow.into())
+    }
+
+    /// Reads a value in the Rice code with parameter `k`.
+    ///
+    /// # Panics
+    ///
+    /// If `k` is 64 or more.
+    pub fn read_rice(&mut self, k: u32) -> Result<u64, io::Error> {
+        self.read_golomb(rice_parameter(k))
+    }
+
+    fn read_msb_first(&mut self, width: u32) -> Result<u64, io::Error> {
+        if width == 0 {
+            return Ok(0);
+        }
+        Ok(self.read_bits(width)?.reverse_bits() >> (64 - width))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use crate::{
+        bitstream::{BitReader, BitWriter},
+        Error,
+    };
+
+    #[test]
+    fn golomb_round_trips() {
+        for m in [1, 2, 3, 5, 10, 64, 1000, 1 << 63, u64::MAX] {
+            let values = [
+                0,
+                1,
+                2,
+                m - 1,
+                m,
+                m.saturating_add(1),
+                3 * (m % 100),
+                u64::MAX,
+            ];
+            let mut writer = BitWriter::new();
+            for &value in &values {
+                if value / m < 200 {
+                    writer.write_golomb(value, m);
+                }
+            }
+            let bytes = writer.into_bytes();
+            let mut reader = BitReader::new(&bytes);
+            for &value in &values {
+                if value / m < 200 {
+                    assert_eq!(reader.read_golomb(m).unwrap(), value, "m = {m}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn truncated_binary_remainders() {
+        // With m = 5, remainders 0 to 2 take two bits and 3 and 4 take three.
+        let mut writer = BitWriter::new();
+        for value in [0, 2, 3, 4, 7] {
+            writer.write_golomb(value, 5);
+        }
+        assert_eq!(writer.bit_len(), 3 + 3 + 4 + 4 + 4);
+        // Rice codes are plain fields: 9 with k = 2 is 110 then 01.
+        let mut writer = BitWriter::new();
+        writer.write_rice(9, 2);
+        assert_eq!(writer.into_bytes(), [0b10_011]);
+    }
+
+    #[test]
+    fn rejects_malformed_codes() {
+        let ones = [0xFF; 3];
+        let err = BitReader::new(&ones).read_golomb(3).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        let mut writer = BitWriter::new();
+        writer.write_bits(0b11, 2);
+        writer.write_bit(false);
+        let bytes = writer.into_bytes();
+        let err = BitReader::new(&bytes).read_rice(63).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+    }
+}
@@ -0,0 +1,93 @@
+//! Conversion between 7-bit groups and little-endian byte strings, shared by
+//! the integer types that are not primitives.
+
+use std::io;
+
+use crate::{Error, CONTINUATION};
+
+fn group(bytes: &[u8], index: usize, fill: u8) -> u8 {
+    let bit = index * 7;
+    let lo = bytes.get(bit / 8).copied().unwrap_or(fill) as u16;
+    let hi = bytes.get(bit / 8 + 1).copied().unwrap_or(fill) as u16;
+    (((hi << 8 | lo) >> (bit % 8)) & 0x7F) as u8
+}
+
+pub(crate) fn write_groups<W>(
+    bytes: &[u8],
+    groups: usize,
+    fill: u8,
+    writer: &mut W,
+) -> Result<usize, io::Error>
+where
+    W: Sized + io::Write,
+{
+    let out: Vec<u8> = (0..groups)
+        .map(|i| {
+            let byte = group(bytes, i, fill);
+            if i + 1 == groups {
+                byte
+            } else {
+                byte | CONTINUATION
+            }
+        })
+        .collect();
+    writer.write_all(&out)?;
+    Ok(out.len())
+}
+
+/// Reads 7-bit groups into little-endian bytes, sign extending the last byte
+/// when `signed` is set. Inputs longer than `max_groups` fail with
+/// [`Error::Overflow`].
+pub(crate) fn read_groups<R>(
+    reader: &mut R,
+    signed: bool,
+    max_groups: usize,
+) -> Result<Vec<u8>, io::Error>
+where
+    R: Sized + io::Read,
+{
+    let mut bytes = Vec::new();
+    let mut acc: u32 = 0;
+    let mut acc_bits = 0;
+    let mut buffer: [u8; 1] = [0];
+    for _ in 0..max_groups {
+        reader.read_exact(&mut buffer)?;
+        acc |= ((buffer[0] & !CONTINUATION) as u32) << acc_bits;
+        acc_bits += 7;
+        while acc_bits >= 8 {
+            bytes.push(acc as u8);
+            acc >>= 8;
+            acc_bits -= 8;
+        }
+        if buffer[0] & CONTINUATION == 0 {
+            if acc_bits > 0 {
+                if signed && buffer[0] & 0x40 != 0 {
+                    acc |= !0 << acc_bits;
+                }
+                bytes.push(acc as u8);
+            }
+            return Ok(bytes);
+        }
+    }
+    Err(Error::Overflow.into())
+}
+
+/// Resizes little-endian `bytes` to exactly `width` bytes, or returns `None`
+/// if the value does not fit.
+#[cfg(any(feature = "ethnum", feature = "ruint"))]
+pub(crate) fn fit_le(mut bytes: Vec<u8>, width: usize, signed: bool) -> Option<Vec<u8>> {
+    let negative = signed && bytes.last().is_some_and(|b| b & 0x80 != 0);
+    let fill = if negative { 0xFF } else { 0 };
+    if bytes.len() > width {
+        if bytes[width..].iter().any(|&b| b != fill) {
+            return None;
+        }
+        if signed && (bytes[width - 1] & 0x80 != 0) != negative {
+            return None;
+        }
+        bytes.truncate(width);
+    } else {
+        bytes.resize(width, fill);
+    }
+    Some(bytes)
+}
@@ -0,0 +1,52 @@
+//! Hashing of decoded values, independent of how they were encoded.
+
+use std::{hash::Hash, hash::Hasher, io};
+
+use crate::LEB128Codec;
+
+/// Decodes every value in `bytes` as `N` and feeds it to `state`, so padded
+/// and canonical encodings of the same values hash identically. Returns the
+/// number of values hashed.
+pub fn hash_decoded<N, H>(bytes: &[u8], state: &mut H) -> Result<usize, io::Error>
+where
+    N: LEB128Codec + Hash,
+    H: Hasher,
+{
+    let mut readable = bytes;
+    let mut count = 0;
+    while !readable.is_empty() {
+        N::leb128_decode(&mut readable)?.hash(state);
+        count += 1;
+    }
+    state.write_usize(count);
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::hash_map::DefaultHasher, hash::Hasher, io};
+
+    use super::hash_decoded;
+
+    fn digest<N: crate::LEB128Codec + std::hash::Hash>(bytes: &[u8]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        hash_decoded::<N, _>(bytes, &mut hasher).unwrap();
+        hasher.finish()
+    }
+
+    #[test]
+    fn padding_does_not_change_hash() {
+        let canonical = [0x01, 0x7F, 0xE5, 0x8E, 0x26];
+        let padded = [0x81, 0x00, 0xFF, 0x80, 0x00, 0xE5, 0x8E, 0xA6, 0x80, 0x00];
+        assert_eq!(digest::<u32>(&canonical), digest::<u32>(&padded));
+        assert_ne!(digest::<u32>(&canonical), digest::<u32>(&canonical[2..]));
+        assert_eq!(digest::<i64>(&[0x7F]), digest::<i64>(&[0xFF, 0x7F]));
+    }
+
+    #[test]
+    fn truncated_input_errors() {
+        let mut hasher = DefaultHasher::new();
+        let err = hash_decoded::<u32, _>(&[0x01, 0x80], &mut hasher).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
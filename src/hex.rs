@@ -0,0 +1,73 @@
+//! Hex strings of encoded values, for tests, docs and log messages.
+//!
+//! ```
+//! use lebase::hex::{decode_hex, encode_hex};
+//!
+//! assert_eq!(encode_hex(624485u32), "e58e26");
+//! assert_eq!(decode_hex::<u32>("e5 8e 26").unwrap(), 624485);
+//! ```
+
+use std::{fmt::Write, io};
+
+use crate::LEB128Codec;
+
+/// Returns the encoding of `value` as lowercase hex without separators.
+pub fn encode_hex<N: LEB128Codec>(value: N) -> String {
+    let bytes = value.to_leb128_vec();
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        let _ = write!(hex, "{byte:02x}");
+    }
+    hex
+}
+
+/// Decodes a value from hex digits of either case, ignoring whitespace.
+/// Anything but exactly one encoded value fails with `InvalidData`.
+pub fn decode_hex<N: LEB128Codec>(hex: &str) -> Result<N, io::Error> {
+    let invalid = || io::Error::from(io::ErrorKind::InvalidData);
+    let digits = hex
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace())
+        .map(|c| c.to_digit(16).ok_or_else(invalid))
+        .collect::<Result<Vec<u32>, io::Error>>()?;
+    if !digits.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+    let bytes: Vec<u8> = digits
+        .chunks(2)
+        .map(|pair| (pair[0] << 4 | pair[1]) as u8)
+        .collect();
+    let mut readable = &bytes[..];
+    let value = N::leb128_decode(&mut readable)?;
+    if !readable.is_empty() {
+        return Err(invalid());
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{decode_hex, encode_hex};
+
+    #[test]
+    fn round_trips() {
+        assert_eq!(encode_hex(0u8), "00");
+        assert_eq!(encode_hex(-123456i64), "c0bb78");
+        assert_eq!(decode_hex::<i64>("C0BB78").unwrap(), -123456);
+        assert_eq!(
+            decode_hex::<u128>(&encode_hex(u128::MAX)).unwrap(),
+            u128::MAX
+        );
+        for bad in ["e58e2", "e58e26 01", "zz", "+1", "e58e"] {
+            let err = decode_hex::<u32>(bad).unwrap_err();
+            let expected = if bad == "e58e" {
+                io::ErrorKind::UnexpectedEof
+            } else {
+                io::ErrorKind::InvalidData
+            };
+            assert_eq!(err.kind(), expected, "{bad}");
+        }
+    }
+}
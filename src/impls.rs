@@ -0,0 +1,427 @@
+//! Implementations for standard library types that are not plain integers.
+
+use std::{
+    io,
+    num::{
+        NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+        NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{Error, LEB128Codec, LEB128Encode};
+
+/// A single `0` or `1` byte. Any other byte is rejected on decode.
+impl LEB128Codec for bool {
+    fn leb128_decode<R>(reader: &mut R) -> Result<Self, io::Error>
+    where
+        R: Sized + io::Read,
+        Self: Sized,
+    {
+        let mut buffer: [u8; 1] = [0];
+        reader.read_exact(&mut buffer)?;
+        match buffer[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(io::Error::from(io::ErrorKind::InvalidData)),
+        }
+    }
+}
+
+impl LEB128Encode for bool {
+    fn leb128_encode<W>(self, writer: &mut W) -> Result<usize, io::Error>
+    where
+        W: Sized + io::Write,
+        Self: Sized,
+    {
+        writer.write_all(&[self as u8])?;
+        Ok(1)
+    }
+}
+
+/// The scalar value as a `u32`. Surrogates and values above `char::MAX` are
+/// rejected on decode.
+impl LEB128Codec for char {
+    fn leb128_decode<R>(reader: &mut R) -> Result<Self, io::Error>
+    where
+        R: Sized + io::Read,
+        Self: Sized,
+    {
+        let scalar = u32::leb128_decode(reader)?;
+        char::from_u32(scalar).ok_or(io::Error::from(io::ErrorKind::InvalidData))
+    }
+}
+
+impl LEB128Encode for char {
+    fn leb128_encode<W>(self, writer: &mut W) -> Result<usize, io::Error>
+    where
+        W: Sized + io::Write,
+        Self: Sized,
+    {
+        (self as u32).leb128_encode(writer)
+    }
+}
+
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+fn decode_nanos<R>(reader: &mut R) -> Result<u32, io::Error>
+where
+    R: Sized + io::Read,
+{
+    let nanos = u32::leb128_decode(reader)?;
+    if nanos >= NANOS_PER_SEC {
+        return Err(Error::OutOfRange.into());
+    }
+    Ok(nanos)
+}
+
+/// Whole seconds as a `u64`, then the nanoseconds below them. Nanoseconds of
+/// a second or more are rejected with [`Error::OutOfRange`] on decode.
+impl LEB128Codec for Duration {
+    fn leb128_decode<R>(reader: &mut R) -> Result<Self, io::Error>
+    where
+        R: Sized + io::Read,
+        Self: Sized,
+    {
+        let secs = u64::leb128_decode(reader)?;
+        Ok(Duration::new(secs, decode_nanos(reader)?))
+    }
+}
+
+impl LEB128Encode for Duration {
+    fn leb128_encode<W>(self, writer: &mut W) -> Result<usize, io::Error>
+    where
+        W: Sized + io::Write,
+        Self: Sized,
+    {
+        let len = self.as_secs().leb128_encode(writer)?;
+        Ok(len + self.subsec_nanos().leb128_encode(writer)?)
+    }
+}
+
+/// The offset from `UNIX_EPOCH` as whole signed seconds, rounded down, then
+/// the nanoseconds above them, so times before the epoch encode too. The
+/// seconds are decoded as an `i128`, so every time encodes; within the `i64`
+/// range the bytes are those of an `i64`. Decoded times the platform cannot
+/// represent fail with [`Error::Overflow`].
+impl LEB128Codec for SystemTime {
+    fn leb128_decode<R>(reader: &mut R) -> Result<Self, io::Error>
+    where
+        R: Sized + io::Read,
+        Self: Sized,
+    {
+        let secs = i128::leb128_decode(reader)?;
+        let nanos = decode_nanos(reader)?;
+        let whole = u64::try_from(secs.unsigned_abs()).map_err(|_| Error::Overflow)?;
+        let time = if secs >= 0 {
+            UNIX_EPOCH.checked_add(Duration::new(whole, nanos))
+        } else {
+            UNIX_EPOCH
+                .checked_sub(Duration::from_secs(whole))
+                .and_then(|t| t.checked_add(Duration::from_nanos(nanos.into())))
+        };
+        time.ok_or(Error::Overflow.into())
+    }
+}
+
+impl LEB128Encode for SystemTime {
+    fn leb128_encode<W>(self, writer: &mut W) -> Result<usize, io::Error>
+    where
+        W: Sized + io::Write,
+        Self: Sized,
+    {
+        let (secs, nanos) = match self.duration_since(UNIX_EPOCH) {
+            Ok(after) => (i128::from(after.as_secs()), after.subsec_nanos()),
+            Err(before) => {
+                let before = before.duration();
+                let secs = -i128::from(before.as_secs());
+                match before.subsec_nanos() {
+                    0 => (secs, 0),
+                    nanos => (secs - 1, NANOS_PER_SEC - nanos),
+                }
+            }
+        };
+        let len = secs.leb128_encode(writer)?;
+        Ok(len + nanos.leb128_encode(writer)?)
+    }
+}
+
+macro_rules! impl_non_zero {
+    ($($ty:ty => $inner:ty),*) => {
+        $(
+            /// Encoded like the underlying integer. Decoding a zero fails with
+            /// [`Error::Zero`].
+            impl LEB128Codec for $ty {
+                fn leb128_decode<R>(reader: &mut R) -> Result<Self, io::Error>
+                where
+                    R: Sized + io::Read,
+                    Self: Sized,
+                {
+                    <$ty>::new(<$inner>::leb128_decode(reader)?).ok_or(Error::Zero.into())
+                }
+            }
+
+            impl LEB128Encode for $ty {
+                fn leb128_encode<W>(self, writer: &mut W) -> Result<usize, io::Error>
+                where
+                    W: Sized + io::Write,
+                    Self: Sized,
+                {
+                    self.get().leb128_encode(writer)
+                }
+            }
+        )*
+    };
+}
+
+impl_non_zero!(
+    NonZeroU8 => u8,
+    NonZeroU16 => u16,
+    NonZeroU32 => u32,
+    NonZeroU64 => u64,
+    NonZeroU128 => u128,
+    NonZeroUsize => usize,
+    NonZeroI8 => i8,
+    NonZeroI16 => i16,
+    NonZeroI32 => i32,
+    NonZeroI64 => i64,
+    NonZeroI128 => i128,
+    NonZeroIsize => isize
+);
+
+/// A presence byte, `0` for `None` or `1` for `Some`, followed by the value.
+impl<N: LEB128Codec> LEB128Codec for Option<N> {
+    fn leb128_decode<R>(reader: &mut R) -> Result<Self, io::Error>
+    where
+        R: Sized + io::Read,
+        Self: Sized,
+    {
+        if bool::leb128_decode(reader)? {
+            Ok(Some(N::leb128_decode(reader)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<N: LEB128Encode> LEB128Encode for Option<N> {
+    fn leb128_encode<W>(self, writer: &mut W) -> Result<usize, io::Error>
+    where
+        W: Sized + io::Write,
+        Self: Sized,
+    {
+        match self {
+            Some(num) => Ok(true.leb128_encode(writer)? + num.leb128_encode(writer)?),
+            None => false.leb128_encode(writer),
+        }
+    }
+}
+
+/// `K` values back to back, with no count prefix.
+impl<N: LEB128Codec, const K: usize> LEB128Codec for [N; K] {
+    fn leb128_decode<R>(reader: &mut R) -> Result<Self, io::Error>
+    where
+        R: Sized + io::Read,
+        Self: Sized,
+    {
+        let mut error = None;
+        let values: [Option<N>; K] = std::array::from_fn(|_| match error {
+            Some(_) => None,
+            None => N::leb128_decode(reader).map_err(|e| error = Some(e)).ok(),
+        });
+        match error {
+            Some(e) => Err(e),
+            None => Ok(values.map(|num| num.expect("every element decoded"))),
+        }
+    }
+}
+
+impl<N: LEB128Encode, const K: usize> LEB128Encode for [N; K] {
+    fn leb128_encode<W>(self, writer: &mut W) -> Result<usize, io::Error>
+    where
+        W: Sized + io::Write,
+        Self: Sized,
+    {
+        let mut written = 0;
+        for num in self {
+            written += num.leb128_encode(writer)?;
+        }
+        Ok(written)
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($name:ident)+) => {
+        /// Each element in order, with no separators or count.
+        impl<$($name: LEB128Codec),+> LEB128Codec for ($($name,)+) {
+            fn leb128_decode<R>(reader: &mut R) -> Result<Self, io::Error>
+            where
+                R: Sized + io::Read,
+                Self: Sized,
+            {
+                Ok(($($name::leb128_decode(reader)?,)+))
+            }
+        }
+
+        impl<$($name: LEB128Encode),+> LEB128Encode for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn leb128_encode<W>(self, writer: &mut W) -> Result<usize, io::Error>
+            where
+                W: Sized + io::Write,
+                Self: Sized,
+            {
+                let ($($name,)+) = self;
+                Ok(0 $(+ $name.leb128_encode(writer)?)+)
+            }
+        }
+    };
+}
+
+impl_tuple!(A);
+impl_tuple!(A B);
+impl_tuple!(A B C);
+impl_tuple!(A B C D);
+impl_tuple!(A B C D E);
+impl_tuple!(A B C D E F);
+impl_tuple!(A B C D E F G);
+impl_tuple!(A B C D E F G H);
+impl_tuple!(A B C D E F G H I);
+impl_tuple!(A B C D E F G H I J);
+impl_tuple!(A B C D E F G H I J K);
+impl_tuple!(A B C D E F G H I J K L);
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io,
+        num::{NonZeroI16, NonZeroU32},
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    };
+
+    use crate::{Error, LEB128Codec, LEB128Encode};
+
+    fn encode<N: LEB128Codec>(num: N) -> Vec<u8> {
+        let mut buf = Vec::new();
+        num.leb128_encode(&mut buf).unwrap();
+        buf
+    }
+
+    fn decode<N: LEB128Codec>(bytes: &[u8]) -> Result<N, io::Error> {
+        N::leb128_decode(&mut &bytes[..])
+    }
+
+    #[test]
+    fn bools() {
+        assert_eq!(encode(true), [1]);
+        assert_eq!(encode(false), [0]);
+        assert!(decode::<bool>(&[1]).unwrap());
+        assert!(!decode::<bool>(&[0]).unwrap());
+        assert_eq!(
+            decode::<bool>(&[2]).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn chars() {
+        for c in ['\0', 'a', 'é', '€', '\u{10FFFF}'] {
+            assert_eq!(decode::<char>(&encode(c)).unwrap(), c);
+        }
+        assert_eq!(encode('€'), encode(0x20ACu32));
+        for scalar in [0xD800u32, 0xDFFF, 0x110000] {
+            let err = decode::<char>(&encode(scalar)).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn non_zero() {
+        let index = NonZeroU32::new(624485).unwrap();
+        assert_eq!(encode(index), encode(624485u32));
+        assert_eq!(decode::<NonZeroU32>(&encode(index)).unwrap(), index);
+        assert_eq!(decode::<NonZeroI16>(&[0x7F]).unwrap().get(), -1);
+        let err = decode::<NonZeroU32>(&[0x00]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(Error::from_io_error(&err), Some(Error::Zero));
+    }
+
+    #[test]
+    fn durations() {
+        let duration = Duration::new(300, 5);
+        assert_eq!(encode(duration), [0xAC, 0x02, 0x05]);
+        assert_eq!(decode::<Duration>(&encode(duration)).unwrap(), duration);
+        assert_eq!(
+            decode::<Duration>(&encode(Duration::MAX)).unwrap(),
+            Duration::MAX
+        );
+        let mut bytes = encode(1u64);
+        bytes.extend(encode(1_000_000_000u32));
+        let err = decode::<Duration>(&bytes).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::OutOfRange));
+    }
+
+    #[test]
+    fn system_times() {
+        let after = UNIX_EPOCH + Duration::new(1_700_000_000, 250);
+        assert_eq!(decode::<SystemTime>(&encode(after)).unwrap(), after);
+        // Half a second before the epoch is one second back plus a half.
+        let before = UNIX_EPOCH - Duration::from_millis(500);
+        assert_eq!(encode(before), [0x7F, 0x80, 0xCA, 0xB5, 0xEE, 0x01]);
+        assert_eq!(decode::<SystemTime>(&encode(before)).unwrap(), before);
+        let whole = UNIX_EPOCH - Duration::from_secs(64);
+        assert_eq!(encode(whole), [0x40, 0x00]);
+        assert_eq!(decode::<SystemTime>(&encode(whole)).unwrap(), whole);
+
+        // The earliest time whose seconds fit, where the platform has it.
+        if let Some(earliest) = UNIX_EPOCH.checked_sub(Duration::from_secs(1 << 63)) {
+            let bytes = encode(earliest);
+            assert_eq!(decode::<i64>(&bytes).unwrap(), i64::MIN);
+            assert_eq!(decode::<SystemTime>(&bytes).unwrap(), earliest);
+            assert_eq!(earliest.to_leb128_vec(), bytes);
+        }
+        let mut beyond = encode(i128::from(u64::MAX) + 1);
+        beyond.push(0);
+        let err = decode::<SystemTime>(&beyond).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+    }
+
+    #[test]
+    fn options() {
+        assert_eq!(encode(None::<u64>), [0]);
+        assert_eq!(encode(Some(0u64)), [1, 0]);
+        assert_eq!(encode(Some(-128i8)), [1, 0x80, 0x7F]);
+        assert_eq!(decode::<Option<i8>>(&[1, 0x80, 0x7F]).unwrap(), Some(-128));
+        assert_eq!(decode::<Option<u32>>(&[0]).unwrap(), None);
+        assert_eq!(decode::<Option<Option<u8>>>(&[1, 0]).unwrap(), Some(None));
+        assert!(decode::<Option<u32>>(&[2, 0]).is_err());
+    }
+
+    #[test]
+    fn tuples() {
+        let record = (624485u32, -1i64, true, 'x');
+        let bytes = encode(record);
+        assert_eq!(bytes, [0xE5, 0x8E, 0x26, 0x7F, 1, 0x78]);
+        assert_eq!(decode::<(u32, i64, bool, char)>(&bytes).unwrap(), record);
+        let wide = (
+            1u8, 2u8, 3u8, 4u8, 5u8, 6u8, 7u8, 8u8, 9u8, 10u8, 11u8, 300u16,
+        );
+        assert_eq!(
+            decode::<(u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u16)>(&encode(wide)).unwrap(),
+            wide
+        );
+        assert!(decode::<(u8, u8)>(&[1]).is_err());
+    }
+
+    #[test]
+    fn arrays() {
+        let values = [1u32, 300, 624485];
+        let bytes = encode(values);
+        assert_eq!(bytes, [0x01, 0xAC, 0x02, 0xE5, 0x8E, 0x26]);
+        assert_eq!(decode::<[u32; 3]>(&bytes).unwrap(), values);
+        assert_eq!(decode::<[u32; 0]>(&[]).unwrap(), [0u32; 0]);
+        let non_zero = decode::<[NonZeroU32; 2]>(&bytes).unwrap();
+        assert_eq!(non_zero.map(NonZeroU32::get), [1, 300]);
+        let err = decode::<[u32; 4]>(&bytes).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
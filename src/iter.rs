@@ -0,0 +1,355 @@
+//! Iterating over a stream of values.
+
+use std::{error, fmt, io, marker::PhantomData};
+
+use num_traits::PrimInt;
+
+use crate::{
+    get_7bits, get_shr, is_encode_end,
+    sketch::{Sketch, Sketched},
+    LEB128Codec, CONTINUATION,
+};
+
+/// Decodes values from a reader until it is exhausted at a value boundary.
+/// Running out of input inside a value yields an `UnexpectedEof` error.
+pub struct DecodeIter<R, N> {
+    reader: R,
+    done: bool,
+    _num: PhantomData<fn() -> N>,
+}
+
+pub fn decode_iter<N, R>(reader: R) -> DecodeIter<R, N>
+where
+    N: LEB128Codec,
+    R: io::Read,
+{
+    DecodeIter {
+        reader,
+        done: false,
+        _num: PhantomData,
+    }
+}
+
+impl<R: io::Read, N: LEB128Codec> DecodeIter<R, N> {
+    /// Records every successfully decoded value in `sketch`.
+    pub fn sketched(self, sketch: &mut Sketch<N>) -> Sketched<'_, Self, N>
+    where
+        N: num_traits::PrimInt + std::hash::Hash,
+    {
+        Sketched::new(self, sketch)
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: io::Read, N: LEB128Codec> Iterator for DecodeIter<R, N> {
+    type Item = Result<N, io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = N::leb128_try_decode(&mut self.reader).transpose();
+        self.done = !matches!(result, Some(Ok(_)));
+        result
+    }
+}
+
+/// The bytes of a primitive value's encoding, produced on demand.
+pub(crate) struct EncodedBytes<N> {
+    num: N,
+    remaining: usize,
+}
+
+impl<N: PrimInt> EncodedBytes<N> {
+    pub(crate) fn new(num: N) -> EncodedBytes<N> {
+        let shr = get_shr::<N>();
+        let mut rest = num;
+        let mut remaining = 1;
+        while !is_encode_end(rest) {
+            rest = shr(rest, 7);
+            remaining += 1;
+        }
+        EncodedBytes { num, remaining }
+    }
+}
+
+impl<N: PrimInt> Iterator for EncodedBytes<N> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let byte = get_7bits(self.num);
+        self.num = get_shr::<N>()(self.num, 7);
+        self.remaining -= 1;
+        Some(if self.remaining == 0 {
+            byte
+        } else {
+            byte | CONTINUATION
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<N: PrimInt> ExactSizeIterator for EncodedBytes<N> {}
+
+/// Presents a byte iterator as a reader, one byte per `read` so that a
+/// decoder never pulls bytes past the end of its value.
+struct IterReader<I> {
+    bytes: I,
+    consumed: usize,
+}
+
+impl<I: Iterator<Item = u8>> io::Read for IterReader<I> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let Some(slot) = buf.first_mut() else {
+            return Ok(0);
+        };
+        match self.bytes.next() {
+            Some(byte) => {
+                *slot = byte;
+                self.consumed += 1;
+                Ok(1)
+            }
+            None => Ok(0),
+        }
+    }
+}
+
+/// Decodes one value from the front of `bytes`, returning it and the number
+/// of bytes it took. Pass `&mut iter` to keep using the iterator afterwards;
+/// nothing past the value is taken from it.
+///
+/// ```
+/// use lebase::iter::decode_from_iter;
+///
+/// let mut bytes = [0xE5, 0x8E, 0x26, 0x7F].into_iter();
+/// assert_eq!(decode_from_iter::<u32, _>(&mut bytes).unwrap(), (624485, 3));
+/// assert_eq!(bytes.next(), Some(0x7F));
+/// ```
+pub fn decode_from_iter<N, I>(bytes: I) -> Result<(N, usize), io::Error>
+where
+    N: LEB128Codec,
+    I: IntoIterator<Item = u8>,
+{
+    let mut reader = IterReader {
+        bytes: bytes.into_iter(),
+        consumed: 0,
+    };
+    let value = N::leb128_decode(&mut reader)?;
+    Ok((value, reader.consumed))
+}
+
+/// A source of bytes with its own error type, for transports whose failures
+/// should not be squeezed into `io::Error`. Implemented for iterators of
+/// `Result<u8, E>`.
+pub trait ByteSource {
+    type Error;
+
+    /// Returns the next byte, or `None` at the end of the input.
+    fn next_byte(&mut self) -> Result<Option<u8>, Self::Error>;
+}
+
+impl<E, I: Iterator<Item = Result<u8, E>>> ByteSource for I {
+    type Error = E;
+
+    fn next_byte(&mut self) -> Result<Option<u8>, E> {
+        self.next().transpose()
+    }
+}
+
+/// A failure of [`decode_from_source`].
+#[derive(Debug)]
+pub enum Leb128Error<E> {
+    /// The source failed.
+    Source(E),
+    /// The source ended inside a value.
+    UnexpectedEof,
+    /// The bytes do not encode a value of the target type. The error is the
+    /// one the `io` based decoders return, with any [`Error`](crate::Error)
+    /// and [`Location`](crate::Location) attached.
+    Invalid(io::Error),
+}
+
+impl<E: fmt::Display> fmt::Display for Leb128Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Leb128Error::Source(e) => write!(f, "byte source failed: {e}"),
+            Leb128Error::UnexpectedEof => write!(f, "unexpected end of input"),
+            Leb128Error::Invalid(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<E: error::Error + 'static> error::Error for Leb128Error<E> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Leb128Error::Source(e) => Some(e),
+            Leb128Error::UnexpectedEof => None,
+            Leb128Error::Invalid(e) => e.source(),
+        }
+    }
+}
+
+struct SourceReader<'a, S: ByteSource> {
+    source: &'a mut S,
+    consumed: usize,
+    failure: Option<S::Error>,
+}
+
+impl<S: ByteSource> io::Read for SourceReader<'_, S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let Some(slot) = buf.first_mut() else {
+            return Ok(0);
+        };
+        match self.source.next_byte() {
+            Ok(Some(byte)) => {
+                *slot = byte;
+                self.consumed += 1;
+                Ok(1)
+            }
+            Ok(None) => Ok(0),
+            Err(e) => {
+                self.failure = Some(e);
+                Err(io::Error::other("byte source failed"))
+            }
+        }
+    }
+}
+
+/// Like [`decode_from_iter`], but reads from a fallible source and returns
+/// its errors unchanged.
+///
+/// ```
+/// use lebase::iter::{decode_from_source, Leb128Error};
+///
+/// let mut bytes = [Ok(0xE5), Ok(0x8E), Err("link down")].into_iter();
+/// match decode_from_source::<u32, _>(&mut bytes) {
+///     Err(Leb128Error::Source(e)) => assert_eq!(e, "link down"),
+///     other => panic!("{other:?}"),
+/// }
+/// ```
+pub fn decode_from_source<N, S>(source: &mut S) -> Result<(N, usize), Leb128Error<S::Error>>
+where
+    N: LEB128Codec,
+    S: ByteSource,
+{
+    let mut reader = SourceReader {
+        source,
+        consumed: 0,
+        failure: None,
+    };
+    let result = N::leb128_decode(&mut reader);
+    if let Some(e) = reader.failure {
+        return Err(Leb128Error::Source(e));
+    }
+    match result {
+        Ok(value) => Ok((value, reader.consumed)),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Err(Leb128Error::UnexpectedEof),
+        Err(e) => Err(Leb128Error::Invalid(e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{decode_from_iter, decode_from_source, decode_iter, ByteSource, Leb128Error};
+    use crate::{Error, LEB128Encode, Location};
+
+    #[test]
+    fn stops_at_value_boundary() {
+        let values: Vec<u32> = decode_iter(&[0x01, 0xE5, 0x8E, 0x26, 0x7F][..])
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(values, [1, 624485, 127]);
+        let mut truncated = decode_iter::<u32, _>(&[0x01, 0xE5][..]);
+        assert_eq!(truncated.next().unwrap().unwrap(), 1);
+        let err = truncated.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert!(truncated.next().is_none());
+    }
+
+    #[test]
+    fn decodes_from_byte_iterators() {
+        let bytes = [0x7F, 0xAC, 0x02];
+        let mut iter = bytes.iter().copied();
+        assert_eq!(decode_from_iter::<i8, _>(&mut iter).unwrap(), (-1, 1));
+        assert_eq!(decode_from_iter::<u16, _>(&mut iter).unwrap(), (300, 2));
+        let err = decode_from_iter::<u16, _>(&mut iter).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        let generated = (0..3).map(|i| if i < 2 { 0xFF } else { 0x7F });
+        let err = decode_from_iter::<u8, _>(generated).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+        assert_eq!(Location::of(&err).unwrap().offset, 1);
+    }
+
+    #[test]
+    fn propagates_source_errors() {
+        struct Port<'a>(&'a [u8]);
+
+        impl ByteSource for Port<'_> {
+            type Error = u16;
+
+            fn next_byte(&mut self) -> Result<Option<u8>, u16> {
+                match self.0.split_first() {
+                    Some((&0xEE, _)) => Err(503),
+                    Some((&byte, rest)) => {
+                        self.0 = rest;
+                        Ok(Some(byte))
+                    }
+                    None => Ok(None),
+                }
+            }
+        }
+
+        let mut port = Port(&[0xAC, 0x02, 0x80, 0xEE]);
+        assert_eq!(decode_from_source::<u32, _>(&mut port).unwrap(), (300, 2));
+        assert!(matches!(
+            decode_from_source::<u32, _>(&mut port),
+            Err(Leb128Error::Source(503))
+        ));
+        let mut short = Port(&[0x80]);
+        assert!(matches!(
+            decode_from_source::<u32, _>(&mut short),
+            Err(Leb128Error::UnexpectedEof)
+        ));
+        let mut overflowing = [0x80, 0x02].map(Ok::<u8, ()>).into_iter();
+        match decode_from_source::<u8, _>(&mut overflowing) {
+            Err(Leb128Error::Invalid(e)) => {
+                assert_eq!(Error::from_io_error(&e), Some(Error::Overflow))
+            }
+            other => panic!("{other:?}"),
+        }
+    }
+
+    #[test]
+    fn yields_encoded_bytes_lazily() {
+        let mut bytes = 624485u32.leb128_bytes();
+        assert_eq!(bytes.len(), 3);
+        assert_eq!(bytes.next(), Some(0xE5));
+        assert_eq!(bytes.len(), 2);
+        assert_eq!(bytes.collect::<Vec<_>>(), [0x8E, 0x26]);
+        for value in [0i64, -1, 63, 64, -64, -65, i64::MIN, i64::MAX] {
+            assert_eq!(
+                value.leb128_bytes().collect::<Vec<_>>(),
+                value.to_leb128_vec()
+            );
+            assert_eq!(
+                (value as isize).leb128_bytes().len(),
+                value.leb128_bytes().len()
+            );
+        }
+        let mut out = vec![0xAA];
+        out.extend(u128::MAX.leb128_bytes());
+        assert_eq!(out.len(), 20);
+    }
+}
@@ -0,0 +1,89 @@
+//! wasm-bindgen exports for JavaScript. 32-bit values cross as numbers and
+//! 64-bit values as `BigInt`. The library is an rlib, so build the module
+//! with `cargo rustc --lib --release --crate-type cdylib --features wasm
+//! --target wasm32-unknown-unknown` and run `wasm-bindgen` on the output.
+//!
+//! ```js
+//! const bytes = encodeU64(624485n);   // Uint8Array [0xe5, 0x8e, 0x26]
+//! const value = decodeU64(bytes, 0);  // 624485n
+//! const next = encodedLength(bytes, 0); // 3
+//! ```
+
+use std::io;
+
+use wasm_bindgen::prelude::*;
+
+use crate::{scan, LEB128Codec, LEB128Encode};
+
+fn decode<N: LEB128Codec>(bytes: &[u8], offset: usize) -> Result<N, io::Error> {
+    let mut rest = bytes
+        .get(offset..)
+        .ok_or(io::Error::from(io::ErrorKind::UnexpectedEof))?;
+    N::leb128_decode(&mut rest)
+}
+
+fn js_error(err: io::Error) -> JsError {
+    JsError::new(&err.to_string())
+}
+
+macro_rules! exports {
+    ($($ty:ty => $encode:ident / $encode_js:literal, $decode:ident / $decode_js:literal),*) => {
+        $(
+            #[wasm_bindgen(js_name = $encode_js)]
+            pub fn $encode(value: $ty) -> Vec<u8> {
+                value.to_leb128_vec()
+            }
+
+            /// Decodes the value starting at `offset`.
+            #[wasm_bindgen(js_name = $decode_js)]
+            pub fn $decode(bytes: &[u8], offset: usize) -> Result<$ty, JsError> {
+                decode(bytes, offset).map_err(js_error)
+            }
+        )*
+    };
+}
+
+exports!(
+    u32 => encode_u32 / "encodeU32", decode_u32 / "decodeU32",
+    i32 => encode_i32 / "encodeI32", decode_i32 / "decodeI32",
+    u64 => encode_u64 / "encodeU64", decode_u64 / "decodeU64",
+    i64 => encode_i64 / "encodeI64", decode_i64 / "decodeI64"
+);
+
+/// Length of the value starting at `offset`, for stepping to the next one.
+#[wasm_bindgen(js_name = "encodedLength")]
+pub fn encoded_length(bytes: &[u8], offset: usize) -> Result<usize, JsError> {
+    let rest = bytes
+        .get(offset..)
+        .ok_or(io::Error::from(io::ErrorKind::UnexpectedEof))
+        .map_err(js_error)?;
+    scan::leb128_skip_slice(rest).map_err(js_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+
+    // `JsError` needs a JavaScript host, so native tests stop short of the
+    // error paths of the exports.
+    #[test]
+    fn exports_round_trip() {
+        let bytes = [encode_u64(624485), encode_i64(-1), encode_u32(u32::MAX)].concat();
+        assert_eq!(&bytes[..3], [0xE5, 0x8E, 0x26]);
+        assert_eq!(decode_u64(&bytes, 0).unwrap(), 624485);
+        assert_eq!(encoded_length(&bytes, 0).unwrap(), 3);
+        assert_eq!(decode_i64(&bytes, 3).unwrap(), -1);
+        assert_eq!(decode_u32(&bytes, 4).unwrap(), u32::MAX);
+        assert_eq!(decode_i32(&encode_i32(i32::MIN), 0).unwrap(), i32::MIN);
+    }
+
+    #[test]
+    fn offsets_past_the_end() {
+        let err = decode::<u32>(&[0x01], 2).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        let err = decode::<u32>(&[0x80], 0).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -1,19 +1,284 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+#![cfg_attr(feature = "portable_simd", feature(portable_simd))]
+#![cfg_attr(not(test), deny(clippy::unwrap_used))]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "std")]
 use std::io;
 
+#[cfg(feature = "alloc")]
 use num_traits::{PrimInt, Zero};
-pub trait LEB128Codec {
+
+#[cfg(feature = "std")]
+pub mod backpatch;
+#[cfg(feature = "std")]
+pub mod ber;
+#[cfg(feature = "bigint")]
+mod bigint;
+#[cfg(feature = "std")]
+pub mod bitstream;
+#[cfg(feature = "std")]
+pub mod bulk;
+#[cfg(feature = "std")]
+pub mod bytes;
+#[cfg(feature = "std")]
+pub mod canonical;
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "std")]
+pub mod checked;
+#[cfg(feature = "std")]
+mod checksum;
+#[cfg(feature = "std")]
+pub mod chunked;
+#[cfg(feature = "std")]
+pub mod collections;
+#[cfg(feature = "std")]
+pub mod compat;
+pub mod const_fn;
+#[cfg(feature = "std")]
+pub mod constant_time;
+#[cfg(feature = "std")]
+pub mod delta;
+#[cfg(feature = "std")]
+pub mod dex;
+#[cfg(feature = "std")]
+pub mod discriminant;
+#[cfg(feature = "std")]
+pub mod dump;
+#[cfg(feature = "std")]
+pub mod dwarf;
+#[cfg(feature = "std")]
+pub mod elias;
+mod error;
+#[cfg(any(feature = "heapless", feature = "arrayvec"))]
+pub mod fixed;
+#[cfg(feature = "std")]
+pub mod flagged;
+#[cfg(feature = "std")]
+pub mod float;
+#[cfg(feature = "std")]
+pub mod format;
+#[cfg(feature = "std")]
+pub mod framing;
+#[cfg(feature = "arbitrary")]
+pub mod fuzz;
+#[cfg(feature = "std")]
+pub mod golomb;
+#[cfg(any(feature = "bigint", feature = "ethnum", feature = "ruint"))]
+mod groups;
+#[cfg(feature = "std")]
+pub mod hash;
+#[cfg(feature = "std")]
+pub mod hex;
+#[cfg(feature = "std")]
+mod impls;
+#[cfg(feature = "std")]
+pub mod iter;
+#[cfg(feature = "wasm")]
+pub mod js;
+#[cfg(feature = "memmap2")]
+pub mod mmap;
+#[cfg(feature = "std")]
+pub mod ordered;
+#[cfg(feature = "std")]
+pub mod packer;
+#[cfg(feature = "std")]
+pub mod prefix;
+#[cfg(feature = "std")]
+pub mod protobuf;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "std")]
+pub mod reader;
+#[cfg(feature = "std")]
+pub mod record;
+#[cfg(feature = "std")]
+pub mod rle;
+#[cfg(feature = "std")]
+pub mod sansio;
+#[cfg(feature = "std")]
+pub mod scan;
+#[cfg(feature = "serde")]
+pub mod serde;
+#[cfg(feature = "std")]
+pub mod shm;
+#[cfg(feature = "std")]
+pub mod sketch;
+#[cfg(feature = "proptest")]
+pub mod strategy;
+#[cfg(feature = "std")]
+pub mod stream_vbyte;
+#[cfg(feature = "std")]
+pub mod tail;
+#[cfg(feature = "std")]
+mod unrolled;
+#[cfg(feature = "std")]
+pub mod varint;
+#[cfg(feature = "alloc")]
+pub mod vec;
+#[cfg(feature = "std")]
+pub mod vectors;
+#[cfg(feature = "std")]
+pub mod vlq;
+#[cfg(feature = "std")]
+pub mod wasm;
+#[cfg(any(feature = "ethnum", feature = "ruint"))]
+mod wide;
+#[cfg(feature = "std")]
+pub mod writer;
+
+#[cfg(feature = "std")]
+pub use error::Location;
+pub use error::{Error, ErrorCode};
+
+/// The encoding half of [`LEB128Codec`], split out so that references to
+/// `Copy` values, which cannot be decoded into, still encode.
+#[cfg(feature = "std")]
+pub trait LEB128Encode {
+    fn leb128_encode<W>(self, writer: &mut W) -> Result<usize, io::Error>
+    where
+        W: Sized + io::Write,
+        Self: Sized;
+
+    /// Appends the encoding to `out`, returning its length. The primitive
+    /// types write straight into the spare capacity instead of going through
+    /// `io::Write`.
+    ///
+    /// # Panics
+    ///
+    /// The default panics if [`leb128_encode`](Self::leb128_encode) fails,
+    /// so it, [`to_leb128_vec`](Self::to_leb128_vec) and
+    /// [`leb128_bytes`](Self::leb128_bytes) suit only types that can fail
+    /// through their writer alone. Every type in this crate encodes any
+    /// value it can hold.
+    fn leb128_encode_to_vec(self, out: &mut Vec<u8>) -> usize
+    where
+        Self: Sized,
+    {
+        self.leb128_encode(out)
+            .expect("an infallible encoder failed")
+    }
+
+    /// Returns the encoding in a new `Vec`.
+    fn to_leb128_vec(self) -> Vec<u8>
+    where
+        Self: Sized,
+    {
+        let mut out = Vec::new();
+        self.leb128_encode_to_vec(&mut out);
+        out
+    }
+
+    /// Yields the encoded bytes one at a time, for extending containers and
+    /// iterator pipelines. The primitive types produce them lazily.
+    fn leb128_bytes(self) -> impl ExactSizeIterator<Item = u8>
+    where
+        Self: Sized,
+    {
+        self.to_leb128_vec().into_iter()
+    }
+}
+
+#[cfg(feature = "std")]
+pub trait LEB128Codec: LEB128Encode {
     fn leb128_decode<R>(reader: &mut R) -> Result<Self, io::Error>
     where
         R: Sized + io::Read,
         Self: Sized;
-    fn leb128_encode<W>(self, writer: &mut W) -> Result<usize, io::Error>
+
+    /// Decodes a value that must fill `bytes` exactly, such as a fixed field
+    /// cut out of a larger buffer. Leftover bytes fail with
+    /// [`Error::TrailingBytes`], located at the first of them.
+    fn from_leb128_bytes(bytes: &[u8]) -> Result<Self, io::Error>
+    where
+        Self: Sized,
+    {
+        let mut rest = bytes;
+        let value = Self::leb128_decode(&mut rest)?;
+        if !rest.is_empty() {
+            return Err(Error::TrailingBytes.at(bytes.len() - rest.len(), 0));
+        }
+        Ok(value)
+    }
+
+    /// Decodes straight out of the reader's buffer when the whole value is
+    /// already there, falling back to `leb128_decode` when it straddles a
+    /// refill.
+    fn leb128_decode_buffered<R>(reader: &mut R) -> Result<Self, io::Error>
+    where
+        R: Sized + io::BufRead,
+        Self: Sized,
+    {
+        let buf = reader.fill_buf()?;
+        match buf.iter().position(|byte| byte & CONTINUATION == 0) {
+            Some(end) => {
+                let value = Self::leb128_decode(&mut &buf[..=end]);
+                reader.consume(end + 1);
+                value
+            }
+            None => Self::leb128_decode(reader),
+        }
+    }
+
+    /// Returns `None` if the reader is exhausted before the first byte. Input
+    /// ending after it, inside the value, is still an `UnexpectedEof` error.
+    fn leb128_try_decode<R>(reader: &mut R) -> Result<Option<Self>, io::Error>
+    where
+        R: Sized + io::Read,
+        Self: Sized,
+    {
+        let mut first = [0];
+        loop {
+            match reader.read(&mut first) {
+                Ok(0) => return Ok(None),
+                Ok(_) => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Self::leb128_decode(&mut io::Read::chain(&first[..], reader)).map(Some)
+    }
+}
+
+/// Writes a value using exactly `width` bytes, padding the minimal encoding
+/// with redundant continuation bytes. Used for relocatable immediates and size
+/// fields that are patched in place.
+#[cfg(feature = "std")]
+pub trait LEB128Padded: LEB128Codec {
+    /// Fails with `InvalidInput` if the value needs more than `width` bytes or
+    /// `width` exceeds the longest encoding the type's decoder accepts.
+    fn leb128_encode_padded<W>(self, writer: &mut W, width: usize) -> Result<usize, io::Error>
     where
         W: Sized + io::Write,
         Self: Sized;
+
+    /// Decodes a value that may have been written padded. Lenient mode
+    /// accepts padding up to the longest encoding of the type, as
+    /// [`leb128_decode`](LEB128Codec::leb128_decode) does; strict mode fails
+    /// with [`Error::TooManyBytes`] on anything but the minimal encoding.
+    fn leb128_decode_padded<R>(reader: &mut R, padding: Padding) -> Result<Self, io::Error>
+    where
+        R: Sized + io::Read,
+        Self: Sized;
+}
+
+/// Whether [`LEB128Padded::leb128_decode_padded`] accepts padded encodings.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Padding {
+    /// Only minimal encodings.
+    Strict,
+    /// Minimal or padded encodings.
+    #[default]
+    Lenient,
 }
 
 pub const CONTINUATION: u8 = 1 << 7;
 
+#[cfg(any(feature = "alloc", feature = "heapless", feature = "arrayvec"))]
 fn get_shr<N: num_traits::PrimInt>() -> fn(N, u32) -> N {
     if is_signed::<N>() {
         N::signed_shr
@@ -21,9 +286,11 @@ fn get_shr<N: num_traits::PrimInt>() -> fn(N, u32) -> N {
         N::unsigned_shr
     }
 }
+#[cfg(any(feature = "alloc", feature = "heapless", feature = "arrayvec"))]
 fn is_signed<N: num_traits::PrimInt>() -> bool {
-    return N::zero().checked_sub(&N::one()).is_some();
+    N::zero().checked_sub(&N::one()).is_some()
 }
+#[cfg(any(feature = "alloc", feature = "heapless", feature = "arrayvec"))]
 fn is_encode_end<N: num_traits::PrimInt>(num: N) -> bool {
     let shr = get_shr::<N>();
     if is_signed::<N>() {
@@ -34,15 +301,27 @@ fn is_encode_end<N: num_traits::PrimInt>(num: N) -> bool {
         num.is_zero()
     }
 }
+#[cfg(any(feature = "alloc", feature = "heapless", feature = "arrayvec"))]
 fn get_7bits<N: num_traits::PrimInt>(num: N) -> u8 {
     let bits = N::zero().count_zeros() as usize;
     let shift = bits - 7;
-    (num << shift).unsigned_shr(shift as u32).to_u8().unwrap()
+    // Seven bits always fit a `u8`.
+    (num << shift)
+        .unsigned_shr(shift as u32)
+        .to_u8()
+        .unwrap_or(0)
 }
+/// Widens a seven bit group. Every primitive integer holds seven bits, so
+/// the fallback is never taken.
+#[cfg(feature = "alloc")]
+fn from_group<N: num_traits::PrimInt>(group: u8) -> N {
+    N::from(group & !CONTINUATION).unwrap_or_else(N::zero)
+}
+#[cfg(feature = "alloc")]
 fn last_byte_overflow<N: num_traits::PrimInt>(byte: u8, shift: usize) -> bool {
     let bits = N::zero().count_zeros() as usize;
     let sections = bits / 7;
-    let max_shift = (sections * 7) as usize;
+    let max_shift = sections * 7;
     let used_bits = bits - max_shift;
     let is_last_byte = shift == max_shift;
     let signed = is_signed::<N>();
@@ -51,83 +330,336 @@ fn last_byte_overflow<N: num_traits::PrimInt>(byte: u8, shift: usize) -> bool {
     } else {
         byte
     };
-    let normalized = full_byte.signed_shr(used_bits as u32);
+    // For signed types the top used bit is the sign, so it must agree with
+    // the padding above it.
+    let normalized = full_byte.signed_shr(used_bits as u32 - signed as u32);
     is_last_byte && !(normalized.is_zero() || (signed && ((normalized ^ 0xFF).is_zero())))
 }
 
-impl<N: num_traits::PrimInt> LEB128Codec for N {
-    fn leb128_decode<R>(reader: &mut R) -> Result<Self, io::Error>
-    where
-        R: Sized + io::Read,
-        Self: Sized,
-    {
-        let mut num = N::zero();
-        let bits = num.count_zeros() as usize;
-        let mut buffer: [u8; 1] = [0];
-        let mut shift = 0;
-        loop {
-            reader.read_exact(&mut buffer)?;
-            let ends = (buffer[0] & CONTINUATION) == 0;
-            if !ends {
-                buffer[0] = buffer[0] ^ CONTINUATION;
-            }
-            let num_like: N = N::from(buffer[0]).unwrap();
+/// Folds the byte at `shift` into `num`, returning whether it ends the value.
+/// On overflow `shift` is left at the offending byte.
+#[cfg(feature = "alloc")]
+fn decode_step<N: num_traits::PrimInt>(
+    num: &mut N,
+    shift: &mut usize,
+    byte: u8,
+) -> Result<bool, Error> {
+    let bits = N::zero().count_zeros() as usize;
+    let max_shift = (bits / 7) * 7;
+    if *shift > max_shift {
+        return Err(Error::Overflow);
+    }
+    let ends = (byte & CONTINUATION) == 0;
+    let group = byte & !CONTINUATION;
+    if last_byte_overflow::<N>(group, *shift) {
+        return Err(Error::Overflow);
+    }
+    *num = *num | (from_group::<N>(group) << *shift);
+    *shift += 7;
+    if ends && is_signed::<N>() && !(group >> 6).is_zero() && *shift < bits {
+        let empty_bits = bits - *shift;
+        *num = (*num << empty_bits).signed_shr(empty_bits as u32);
+    }
+    Ok(ends)
+}
 
-            if last_byte_overflow::<N>(buffer[0], shift) {
-                return Err(io::Error::from(io::ErrorKind::InvalidData));
-            }
-            num = num | (num_like << shift);
-            shift += 7;
-            if ends {
-                if is_signed::<Self>() && !(buffer[0] >> 6).is_zero() && shift < bits {
-                    let empty_bits = bits - shift;
-                    num = (num << empty_bits).signed_shr(empty_bits as u32);
-                }
-                break Ok(num);
-            }
+#[cfg(feature = "std")]
+fn decode_prim<N: num_traits::PrimInt, R>(reader: &mut R) -> Result<N, io::Error>
+where
+    R: Sized + io::Read,
+{
+    let mut num = N::zero();
+    let mut buffer: [u8; 1] = [0];
+    let mut shift = 0;
+    loop {
+        reader.read_exact(&mut buffer)?;
+        match decode_step(&mut num, &mut shift, buffer[0]) {
+            Ok(true) => break Ok(num),
+            Ok(false) => {}
+            Err(e) => break Err(e.at(shift / 7, shift)),
         }
     }
+}
 
-    fn leb128_encode<W>(self, writer: &mut W) -> Result<usize, io::Error>
-    where
-        W: Sized + io::Write,
-        Self: Sized,
-    {
-        let mut num = self;
-        let mut bytes_written = 0;
-        let shr = get_shr::<Self>();
-        loop {
-            let byte: u8 = get_7bits(num);
-            let ends = is_encode_end(num);
-            num = shr(num, 7);
-            let out = if ends {
-                byte & !CONTINUATION
-            } else {
-                byte | CONTINUATION
-            };
-            writer.write(&[out])?;
-            bytes_written += 1;
-            if ends {
-                break Ok(bytes_written);
-            };
+#[cfg(feature = "std")]
+fn decode_prim_padded<N: num_traits::PrimInt, R>(
+    reader: &mut R,
+    padding: Padding,
+) -> Result<N, io::Error>
+where
+    R: Sized + io::Read,
+{
+    let mut num = N::zero();
+    let mut bytes = [0u8; 2];
+    let mut shift = 0;
+    loop {
+        bytes[0] = bytes[1];
+        reader.read_exact(&mut bytes[1..])?;
+        match decode_step(&mut num, &mut shift, bytes[1]) {
+            Ok(true) => break,
+            Ok(false) => {}
+            Err(e) => return Err(e.at(shift / 7, shift)),
         }
     }
+    // Only the last two bytes decide whether the encoding is minimal.
+    let minimal = shift == 7 || canonical::is_canonical(&bytes, is_signed::<N>());
+    if padding == Padding::Strict && !minimal {
+        return Err(Error::TooManyBytes.at(shift / 7 - 1, shift - 7));
+    }
+    Ok(num)
 }
 
-#[cfg(test)]
-mod tests {
+#[cfg(feature = "std")]
+fn encode_prim<N: num_traits::PrimInt, W>(num: N, writer: &mut W) -> Result<usize, io::Error>
+where
+    W: Sized + io::Write,
+{
+    let mut num = num;
+    let mut bytes_written = 0;
+    let shr = get_shr::<N>();
+    loop {
+        let byte: u8 = get_7bits(num);
+        let ends = is_encode_end(num);
+        num = shr(num, 7);
+        let out = if ends {
+            byte & !CONTINUATION
+        } else {
+            byte | CONTINUATION
+        };
+        writer.write_all(&[out])?;
+        bytes_written += 1;
+        if ends {
+            break Ok(bytes_written);
+        };
+    }
+}
+
+#[cfg(any(feature = "alloc", feature = "heapless", feature = "arrayvec"))]
+fn max_encoded_len<N: num_traits::PrimInt>() -> usize {
+    (N::zero().count_zeros() as usize).div_ceil(7)
+}
 
-    use std::{
-        cmp::min,
-        fmt::Debug,
-        io::{self, Write},
+/// Appends the encoding of `num` to `out` with a single capacity check,
+/// writing the bytes into the spare capacity directly.
+#[cfg(feature = "alloc")]
+fn encode_prim_to_vec<N: num_traits::PrimInt>(num: N, out: &mut alloc::vec::Vec<u8>) -> usize {
+    let max_len = max_encoded_len::<N>();
+    out.reserve(max_len);
+    let spare = &mut out.spare_capacity_mut()[..max_len];
+    let shr = get_shr::<N>();
+    let mut num = num;
+    let mut len = 0;
+    loop {
+        let byte = get_7bits(num);
+        let ends = is_encode_end(num);
+        num = shr(num, 7);
+        spare[len].write(if ends {
+            byte & !CONTINUATION
+        } else {
+            byte | CONTINUATION
+        });
+        len += 1;
+        if ends {
+            break;
+        }
+    }
+    // SAFETY: the first `len` bytes of the spare capacity were just written.
+    unsafe { out.set_len(out.len() + len) };
+    len
+}
+
+#[cfg(feature = "std")]
+fn encode_prim_padded<N: num_traits::PrimInt, W>(
+    num: N,
+    writer: &mut W,
+    width: usize,
+) -> Result<usize, io::Error>
+where
+    W: Sized + io::Write,
+{
+    let mut bytes = Vec::with_capacity(width);
+    let len = encode_prim(num, &mut bytes)?;
+    if len > width || width > max_encoded_len::<N>() {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput));
+    }
+    if width > len {
+        let pad = if num < N::zero() { 0x7F } else { 0x00 };
+        bytes.resize(width, pad | CONTINUATION);
+        bytes[len - 1] |= CONTINUATION;
+        bytes[width - 1] = pad;
+    }
+    writer.write_all(&bytes)?;
+    Ok(width)
+}
+
+#[cfg(feature = "std")]
+macro_rules! impl_prim {
+    ($($ty:ty => $decode:path),*) => {
+        $(
+            impl LEB128Codec for $ty {
+                fn leb128_decode<R>(reader: &mut R) -> Result<Self, io::Error>
+                where
+                    R: Sized + io::Read,
+                    Self: Sized,
+                {
+                    $decode(reader)
+                }
+            }
+
+            impl LEB128Encode for $ty {
+                fn leb128_encode<W>(self, writer: &mut W) -> Result<usize, io::Error>
+                where
+                    W: Sized + io::Write,
+                    Self: Sized,
+                {
+                    encode_prim(self, writer)
+                }
+
+                fn leb128_encode_to_vec(self, out: &mut Vec<u8>) -> usize {
+                    encode_prim_to_vec(self, out)
+                }
+
+                fn leb128_bytes(self) -> impl ExactSizeIterator<Item = u8> {
+                    iter::EncodedBytes::new(self)
+                }
+            }
+
+            impl LEB128Padded for $ty {
+                fn leb128_encode_padded<W>(self, writer: &mut W, width: usize) -> Result<usize, io::Error>
+                where
+                    W: Sized + io::Write,
+                    Self: Sized,
+                {
+                    encode_prim_padded(self, writer, width)
+                }
+
+                fn leb128_decode_padded<R>(reader: &mut R, padding: Padding) -> Result<Self, io::Error>
+                where
+                    R: Sized + io::Read,
+                    Self: Sized,
+                {
+                    decode_prim_padded(reader, padding)
+                }
+            }
+        )*
     };
+}
+
+#[cfg(feature = "std")]
+impl_prim!(
+    u8 => decode_prim,
+    u16 => decode_prim,
+    u32 => unrolled::decode_u32,
+    u64 => unrolled::decode_u64,
+    u128 => decode_prim,
+    i8 => decode_prim,
+    i16 => decode_prim,
+    i32 => decode_prim,
+    i64 => unrolled::decode_i64,
+    i128 => decode_prim
+);
+
+#[cfg(feature = "std")]
+macro_rules! impl_portable {
+    ($($ty:ty => $wire:ty),*) => {
+        $(
+            /// Encoded exactly like the 64-bit type on every target. Decoding
+            /// a value that does not fit the target's pointer width fails with
+            /// `InvalidData` instead of truncating.
+            impl LEB128Codec for $ty {
+                fn leb128_decode<R>(reader: &mut R) -> Result<Self, io::Error>
+                where
+                    R: Sized + io::Read,
+                    Self: Sized,
+                {
+                    let wide = <$wire>::leb128_decode(reader)?;
+                    <$ty>::try_from(wide).map_err(|_| Error::Overflow.into())
+                }
+            }
+
+            impl LEB128Encode for $ty {
+                fn leb128_encode<W>(self, writer: &mut W) -> Result<usize, io::Error>
+                where
+                    W: Sized + io::Write,
+                    Self: Sized,
+                {
+                    (self as $wire).leb128_encode(writer)
+                }
+
+                fn leb128_encode_to_vec(self, out: &mut Vec<u8>) -> usize {
+                    (self as $wire).leb128_encode_to_vec(out)
+                }
+
+                fn leb128_bytes(self) -> impl ExactSizeIterator<Item = u8> {
+                    (self as $wire).leb128_bytes()
+                }
+            }
+
+            impl LEB128Padded for $ty {
+                fn leb128_encode_padded<W>(self, writer: &mut W, width: usize) -> Result<usize, io::Error>
+                where
+                    W: Sized + io::Write,
+                    Self: Sized,
+                {
+                    (self as $wire).leb128_encode_padded(writer, width)
+                }
+
+                fn leb128_decode_padded<R>(reader: &mut R, padding: Padding) -> Result<Self, io::Error>
+                where
+                    R: Sized + io::Read,
+                    Self: Sized,
+                {
+                    let wide = <$wire>::leb128_decode_padded(reader, padding)?;
+                    <$ty>::try_from(wide).map_err(|_| Error::Overflow.into())
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "std")]
+impl_portable!(usize => u64, isize => i64);
+
+#[cfg(feature = "std")]
+macro_rules! impl_ref {
+    ($($ref:ty),*) => {
+        $(
+            /// Encodes the referenced value, so that `slice.iter()` can feed
+            /// an encoder without `copied()`.
+            impl<N: LEB128Encode + Copy> LEB128Encode for $ref {
+                fn leb128_encode<W>(self, writer: &mut W) -> Result<usize, io::Error>
+                where
+                    W: Sized + io::Write,
+                    Self: Sized,
+                {
+                    (*self).leb128_encode(writer)
+                }
+
+                fn leb128_encode_to_vec(self, out: &mut Vec<u8>) -> usize {
+                    (*self).leb128_encode_to_vec(out)
+                }
+
+                fn leb128_bytes(self) -> impl ExactSizeIterator<Item = u8> {
+                    (*self).leb128_bytes()
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "std")]
+impl_ref!(&N, &mut N);
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+
+    use std::{cmp::min, fmt::Debug, io};
 
     use num_traits::PrimInt;
 
-    use crate::{is_signed, LEB128Codec};
+    use crate::{is_signed, Error, LEB128Codec, LEB128Encode, LEB128Padded, Location, Padding};
 
-    fn trip<N: PrimInt + std::fmt::Debug, O: PrimInt + std::fmt::Debug>(
+    fn trip<N: PrimInt + LEB128Codec + Debug, O: PrimInt + LEB128Codec + Debug>(
         num: N,
     ) -> Result<O, io::Error> {
         let mut buf = [0; 32];
@@ -137,7 +669,7 @@ mod tests {
         O::leb128_decode(&mut readable)
     }
 
-    fn assert_trip<N: PrimInt + std::fmt::Debug>(num: N) {
+    fn assert_trip<N: PrimInt + LEB128Codec + Debug>(num: N) {
         assert_eq!(
             num,
             trip(num).unwrap_or_else(|e| panic!(
@@ -163,6 +695,8 @@ mod tests {
         }
     }
 
+    // The casts spell out each width, even where the range already has it.
+    #[allow(clippy::unnecessary_cast)]
     #[test]
     fn signed_trips() {
         for x in -128..128 {
@@ -177,15 +711,20 @@ mod tests {
     }
 
     fn assert_trip_overflow<
-        Encode: PrimInt + std::fmt::Debug,
-        Decode: PrimInt + std::fmt::Debug,
+        Encode: PrimInt + LEB128Codec + Debug,
+        Decode: PrimInt + LEB128Codec + Debug,
     >(
         input: Encode,
     ) {
-        assert!(trip::<Encode, Decode>(input).unwrap_err().kind() == io::ErrorKind::InvalidData)
+        let err = trip::<Encode, Decode>(input).unwrap_err();
+        assert!(err.kind() == io::ErrorKind::InvalidData);
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
     }
 
-    fn test_overflow<Encode: PrimInt + std::fmt::Debug, Decode: PrimInt + std::fmt::Debug>(
+    fn test_overflow<
+        Encode: PrimInt + LEB128Codec + Debug,
+        Decode: PrimInt + LEB128Codec + Debug,
+    >(
         negative: bool,
     ) {
         let sign = if negative { -1 } else { 1 };
@@ -235,7 +774,10 @@ mod tests {
             }
         }
     }
-    fn assert_trip_exact<N: PrimInt + Debug, const E: usize>(num: N, encoding: [u8; E]) {
+    fn assert_trip_exact<N: PrimInt + LEB128Codec + Debug, const E: usize>(
+        num: N,
+        encoding: [u8; E],
+    ) {
         let mut buf = [0; 32];
         let mut writable = &mut buf[..];
         num.leb128_encode(&mut writable).unwrap();
@@ -250,10 +792,168 @@ mod tests {
         assert_trip_exact(0x29442u64, [0xC2, 0xA8, 0xA]);
     }
     #[test]
+    fn portable_sizes() {
+        assert_trip_exact(0x29442usize, [0xC2, 0xA8, 0xA]);
+        assert_trip_exact(-0x8652isize, [0xAE, 0xF3, 0x7D]);
+        assert_trip(usize::MAX);
+        assert_trip(isize::MIN);
+        let mut readable = &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x02][..];
+        assert_eq!(
+            usize::leb128_decode(&mut readable).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+    #[test]
     fn test_signed_exact() {
         assert_trip_exact(-128i8, [0x80, 0x7F]);
         assert_trip_exact(0x7Fi16, [0xFF, 0x00]);
         assert_trip_exact(-0x53i32, [0xAD, 0x7F]);
         assert_trip_exact(-0x8652i32, [0xAE, 0xF3, 0x7D]);
     }
+
+    #[test]
+    fn padded_encoding() {
+        let mut buf = Vec::new();
+        assert_eq!(1u32.leb128_encode_padded(&mut buf, 5).unwrap(), 5);
+        assert_eq!(buf, [0x81, 0x80, 0x80, 0x80, 0x00]);
+        assert_eq!(u32::leb128_decode(&mut &buf[..]).unwrap(), 1);
+        buf.clear();
+        (-2i32).leb128_encode_padded(&mut buf, 3).unwrap();
+        assert_eq!(buf, [0xFE, 0xFF, 0x7F]);
+        assert_eq!(i32::leb128_decode(&mut &buf[..]).unwrap(), -2);
+        buf.clear();
+        624485usize.leb128_encode_padded(&mut buf, 3).unwrap();
+        assert_eq!(buf, [0xE5, 0x8E, 0x26]);
+        for (num, width) in [(624485u32, 2), (1, 6)] {
+            let err = num
+                .leb128_encode_padded(&mut Vec::new(), width)
+                .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+    #[test]
+    fn padded_decoding() {
+        let padded = [0x81, 0x80, 0x80, 0x80, 0x00];
+        assert_eq!(
+            u32::leb128_decode_padded(&mut &padded[..], Padding::Lenient).unwrap(),
+            1
+        );
+        let err = u32::leb128_decode_padded(&mut &padded[..], Padding::Strict).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::TooManyBytes));
+        assert_eq!(Location::of(&err).unwrap().offset, 4);
+        let err =
+            i32::leb128_decode_padded(&mut &[0xFE, 0xFF, 0x7F][..], Padding::Strict).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::TooManyBytes));
+        for value in [0i64, 63, 64, -64, -65, i64::MIN, i64::MAX] {
+            let mut buf = Vec::new();
+            value.leb128_encode(&mut buf).unwrap();
+            let strict = i64::leb128_decode_padded(&mut &buf[..], Padding::Strict);
+            assert_eq!(strict.unwrap(), value);
+            buf.clear();
+            value.leb128_encode_padded(&mut buf, 10).unwrap();
+            let lenient = i64::leb128_decode_padded(&mut &buf[..], Padding::default());
+            assert_eq!(lenient.unwrap(), value);
+        }
+        let mut buf = Vec::new();
+        300usize.leb128_encode_padded(&mut buf, 4).unwrap();
+        assert!(usize::leb128_decode_padded(&mut &buf[..], Padding::Strict).is_err());
+        assert_eq!(
+            usize::leb128_decode_padded(&mut &buf[..], Padding::Lenient).unwrap(),
+            300
+        );
+        // Lenience does not extend past the longest encoding.
+        let err = u32::leb128_decode_padded(
+            &mut &[0x81, 0x80, 0x80, 0x80, 0x80, 0x00][..],
+            Padding::Lenient,
+        )
+        .unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+    }
+
+    #[test]
+    fn overlong_input() {
+        let mut readable = &[0x81, 0x80, 0x80, 0x80, 0x80, 0x00][..];
+        let err = u32::leb128_decode(&mut readable).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+        // 128 and -129 do not fit an i8 even though the top group is small.
+        for input in [[0x80, 0x01], [0xFF, 0x7E]] {
+            let err = i8::leb128_decode(&mut &input[..]).unwrap_err();
+            assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+        }
+    }
+
+    #[test]
+    fn buffered_decode() {
+        let mut bytes = Vec::new();
+        for x in 0..500i64 {
+            (x * -7919).leb128_encode(&mut bytes).unwrap();
+        }
+        let mut reader = io::BufReader::with_capacity(5, &bytes[..]);
+        for x in 0..500i64 {
+            assert_eq!(i64::leb128_decode_buffered(&mut reader).unwrap(), x * -7919);
+        }
+        let err = i64::leb128_decode_buffered(&mut reader).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        let mut overflow = &[0x80, 0x02][..];
+        assert!(u8::leb128_decode_buffered(&mut overflow).is_err());
+        assert!(overflow.is_empty());
+    }
+
+    #[test]
+    fn try_decode_at_boundaries() {
+        let mut readable = &[0xE5, 0x8E, 0x26, 0xFF][..];
+        assert_eq!(u32::leb128_try_decode(&mut readable).unwrap(), Some(624485));
+        let err = u32::leb128_try_decode(&mut readable).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(u32::leb128_try_decode(&mut readable).unwrap(), None);
+    }
+
+    #[test]
+    fn encodes_to_new_vec() {
+        assert_eq!(624485u32.to_leb128_vec(), [0xE5, 0x8E, 0x26]);
+        assert_eq!((-1i128).to_leb128_vec(), [0x7F]);
+        assert_eq!(usize::MAX.to_leb128_vec(), u64::MAX.to_leb128_vec());
+    }
+
+    #[test]
+    fn appends_to_vec() {
+        let mut out = vec![0xAA];
+        let mut expected = out.clone();
+        for x in (-70000i32..70000).step_by(13).chain([i32::MIN, i32::MAX]) {
+            let len = x.leb128_encode_to_vec(&mut out);
+            assert_eq!(len, x.leb128_encode(&mut expected).unwrap());
+            (x as u128).leb128_encode_to_vec(&mut out);
+            (x as u128).leb128_encode(&mut expected).unwrap();
+            (x as isize).leb128_encode_to_vec(&mut out);
+            (x as isize).leb128_encode(&mut expected).unwrap();
+        }
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn parses_whole_buffers() {
+        assert_eq!(u32::from_leb128_bytes(&[0xE5, 0x8E, 0x26]).unwrap(), 624485);
+        assert_eq!(i8::from_leb128_bytes(&[0x7F]).unwrap(), -1);
+        let err = u32::from_leb128_bytes(&[0xE5, 0x8E, 0x26, 0x00]).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::TrailingBytes));
+        assert_eq!(Location::of(&err).unwrap().offset, 3);
+        let err = u32::from_leb128_bytes(&[]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn encodes_through_references() {
+        let values = [624485u32, 0, u32::MAX];
+        let mut by_value = Vec::new();
+        let mut by_ref = Vec::new();
+        for (num, mut borrowed) in values.into_iter().zip(values) {
+            num.leb128_encode(&mut by_value).unwrap();
+            (&borrowed).leb128_encode(&mut by_ref).unwrap();
+            (&mut borrowed).leb128_encode(&mut by_ref).unwrap();
+            num.leb128_encode(&mut by_value).unwrap();
+        }
+        assert_eq!(by_ref, by_value);
+        let lengths: Vec<usize> = values.iter().map(|num| num.leb128_bytes().len()).collect();
+        assert_eq!(lengths, [3, 1, 5]);
+    }
 }
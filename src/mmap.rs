@@ -0,0 +1,189 @@
+//! Decoding whole files of packed values through a memory map, for columns
+//! too large to read into memory first.
+//!
+//! ```
+//! use lebase::{mmap::MmapIter, LEB128Encode};
+//!
+//! let path = std::env::temp_dir().join(format!("lebase-doc-{}", std::process::id()));
+//! let mut bytes = Vec::new();
+//! for value in [1u64, 300, u64::MAX] {
+//!     value.leb128_encode(&mut bytes).unwrap();
+//! }
+//! std::fs::write(&path, &bytes).unwrap();
+//!
+//! let file = std::fs::File::open(&path).unwrap();
+//! // SAFETY: nothing else touches the file while it is mapped.
+//! let map = unsafe { memmap2::Mmap::map(&file) }.unwrap();
+//! let values: Vec<u64> = MmapIter::new(map).collect::<Result<_, _>>().unwrap();
+//! assert_eq!(values, [1, 300, u64::MAX]);
+//! # std::fs::remove_file(&path).unwrap();
+//! ```
+
+use std::{fs::File, io, marker::PhantomData, path::Path};
+
+use memmap2::Mmap;
+
+use crate::{error::relocate, LEB128Codec};
+
+/// Bytes the kernel is asked to have read ahead of the decoder.
+const PREFETCH_WINDOW: usize = 4 << 20;
+
+/// Decodes values from a memory map until it ends at a value boundary,
+/// asking the kernel to keep a window ahead of it read in. A value cut off by
+/// the end of the map yields an `UnexpectedEof` error, and errors carry
+/// their offset in the file.
+pub struct MmapIter<N> {
+    map: Mmap,
+    pos: usize,
+    prefetched_end: usize,
+    done: bool,
+    _num: PhantomData<fn() -> N>,
+}
+
+impl<N: LEB128Codec> MmapIter<N> {
+    pub fn new(map: Mmap) -> Self {
+        #[cfg(unix)]
+        if !map.is_empty() {
+            // Only a hint; decoding works the same if it is refused.
+            let _ = map.advise(memmap2::Advice::Sequential);
+        }
+        MmapIter {
+            map,
+            pos: 0,
+            prefetched_end: 0,
+            done: false,
+            _num: PhantomData,
+        }
+    }
+
+    /// Offset of the next value in the file.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Keeps at least a window past the cursor advised. Each call that runs
+    /// short advises up to two windows ahead, so the kernel is asked about a
+    /// window at a time rather than for every value.
+    fn prefetch(&mut self) {
+        let len = self.map.len();
+        if self.prefetched_end >= self.pos.saturating_add(PREFETCH_WINDOW).min(len) {
+            return;
+        }
+        let start = self.prefetched_end.max(self.pos);
+        let end = self.pos.saturating_add(2 * PREFETCH_WINDOW).min(len);
+        #[cfg(unix)]
+        let _ = self
+            .map
+            .advise_range(memmap2::Advice::WillNeed, start, end - start);
+        self.prefetched_end = end;
+    }
+}
+
+impl<N: LEB128Codec> Iterator for MmapIter<N> {
+    type Item = Result<N, io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.pos == self.map.len() {
+            return None;
+        }
+        self.prefetch();
+        let mut rest = &self.map[self.pos..];
+        let result = N::leb128_decode(&mut rest);
+        match result {
+            Ok(num) => {
+                self.pos = self.map.len() - rest.len();
+                Some(Ok(num))
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(relocate(e, self.pos as u64)))
+            }
+        }
+    }
+}
+
+/// Maps the file at `path` and decodes every value in it.
+///
+/// # Safety
+///
+/// The file must not be truncated or modified, by this process or another,
+/// while the iterator exists; see [`Mmap::map`].
+pub unsafe fn decode_all_mmap<N, P>(path: P) -> Result<MmapIter<N>, io::Error>
+where
+    N: LEB128Codec,
+    P: AsRef<Path>,
+{
+    let file = File::open(path)?;
+    // SAFETY: the caller upholds the requirements of `Mmap::map`.
+    let map = unsafe { Mmap::map(&file)? };
+    Ok(MmapIter::new(map))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io, path::PathBuf};
+
+    use super::{decode_all_mmap, MmapIter, PREFETCH_WINDOW};
+    use crate::{Error, LEB128Encode, Location};
+
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str, bytes: &[u8]) -> Self {
+            let path = std::env::temp_dir().join(format!("lebase-{name}-{}", std::process::id()));
+            fs::write(&path, bytes).unwrap();
+            TempFile(path)
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn prefetches_ahead_of_the_cursor() {
+        let values: Vec<u32> = (0..PREFETCH_WINDOW as u32 / 2)
+            .map(|i| i.wrapping_mul(7919))
+            .collect();
+        let mut bytes = Vec::new();
+        for &value in &values {
+            value.leb128_encode(&mut bytes).unwrap();
+        }
+        assert!(bytes.len() > 2 * PREFETCH_WINDOW);
+        let file = TempFile::new("chunks", &bytes);
+        // SAFETY: the file is private to this test.
+        let mut iter = unsafe { decode_all_mmap::<u32, _>(&file.0) }.unwrap();
+        for &value in &values {
+            let pos = iter.position();
+            assert_eq!(iter.next().unwrap().unwrap(), value);
+            // The advised range always covers a window past the value read.
+            assert!(iter.prefetched_end >= (pos + PREFETCH_WINDOW).min(bytes.len()));
+        }
+        assert_eq!(iter.prefetched_end, bytes.len());
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn errors_carry_file_offsets() {
+        let file = TempFile::new("errors", &[0x01, 0x80, 0x80, 0x80, 0x80, 0x10]);
+        // SAFETY: the file is private to this test.
+        let mut iter = unsafe { decode_all_mmap::<u32, _>(&file.0) }.unwrap();
+        assert_eq!(iter.next().unwrap().unwrap(), 1);
+        let err = iter.next().unwrap().unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+        assert_eq!(Location::of(&err).unwrap().offset, 5);
+        assert!(iter.next().is_none());
+
+        let file = TempFile::new("eof", &[0x80]);
+        // SAFETY: as above.
+        let map = unsafe { memmap2::Mmap::map(&fs::File::open(&file.0).unwrap()) }.unwrap();
+        let err = MmapIter::<u8>::new(map).next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        let empty = TempFile::new("empty", &[]);
+        // SAFETY: as above.
+        let mut iter = unsafe { decode_all_mmap::<u8, _>(&empty.0) }.unwrap();
+        assert!(iter.next().is_none());
+    }
+}
@@ -0,0 +1,162 @@
+//! An order-preserving varint for key components, following SQLite 4: the
+//! first byte determines the length, and encodings compare bytewise in the
+//! same order as the values they encode.
+//!
+//! | first byte `A0` | value                                   |
+//! |-----------------|-----------------------------------------|
+//! | 0 to 240        | `A0`                                    |
+//! | 241 to 248      | `240 + 256 * (A0 - 241) + A1`           |
+//! | 249             | `2288 + 256 * A1 + A2`                  |
+//! | 250 to 255      | `A1..` as `A0 - 247` big-endian bytes   |
+
+use std::io;
+
+use crate::{format::VarintFormat, varint::Varint};
+
+pub struct OrderedVarint;
+
+fn non_canonical() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "non-canonical ordered varint")
+}
+
+impl Varint for OrderedVarint {
+    const MAX_LEN: usize = 9;
+
+    fn encoded_len(value: u64) -> usize {
+        match value {
+            0..=240 => 1,
+            241..=2287 => 2,
+            2288..=67823 => 3,
+            _ => 1 + (64 - value.leading_zeros() as usize).div_ceil(8).max(3),
+        }
+    }
+
+    fn encode<W>(value: u64, writer: &mut W) -> Result<usize, io::Error>
+    where
+        W: Sized + io::Write,
+    {
+        let mut bytes = [0u8; 9];
+        let len = Self::encoded_len(value);
+        match len {
+            1 => bytes[0] = value as u8,
+            2 => {
+                let rest = value - 240;
+                bytes[0] = (rest / 256 + 241) as u8;
+                bytes[1] = (rest % 256) as u8;
+            }
+            3 => {
+                let rest = value - 2288;
+                bytes[0] = 249;
+                bytes[1] = (rest / 256) as u8;
+                bytes[2] = (rest % 256) as u8;
+            }
+            _ => {
+                bytes[0] = (247 + len - 1) as u8;
+                bytes[1..len].copy_from_slice(&value.to_be_bytes()[9 - len..]);
+            }
+        }
+        writer.write_all(&bytes[..len])?;
+        Ok(len)
+    }
+
+    /// Rejects encodings longer than necessary with `InvalidData`, since they
+    /// would sort out of order.
+    fn decode<R>(reader: &mut R) -> Result<u64, io::Error>
+    where
+        R: Sized + io::Read,
+    {
+        let mut first = [0u8; 1];
+        reader.read_exact(&mut first)?;
+        let a0 = first[0] as u64;
+        let value = match first[0] {
+            0..=240 => return Ok(a0),
+            241..=248 => {
+                reader.read_exact(&mut first)?;
+                // 240 itself fits in the first byte.
+                if a0 == 241 && first[0] == 0 {
+                    return Err(non_canonical());
+                }
+                return Ok(240 + 256 * (a0 - 241) + first[0] as u64);
+            }
+            249 => {
+                let mut rest = [0u8; 2];
+                reader.read_exact(&mut rest)?;
+                return Ok(2288 + u16::from_be_bytes(rest) as u64);
+            }
+            _ => {
+                let len = first[0] as usize - 247;
+                let mut bytes = [0u8; 8];
+                reader.read_exact(&mut bytes[8 - len..])?;
+                u64::from_be_bytes(bytes)
+            }
+        };
+        if Self::encoded_len(value) != first[0] as usize - 246 {
+            return Err(non_canonical());
+        }
+        Ok(value)
+    }
+}
+
+impl VarintFormat for OrderedVarint {
+    fn name(&self) -> &str {
+        "ordered-varint"
+    }
+
+    fn encode(&self, value: i128, out: &mut Vec<u8>) -> Result<usize, io::Error> {
+        let value = u64::try_from(value).map_err(|_| io::ErrorKind::InvalidInput)?;
+        <Self as Varint>::encode(value, out)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<(i128, usize), io::Error> {
+        let mut readable = bytes;
+        let num = <Self as Varint>::decode(&mut readable)?;
+        Ok((num.into(), bytes.len() - readable.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::OrderedVarint;
+    use crate::varint::Varint;
+
+    fn encode(value: u64) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        OrderedVarint::encode(value, &mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn boundaries() {
+        for (value, bytes) in [
+            (240, &[240][..]),
+            (241, &[241, 1]),
+            (2287, &[248, 255]),
+            (2288, &[249, 0, 0]),
+            (67823, &[249, 255, 255]),
+            (67824, &[250, 1, 8, 240]),
+            (u32::MAX as u64, &[251, 255, 255, 255, 255]),
+            (u64::MAX, &[255, 255, 255, 255, 255, 255, 255, 255, 255]),
+        ] {
+            assert_eq!(encode(value), bytes);
+            assert_eq!(OrderedVarint::decode(&mut &bytes[..]).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn preserves_order() {
+        let mut values: Vec<u64> = (0..64).flat_map(|s| [(1 << s) - 1, 1 << s]).collect();
+        values.extend([239, 240, 241, 2287, 2288, 67823, 67824, u64::MAX]);
+        values.sort();
+        values.dedup();
+        for pair in values.windows(2) {
+            assert!(encode(pair[0]) < encode(pair[1]), "{pair:?}");
+        }
+        let err = OrderedVarint::decode(&mut &[250, 0, 0, 5][..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = OrderedVarint::decode(&mut &[241, 0][..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(OrderedVarint::decode(&mut &[241, 1][..]).unwrap(), 241);
+    }
+}
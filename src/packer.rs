@@ -0,0 +1,95 @@
+//! Greedy packing of values into MTU-sized packets.
+
+use crate::{vec, LEB128Encode};
+
+pub struct PacketPacker {
+    mtu: usize,
+    packet: Vec<u8>,
+    scratch: Vec<u8>,
+}
+
+impl PacketPacker {
+    pub fn new(mtu: usize) -> Self {
+        PacketPacker {
+            mtu,
+            packet: Vec::with_capacity(mtu),
+            scratch: Vec::new(),
+        }
+    }
+
+    pub fn mtu(&self) -> usize {
+        self.mtu
+    }
+
+    fn try_push(&mut self) -> bool {
+        if self.packet.len() + self.scratch.len() > self.mtu {
+            return false;
+        }
+        self.packet.extend_from_slice(&self.scratch);
+        true
+    }
+
+    /// Encodes as many leading `values` as fit in one packet, returning the
+    /// packet and the values that did not fit. A value whose encoding alone
+    /// exceeds the MTU yields an empty packet.
+    pub fn pack<'v, N>(&mut self, values: &'v [N]) -> (&[u8], &'v [N])
+    where
+        N: LEB128Encode + Copy,
+    {
+        self.packet.clear();
+        let mut packed = 0;
+        for &num in values {
+            self.scratch.clear();
+            num.leb128_encode_to_vec(&mut self.scratch);
+            if !self.try_push() {
+                break;
+            }
+            packed += 1;
+        }
+        (&self.packet, &values[packed..])
+    }
+
+    /// Like [`pack`](Self::pack), for length-prefixed frames.
+    pub fn pack_frames<'f, 'a>(&mut self, frames: &'f [&'a [u8]]) -> (&[u8], &'f [&'a [u8]]) {
+        self.packet.clear();
+        let mut packed = 0;
+        for frame in frames {
+            self.scratch.clear();
+            vec::encode_bytes(frame, &mut self.scratch);
+            if !self.try_push() {
+                break;
+            }
+            packed += 1;
+        }
+        (&self.packet, &frames[packed..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PacketPacker;
+
+    #[test]
+    fn packs_until_mtu() {
+        let mut packer = PacketPacker::new(4);
+        let values = [1u32, 300, 2, 624485, 5];
+        let (packet, rest) = packer.pack(&values);
+        assert_eq!(packet, [0x01, 0xAC, 0x02, 0x02]);
+        assert_eq!(rest, [624485, 5]);
+        let (packet, rest) = packer.pack(rest);
+        assert_eq!(packet, [0xE5, 0x8E, 0x26, 0x05]);
+        assert!(rest.is_empty());
+        let (packet, rest) = packer.pack(&[u64::MAX]);
+        assert!(packet.is_empty());
+        assert_eq!(rest.len(), 1);
+    }
+
+    #[test]
+    fn packs_frames() {
+        let mut packer = PacketPacker::new(6);
+        let frames: [&[u8]; 3] = [b"ab", b"c", b"de"];
+        let (packet, rest) = packer.pack_frames(&frames);
+        assert_eq!(packet, [2, b'a', b'b', 1, b'c']);
+        assert_eq!(rest, [b"de"]);
+    }
+}
@@ -0,0 +1,115 @@
+//! Prefix varints: like UTF-8, the number of leading one bits in the first
+//! byte gives the number of bytes that follow, so the length is known after
+//! a single byte. The remaining bits of the first byte hold the most
+//! significant part of the value and the following bytes the rest, big
+//! endian. A first byte of `0xFF` is followed by all eight bytes of the
+//! value.
+//!
+//! | first byte  | total bytes | value bits |
+//! |-------------|-------------|------------|
+//! | `0xxxxxxx`  | 1           | 7          |
+//! | `10xxxxxx`  | 2           | 14         |
+//! | `110xxxxx`  | 3           | 21         |
+//! | ...         | ...         | ...        |
+//! | `11111110`  | 8           | 56         |
+//! | `11111111`  | 9           | 64         |
+
+use std::io;
+
+use crate::{format::VarintFormat, varint::Varint};
+
+pub struct PrefixVarint;
+
+impl Varint for PrefixVarint {
+    const MAX_LEN: usize = 9;
+
+    fn encoded_len(value: u64) -> usize {
+        let bits = 64 - value.leading_zeros() as usize;
+        if bits > 56 {
+            9
+        } else {
+            bits.div_ceil(7).max(1)
+        }
+    }
+
+    fn encode<W>(value: u64, writer: &mut W) -> Result<usize, io::Error>
+    where
+        W: Sized + io::Write,
+    {
+        let len = Self::encoded_len(value);
+        let mut bytes = [0u8; 9];
+        if len == 9 {
+            bytes[0] = 0xFF;
+            bytes[1..].copy_from_slice(&value.to_be_bytes());
+        } else {
+            let extra = len - 1;
+            bytes[..len].copy_from_slice(&value.to_be_bytes()[8 - len..]);
+            bytes[0] |= !(0xFFu8 >> extra);
+        }
+        writer.write_all(&bytes[..len])?;
+        Ok(len)
+    }
+
+    fn decode<R>(reader: &mut R) -> Result<u64, io::Error>
+    where
+        R: Sized + io::Read,
+    {
+        let mut first = [0u8; 1];
+        reader.read_exact(&mut first)?;
+        let extra = first[0].leading_ones() as usize;
+        let mut bytes = [0u8; 8];
+        reader.read_exact(&mut bytes[8 - extra..])?;
+        if extra < 8 {
+            bytes[7 - extra] = first[0] & (0x7F >> extra);
+        }
+        Ok(u64::from_be_bytes(bytes))
+    }
+}
+
+impl VarintFormat for PrefixVarint {
+    fn name(&self) -> &str {
+        "prefix-varint"
+    }
+
+    fn encode(&self, value: i128, out: &mut Vec<u8>) -> Result<usize, io::Error> {
+        let value = u64::try_from(value).map_err(|_| io::ErrorKind::InvalidInput)?;
+        <Self as Varint>::encode(value, out)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<(i128, usize), io::Error> {
+        let mut readable = bytes;
+        let num = <Self as Varint>::decode(&mut readable)?;
+        Ok((num.into(), bytes.len() - readable.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::PrefixVarint;
+    use crate::varint::Varint;
+
+    #[test]
+    fn layout() {
+        for (value, bytes) in [
+            (0, &[0x00][..]),
+            (0x7F, &[0x7F]),
+            (0x80, &[0x80, 0x80]),
+            (0x3FFF, &[0xBF, 0xFF]),
+            (0x4000, &[0xC0, 0x40, 0x00]),
+            (
+                (1 << 56) - 1,
+                &[0xFE, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF],
+            ),
+            (1 << 56, &[0xFF, 0x01, 0, 0, 0, 0, 0, 0, 0]),
+        ] {
+            let mut out = Vec::new();
+            assert_eq!(PrefixVarint::encode(value, &mut out).unwrap(), bytes.len());
+            assert_eq!(out, bytes);
+            assert_eq!(PrefixVarint::decode(&mut &bytes[..]).unwrap(), value);
+        }
+        let err = PrefixVarint::decode(&mut &[0xC0, 0x01][..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
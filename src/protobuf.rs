@@ -0,0 +1,209 @@
+//! Protocol Buffers wire-format building blocks: zigzag-coded signed
+//! varints, field keys, and skipping unknown fields.
+
+use std::io::{self, Read};
+
+use crate::{scan::leb128_skip, LEB128Codec, LEB128Encode};
+
+/// Largest field number the wire format allows.
+pub const MAX_FIELD: u32 = (1 << 29) - 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WireType {
+    Varint = 0,
+    I64 = 1,
+    Len = 2,
+    StartGroup = 3,
+    EndGroup = 4,
+    I32 = 5,
+}
+
+impl WireType {
+    fn from_bits(bits: u32) -> Option<WireType> {
+        Some(match bits {
+            0 => WireType::Varint,
+            1 => WireType::I64,
+            2 => WireType::Len,
+            3 => WireType::StartGroup,
+            4 => WireType::EndGroup,
+            5 => WireType::I32,
+            _ => return None,
+        })
+    }
+}
+
+pub const fn zigzag_encode_64(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+pub const fn zigzag_decode_64(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+pub const fn zigzag_encode_32(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+pub const fn zigzag_decode_32(value: u32) -> i32 {
+    (value >> 1) as i32 ^ -((value & 1) as i32)
+}
+
+/// Writes a `sint64` field value.
+pub fn encode_sint64<W>(value: i64, writer: &mut W) -> Result<usize, io::Error>
+where
+    W: Sized + io::Write,
+{
+    zigzag_encode_64(value).leb128_encode(writer)
+}
+
+pub fn decode_sint64<R>(reader: &mut R) -> Result<i64, io::Error>
+where
+    R: Sized + io::Read,
+{
+    Ok(zigzag_decode_64(u64::leb128_decode(reader)?))
+}
+
+/// Writes a `sint32` field value.
+pub fn encode_sint32<W>(value: i32, writer: &mut W) -> Result<usize, io::Error>
+where
+    W: Sized + io::Write,
+{
+    zigzag_encode_32(value).leb128_encode(writer)
+}
+
+pub fn decode_sint32<R>(reader: &mut R) -> Result<i32, io::Error>
+where
+    R: Sized + io::Read,
+{
+    Ok(zigzag_decode_32(u32::leb128_decode(reader)?))
+}
+
+/// Writes the key introducing a field. Field numbers outside
+/// `1..=MAX_FIELD` fail with `InvalidInput`.
+pub fn encode_key<W>(field: u32, wire_type: WireType, writer: &mut W) -> Result<usize, io::Error>
+where
+    W: Sized + io::Write,
+{
+    if field == 0 || field > MAX_FIELD {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput));
+    }
+    (field << 3 | wire_type as u32).leb128_encode(writer)
+}
+
+/// Reads a field key, failing with `InvalidData` on field number zero or an
+/// unknown wire type.
+pub fn decode_key<R>(reader: &mut R) -> Result<(u32, WireType), io::Error>
+where
+    R: Sized + io::Read,
+{
+    let key = u32::leb128_decode(reader)?;
+    let wire_type = WireType::from_bits(key & 7);
+    match (key >> 3, wire_type) {
+        (field, Some(wire_type)) if field != 0 => Ok((field, wire_type)),
+        _ => Err(io::Error::from(io::ErrorKind::InvalidData)),
+    }
+}
+
+/// Skips the value of a field whose key has just been read, returning the
+/// bytes skipped. Groups are deprecated and fail with `InvalidData`.
+pub fn skip_field<R>(reader: &mut R, wire_type: WireType) -> Result<u64, io::Error>
+where
+    R: Sized + io::Read,
+{
+    let (prefix, len) = match wire_type {
+        WireType::Varint => return Ok(leb128_skip(reader)? as u64),
+        WireType::I64 => (0, 8),
+        WireType::I32 => (0, 4),
+        WireType::Len => {
+            let mut counted = Counted { reader, count: 0 };
+            let len = u64::leb128_decode(&mut counted)?;
+            (counted.count, len)
+        }
+        WireType::StartGroup | WireType::EndGroup => {
+            return Err(io::Error::from(io::ErrorKind::InvalidData))
+        }
+    };
+    let skipped = io::copy(&mut reader.take(len), &mut io::sink())?;
+    if skipped != len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+    }
+    Ok(prefix as u64 + len)
+}
+
+struct Counted<'a, R> {
+    reader: &'a mut R,
+    count: usize,
+}
+
+impl<R: io::Read> io::Read for Counted<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.reader.read(buf)?;
+        self.count += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+
+    #[test]
+    fn zigzag_matches_spec() {
+        for (value, zigzag) in [(0, 0), (-1, 1), (1, 2), (-2, 3), (i64::MAX, u64::MAX - 1)] {
+            assert_eq!(zigzag_encode_64(value), zigzag);
+            assert_eq!(zigzag_decode_64(zigzag), value);
+        }
+        assert_eq!(zigzag_encode_64(i64::MIN), u64::MAX);
+        assert_eq!(zigzag_encode_32(i32::MIN), u32::MAX);
+        assert_eq!(zigzag_decode_32(u32::MAX - 1), i32::MAX);
+
+        let mut bytes = Vec::new();
+        encode_sint32(-150, &mut bytes).unwrap();
+        encode_sint64(i64::MIN, &mut bytes).unwrap();
+        let mut readable = &bytes[..];
+        assert_eq!(decode_sint32(&mut readable).unwrap(), -150);
+        assert_eq!(decode_sint64(&mut readable).unwrap(), i64::MIN);
+    }
+
+    #[test]
+    fn reads_message_fields() {
+        // Field 1 = 150, field 2 = "testing", field 5 = fixed32, field 3 = -2.
+        let mut bytes = vec![0x08, 0x96, 0x01];
+        bytes.extend([0x12, 0x07]);
+        bytes.extend(b"testing");
+        encode_key(5, WireType::I32, &mut bytes).unwrap();
+        bytes.extend([1, 2, 3, 4]);
+        encode_key(3, WireType::Varint, &mut bytes).unwrap();
+        encode_sint64(-2, &mut bytes).unwrap();
+
+        let mut readable = &bytes[..];
+        assert_eq!(decode_key(&mut readable).unwrap(), (1, WireType::Varint));
+        assert_eq!(u32::leb128_decode(&mut readable).unwrap(), 150);
+        assert_eq!(decode_key(&mut readable).unwrap(), (2, WireType::Len));
+        assert_eq!(skip_field(&mut readable, WireType::Len).unwrap(), 8);
+        let (_, wire_type) = decode_key(&mut readable).unwrap();
+        assert_eq!(skip_field(&mut readable, wire_type).unwrap(), 4);
+        assert_eq!(decode_key(&mut readable).unwrap(), (3, WireType::Varint));
+        assert_eq!(decode_sint64(&mut readable).unwrap(), -2);
+        assert!(readable.is_empty());
+    }
+
+    #[test]
+    fn rejects_bad_keys() {
+        let err = encode_key(0, WireType::Len, &mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(encode_key(MAX_FIELD + 1, WireType::Len, &mut Vec::new()).is_err());
+        for key in [&[0x02][..], &[0x0E]] {
+            let err = decode_key(&mut &key[..]).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+        assert_eq!(
+            skip_field(&mut &[0x81, 0x00, 1][..], WireType::Len).unwrap(),
+            3
+        );
+        let err = skip_field(&mut &[0x05, 1][..], WireType::Len).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
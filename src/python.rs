@@ -0,0 +1,146 @@
+//! PyO3 bindings, built into a `lebase` extension module with maturin.
+//!
+//! ```python
+//! import lebase
+//! data = lebase.encode(624485) + lebase.encode(-2, signed=True)
+//! assert lebase.decode(data) == (624485, 3)
+//! assert list(lebase.iterate(data[3:], signed=True)) == [-2]
+//! ```
+
+use std::io;
+
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyBytes, IntoPyObjectExt};
+
+use crate::{LEB128Codec, LEB128Encode};
+
+fn value_error(err: io::Error) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+/// Decodes one value from `data`, returning it and its length.
+fn decode_one<'py>(
+    py: Python<'py>,
+    data: &[u8],
+    signed: bool,
+) -> PyResult<(Bound<'py, PyAny>, usize)> {
+    let mut rest = data;
+    let value = if signed {
+        i128::leb128_decode(&mut rest)
+            .map_err(value_error)?
+            .into_bound_py_any(py)?
+    } else {
+        u128::leb128_decode(&mut rest)
+            .map_err(value_error)?
+            .into_bound_py_any(py)?
+    };
+    Ok((value, data.len() - rest.len()))
+}
+
+/// Encodes an int as ULEB128, or SLEB128 if `signed`.
+#[pyfunction]
+#[pyo3(signature = (value, signed = false))]
+fn encode<'py>(
+    py: Python<'py>,
+    value: &Bound<'py, PyAny>,
+    signed: bool,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let mut out = Vec::new();
+    if signed {
+        value.extract::<i128>()?.leb128_encode(&mut out)?;
+    } else {
+        value.extract::<u128>()?.leb128_encode(&mut out)?;
+    }
+    Ok(PyBytes::new(py, &out))
+}
+
+/// Decodes the value at `offset`, returning `(value, length)`.
+#[pyfunction]
+#[pyo3(signature = (data, offset = 0, signed = false))]
+fn decode<'py>(
+    py: Python<'py>,
+    data: &[u8],
+    offset: usize,
+    signed: bool,
+) -> PyResult<(Bound<'py, PyAny>, usize)> {
+    let rest = data
+        .get(offset..)
+        .ok_or_else(|| value_error(io::Error::from(io::ErrorKind::UnexpectedEof)))?;
+    decode_one(py, rest, signed)
+}
+
+/// Iterator over every value in a byte string.
+#[pyclass(module = "lebase")]
+struct Values {
+    data: Vec<u8>,
+    position: usize,
+    signed: bool,
+}
+
+#[pymethods]
+impl Values {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__<'py>(&mut self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyAny>>> {
+        if self.position == self.data.len() {
+            return Ok(None);
+        }
+        let (value, len) = decode_one(py, &self.data[self.position..], self.signed)?;
+        self.position += len;
+        Ok(Some(value))
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (data, signed = false))]
+fn iterate(data: &[u8], signed: bool) -> Values {
+    Values {
+        data: data.to_vec(),
+        position: 0,
+        signed,
+    }
+}
+
+#[pymodule]
+fn lebase(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(encode, module)?)?;
+    module.add_function(wrap_pyfunction!(decode, module)?)?;
+    module.add_function(wrap_pyfunction!(iterate, module)?)?;
+    module.add_class::<Values>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pyo3::{
+        ffi::c_str,
+        prelude::*,
+        types::{IntoPyDict, PyModule},
+    };
+
+    #[test]
+    fn module_round_trips() {
+        Python::initialize();
+        Python::attach(|py| {
+            let module = PyModule::new(py, "lebase").unwrap();
+            super::lebase(&module).unwrap();
+            let globals = [("lebase", module)].into_py_dict(py).unwrap();
+            py.run(
+                c_str!(
+                    "data = lebase.encode(624485) + lebase.encode(-2, signed=True)\n\
+                     assert data == b'\\xe5\\x8e\\x26\\x7e'\n\
+                     assert lebase.decode(data) == (624485, 3)\n\
+                     assert lebase.decode(data, 3, signed=True) == (-2, 1)\n\
+                     assert list(lebase.iterate(data[:3])) == [624485]\n\
+                     assert lebase.encode(2**128 - 1)[-1] == 0x03\n\
+                     try:\n    lebase.decode(b'\\x80')\nexcept ValueError:\n    pass\n\
+                     else:\n    raise AssertionError"
+                ),
+                Some(&globals),
+                None,
+            )
+            .unwrap();
+        });
+    }
+}
@@ -0,0 +1,215 @@
+//! Buffered readers with typed decode methods.
+
+use std::io::{self, BufRead};
+
+use crate::{bytes, error::relocate, Error, LEB128Codec};
+
+const DEFAULT_CAPACITY: usize = 512;
+
+/// Wraps a reader in a small buffer and tracks how many bytes have been
+/// consumed. An optional budget caps how far into the stream it may read;
+/// reads past it behave as if the input ended there, or fail with
+/// [`Error::QuotaExceeded`] when it was set as a quota.
+pub struct Leb128Reader<R> {
+    inner: io::BufReader<R>,
+    position: u64,
+    budget: Option<u64>,
+    quota: bool,
+}
+
+macro_rules! typed_reads {
+    ($($name:ident => $ty:ty),*) => {
+        $(
+            pub fn $name(&mut self) -> Result<$ty, io::Error> {
+                self.read_value()
+            }
+        )*
+    };
+}
+
+impl<R: io::Read> Leb128Reader<R> {
+    pub fn new(inner: R) -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY, inner)
+    }
+
+    pub fn with_capacity(capacity: usize, inner: R) -> Self {
+        Leb128Reader {
+            inner: io::BufReader::with_capacity(capacity, inner),
+            position: 0,
+            budget: None,
+            quota: false,
+        }
+    }
+
+    /// Allows at most `limit` more bytes to be consumed.
+    pub fn budget(mut self, limit: u64) -> Self {
+        self.budget = Some(limit);
+        self.quota = false;
+        self
+    }
+
+    /// Like [`budget`](Self::budget), for parsing untrusted nested
+    /// structures: running into the limit while input remains fails with
+    /// [`Error::QuotaExceeded`] instead of looking like the end of input.
+    pub fn quota(mut self, limit: u64) -> Self {
+        self.budget = Some(limit);
+        self.quota = true;
+        self
+    }
+
+    /// Bytes consumed so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Bytes left in the budget, or `None` if there is no budget.
+    pub fn remaining(&self) -> Option<u64> {
+        self.budget
+    }
+
+    /// Error locations are relative to the start of the stream.
+    pub fn read_value<N: LEB128Codec>(&mut self) -> Result<N, io::Error> {
+        let start = self.position;
+        N::leb128_decode_buffered(self).map_err(|e| relocate(e, start))
+    }
+
+    /// Like [`read_value`](Self::read_value), but returns `None` at a clean
+    /// end of input.
+    pub fn try_read_value<N: LEB128Codec>(&mut self) -> Result<Option<N>, io::Error> {
+        if self.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+        self.read_value().map(Some)
+    }
+
+    typed_reads!(
+        read_u8 => u8, read_u16 => u16, read_u32 => u32, read_u64 => u64, read_u128 => u128,
+        read_i8 => i8, read_i16 => i16, read_i32 => i32, read_i64 => i64, read_i128 => i128,
+        read_usize => usize, read_isize => isize
+    );
+
+    /// Reads a length-prefixed byte string of at most `max_len` bytes.
+    pub fn read_bytes(&mut self, max_len: usize) -> Result<Vec<u8>, io::Error> {
+        bytes::decode_bytes(self, max_len)
+    }
+
+    /// Reads a length-prefixed UTF-8 string of at most `max_len` bytes.
+    pub fn read_string(&mut self, max_len: usize) -> Result<String, io::Error> {
+        bytes::decode_string(self, max_len)
+    }
+
+    pub fn get_ref(&self) -> &R {
+        self.inner.get_ref()
+    }
+
+    /// Returns the wrapped reader. Buffered but unconsumed bytes are lost.
+    pub fn into_inner(self) -> R {
+        self.inner.into_inner()
+    }
+}
+
+impl<R: io::Read> io::Read for Leb128Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<R: io::Read> io::BufRead for Leb128Reader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let available = self.inner.fill_buf()?;
+        Ok(match self.budget {
+            Some(0) if self.quota && !available.is_empty() => {
+                return Err(Error::QuotaExceeded.into());
+            }
+            Some(budget) if budget < available.len() as u64 => &available[..budget as usize],
+            _ => available,
+        })
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.inner.consume(amount);
+        self.position += amount as u64;
+        if let Some(budget) = &mut self.budget {
+            *budget -= amount as u64;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::Leb128Reader;
+    use crate::{bytes, Error, LEB128Encode, Location};
+
+    #[test]
+    fn typed_reads_track_position() {
+        let mut data = Vec::new();
+        300u32.leb128_encode(&mut data).unwrap();
+        (-2i64).leb128_encode(&mut data).unwrap();
+        bytes::encode_str("hello", &mut data).unwrap();
+        u128::MAX.leb128_encode(&mut data).unwrap();
+
+        let mut reader = Leb128Reader::with_capacity(4, &data[..]);
+        assert_eq!(reader.read_u32().unwrap(), 300);
+        assert_eq!(reader.position(), 2);
+        assert_eq!(reader.read_i64().unwrap(), -2);
+        assert_eq!(reader.read_string(16).unwrap(), "hello");
+        assert_eq!(reader.read_u128().unwrap(), u128::MAX);
+        assert_eq!(reader.position(), data.len() as u64);
+        assert_eq!(reader.remaining(), None);
+    }
+
+    #[test]
+    fn budget_stops_reads() {
+        let data = [0xE5, 0x8E, 0x26, 0x01, 0x02];
+        let mut reader = Leb128Reader::new(&data[..]).budget(4);
+        assert_eq!(reader.read_u32().unwrap(), 624485);
+        assert_eq!(reader.remaining(), Some(1));
+        assert_eq!(reader.read_u8().unwrap(), 1);
+        let err = reader.read_u8().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(reader.position(), 4);
+        assert_eq!(reader.try_read_value::<u8>().unwrap(), None);
+    }
+
+    #[test]
+    fn errors_carry_stream_offsets() {
+        let data = [0x05, 0x80, 0x80, 0x04];
+        let mut reader = Leb128Reader::new(&data[..]);
+        assert_eq!(reader.read_u16().unwrap(), 5);
+        let err = reader.read_u16().unwrap_err();
+        assert_eq!(
+            Location::of(&err),
+            Some(Location {
+                offset: 3,
+                shift: 14
+            })
+        );
+    }
+
+    #[test]
+    fn quota_spans_values() {
+        let mut data = Vec::new();
+        624485u32.leb128_encode(&mut data).unwrap();
+        bytes::encode_bytes(&[7; 10], &mut data).unwrap();
+
+        let mut reader = Leb128Reader::new(&data[..]).quota(14);
+        assert_eq!(reader.read_u32().unwrap(), 624485);
+        assert_eq!(reader.read_bytes(64).unwrap(), [7; 10]);
+        assert_eq!(reader.remaining(), Some(0));
+        assert_eq!(reader.try_read_value::<u8>().unwrap(), None);
+
+        let mut reader = Leb128Reader::new(&data[..]).quota(8);
+        assert_eq!(reader.read_u32().unwrap(), 624485);
+        let err = reader.read_bytes(64).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::QuotaExceeded));
+        assert_eq!(reader.position(), 8);
+        let err = reader.try_read_value::<u8>().unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::QuotaExceeded));
+    }
+}
@@ -0,0 +1,189 @@
+//! Chained writes and reads of the fields of one record, for layouts too
+//! irregular to derive and too long to spell out as separate calls.
+//!
+//! ```
+//! use lebase::record::{RecordReader, RecordWriter};
+//!
+//! let record = RecordWriter::new(Vec::new())
+//!     .u32(300)?
+//!     .sleb(-2)?
+//!     .bytes(b"payload")?
+//!     .finish();
+//! assert_eq!(record.len(), 11);
+//!
+//! let mut reader = RecordReader::new(&record[..]);
+//! assert_eq!(reader.u32()?, 300);
+//! assert_eq!(reader.sleb()?, -2);
+//! assert_eq!(reader.bytes(64)?, b"payload");
+//! assert_eq!(reader.position(), 11);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::io;
+
+use crate::{bytes, reader::Leb128Reader, LEB128Codec, LEB128Encode};
+
+/// Writes the fields of a record in order, consuming and returning itself
+/// so that calls chain with `?`.
+pub struct RecordWriter<W> {
+    writer: W,
+    len: usize,
+}
+
+macro_rules! typed_fields {
+    ($($name:ident => $ty:ty),*) => {
+        $(
+            pub fn $name(self, value: $ty) -> Result<Self, io::Error> {
+                self.value(value)
+            }
+        )*
+    };
+}
+
+impl<W: io::Write> RecordWriter<W> {
+    pub fn new(writer: W) -> Self {
+        RecordWriter { writer, len: 0 }
+    }
+
+    /// Bytes written so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn value<N: LEB128Encode>(mut self, value: N) -> Result<Self, io::Error> {
+        self.len += value.leb128_encode(&mut self.writer)?;
+        Ok(self)
+    }
+
+    typed_fields!(
+        u8 => u8, u16 => u16, u32 => u32, u64 => u64, u128 => u128, usize => usize,
+        i8 => i8, i16 => i16, i32 => i32, i64 => i64, i128 => i128, isize => isize,
+        uleb => u64, sleb => i64
+    );
+
+    /// Writes a length-prefixed byte string.
+    pub fn bytes(mut self, bytes: &[u8]) -> Result<Self, io::Error> {
+        self.len += bytes::encode_bytes(bytes, &mut self.writer)?;
+        Ok(self)
+    }
+
+    /// Writes a length-prefixed UTF-8 string.
+    pub fn str(mut self, s: &str) -> Result<Self, io::Error> {
+        self.len += bytes::encode_str(s, &mut self.writer)?;
+        Ok(self)
+    }
+
+    /// Returns the wrapped writer.
+    pub fn finish(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads the fields of a record in order through a [`Leb128Reader`]. Error
+/// locations are relative to the start of the record.
+pub struct RecordReader<R> {
+    reader: Leb128Reader<R>,
+}
+
+macro_rules! typed_reads {
+    ($($name:ident => $ty:ty),*) => {
+        $(
+            pub fn $name(&mut self) -> Result<$ty, io::Error> {
+                self.value()
+            }
+        )*
+    };
+}
+
+impl<R: io::Read> RecordReader<R> {
+    pub fn new(reader: R) -> Self {
+        // A one-byte buffer never reads past the fields, so `finish` can
+        // hand back a reader positioned right after them.
+        RecordReader {
+            reader: Leb128Reader::with_capacity(1, reader),
+        }
+    }
+
+    /// Bytes read so far.
+    pub fn position(&self) -> u64 {
+        self.reader.position()
+    }
+
+    pub fn value<N: LEB128Codec>(&mut self) -> Result<N, io::Error> {
+        self.reader.read_value()
+    }
+
+    typed_reads!(
+        u8 => u8, u16 => u16, u32 => u32, u64 => u64, u128 => u128, usize => usize,
+        i8 => i8, i16 => i16, i32 => i32, i64 => i64, i128 => i128, isize => isize,
+        uleb => u64, sleb => i64
+    );
+
+    /// Reads a length-prefixed byte string of at most `max_len` bytes.
+    pub fn bytes(&mut self, max_len: usize) -> Result<Vec<u8>, io::Error> {
+        self.reader.read_bytes(max_len)
+    }
+
+    /// Reads a length-prefixed UTF-8 string of at most `max_len` bytes.
+    pub fn string(&mut self, max_len: usize) -> Result<String, io::Error> {
+        self.reader.read_string(max_len)
+    }
+
+    /// Returns the wrapped reader, positioned after the fields read.
+    pub fn finish(self) -> R {
+        self.reader.into_inner()
+    }
+}
+
+impl<R: io::Read> io::Read for RecordReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{RecordReader, RecordWriter};
+    use crate::{Error, Location};
+
+    #[test]
+    fn fields_round_trip() {
+        let writer = RecordWriter::new(Vec::new())
+            .u8(1)
+            .and_then(|w| w.str("name"))
+            .and_then(|w| w.i128(i128::MIN))
+            .and_then(|w| w.value(624485u32))
+            .unwrap();
+        assert_eq!(writer.len(), 1 + 5 + 19 + 3);
+        let mut record = writer.finish();
+        record.push(0xAA);
+
+        let mut reader = RecordReader::new(&record[..]);
+        assert_eq!(reader.u8().unwrap(), 1);
+        assert_eq!(reader.string(4).unwrap(), "name");
+        assert_eq!(reader.i128().unwrap(), i128::MIN);
+        assert_eq!(reader.uleb().unwrap(), 624485);
+        assert_eq!(reader.position(), 28);
+        assert_eq!(reader.finish(), [0xAA]);
+    }
+
+    #[test]
+    fn errors_are_located_in_the_record() {
+        let record = [0x05, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01];
+        let mut reader = RecordReader::new(&record[..]);
+        assert_eq!(reader.u8().unwrap(), 5);
+        let err = reader.u32().unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+        assert_eq!(Location::of(&err).unwrap().offset, 5);
+
+        let err = RecordReader::new(&[0x02, b'a'][..]).bytes(8).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert!(RecordWriter::new(Vec::new()).is_empty());
+    }
+}
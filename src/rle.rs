@@ -0,0 +1,78 @@
+//! Run-length coding: a ULEB128 run count followed by that many
+//! `(length, value)` pairs, each run collapsing consecutive equal values.
+
+use std::io;
+
+use crate::{bytes::decode_len, Error, LEB128Codec, LEB128Encode};
+
+/// Encodes `values` as runs, returning the bytes written.
+pub fn encode_rle<N, W>(values: &[N], writer: &mut W) -> Result<usize, io::Error>
+where
+    N: LEB128Encode + PartialEq + Copy,
+    W: io::Write,
+{
+    let runs = values.chunk_by(|a, b| a == b);
+    let mut written = runs.clone().count().leb128_encode(writer)?;
+    for run in runs {
+        written += run.len().leb128_encode(writer)?;
+        written += run[0].leb128_encode(writer)?;
+    }
+    Ok(written)
+}
+
+/// Decodes run-length coded values, failing with [`Error::TooLong`] once they
+/// would expand past `max_len` values and with [`Error::Zero`] on an empty
+/// run.
+pub fn decode_rle<N, R>(reader: &mut R, max_len: usize) -> Result<Vec<N>, io::Error>
+where
+    N: LEB128Codec + Copy,
+    R: io::Read,
+{
+    let runs = decode_len(reader, max_len)?;
+    let mut values = Vec::new();
+    for _ in 0..runs {
+        let len = decode_len(reader, max_len - values.len())?;
+        if len == 0 {
+            return Err(Error::Zero.into());
+        }
+        let num = N::leb128_decode(reader)?;
+        values.resize(values.len() + len, num);
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_rle, encode_rle};
+    use crate::Error;
+
+    #[test]
+    fn collapses_runs() {
+        let mut values = vec![0u32; 1000];
+        values[10] = 7;
+        values[11] = 7;
+        values.push(300);
+        let mut bytes = Vec::new();
+        let written = encode_rle(&values, &mut bytes).unwrap();
+        assert_eq!(written, bytes.len());
+        assert_eq!(bytes, [4, 10, 0, 2, 7, 0xDC, 0x07, 0, 1, 0xAC, 0x02]);
+        assert_eq!(decode_rle::<u32, _>(&mut &bytes[..], 1001).unwrap(), values);
+
+        let mut empty = Vec::new();
+        encode_rle::<u8, _>(&[], &mut empty).unwrap();
+        assert_eq!(decode_rle::<u8, _>(&mut &empty[..], 0).unwrap(), [0u8; 0]);
+    }
+
+    #[test]
+    fn bounds_expansion() {
+        let bytes = [1, 0xE8, 0x07, 0];
+        let err = decode_rle::<u8, _>(&mut &bytes[..], 999).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::TooLong));
+        assert_eq!(
+            decode_rle::<u8, _>(&mut &bytes[..], 1000).unwrap().len(),
+            1000
+        );
+        let err = decode_rle::<u8, _>(&mut &[1, 0, 5][..], 10).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Zero));
+    }
+}
@@ -0,0 +1,262 @@
+//! A decoder that is fed bytes as they arrive instead of reading them itself.
+
+use std::{collections::VecDeque, io, marker::PhantomData};
+
+use num_traits::PrimInt;
+
+use crate::{decode_step, max_encoded_len, Error, LEB128Codec, CONTINUATION};
+
+/// Work done by one or more [`StreamDecoder::push_bytes`] calls.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PushReport {
+    pub bytes_consumed: usize,
+    pub values_produced: usize,
+}
+
+pub struct StreamDecoder<N> {
+    partial: Vec<u8>,
+    decoded: VecDeque<N>,
+    totals: PushReport,
+    _num: PhantomData<fn() -> N>,
+}
+
+impl<N: LEB128Codec + PrimInt> Default for StreamDecoder<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N: LEB128Codec + PrimInt> StreamDecoder<N> {
+    pub fn new() -> Self {
+        StreamDecoder {
+            partial: Vec::new(),
+            decoded: VecDeque::new(),
+            totals: PushReport::default(),
+            _num: PhantomData,
+        }
+    }
+
+    /// Consumes all of `bytes`, decoding every value they complete.
+    pub fn push_bytes(&mut self, bytes: &[u8]) -> Result<PushReport, io::Error> {
+        self.push_bytes_limited(bytes, usize::MAX)
+    }
+
+    /// Consumes `bytes` until `max_values` values have been produced. The
+    /// report says how many bytes were used; the rest should be pushed again
+    /// later.
+    ///
+    /// A value still unfinished after the longest encoding of `N` fails with
+    /// [`Error::Overflow`] and is discarded, so a stream of continuation
+    /// bytes cannot grow the decoder without bound. The totals count the
+    /// bytes and values up to and including a failed value either way.
+    pub fn push_bytes_limited(
+        &mut self,
+        bytes: &[u8],
+        max_values: usize,
+    ) -> Result<PushReport, io::Error> {
+        let mut report = PushReport::default();
+        let mut result = Ok(());
+        for &byte in bytes {
+            if report.values_produced == max_values {
+                break;
+            }
+            report.bytes_consumed += 1;
+            self.partial.push(byte);
+            if byte & CONTINUATION == 0 {
+                let num = N::leb128_decode(&mut &self.partial[..]);
+                self.partial.clear();
+                match num {
+                    Ok(num) => self.decoded.push_back(num),
+                    Err(e) => {
+                        result = Err(e);
+                        break;
+                    }
+                }
+                report.values_produced += 1;
+            } else if self.partial.len() == max_encoded_len::<N>() {
+                let last = self.partial.len() - 1;
+                self.partial.clear();
+                result = Err(Error::Overflow.at(last, last * 7));
+                break;
+            }
+        }
+        self.totals.bytes_consumed += report.bytes_consumed;
+        self.totals.values_produced += report.values_produced;
+        result.map(|()| report)
+    }
+
+    /// Takes the oldest decoded value not yet returned.
+    pub fn next_value(&mut self) -> Option<N> {
+        self.decoded.pop_front()
+    }
+
+    /// Running totals over every push since the decoder was created.
+    pub fn totals(&self) -> PushReport {
+        self.totals
+    }
+
+    /// Whether the bytes pushed so far end inside a value.
+    pub fn is_mid_value(&self) -> bool {
+        !self.partial.is_empty()
+    }
+}
+
+/// Outcome of [`decode_partial`] or [`PartialState::resume`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Partial<N> {
+    /// The value is complete, using this many bytes of the latest input.
+    Done(N, usize),
+    /// The input ended mid-value. Pass the next bytes to `state.resume`.
+    NeedMoreData {
+        /// The fewest further bytes that could complete the value. Always 1
+        /// for LEB128, as any byte without the continuation bit ends it.
+        needed: usize,
+        state: PartialState<N>,
+    },
+}
+
+/// A value whose encoding has been seen only up to the end of an input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PartialState<N> {
+    num: N,
+    shift: usize,
+}
+
+impl<N: PrimInt> Default for PartialState<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N: PrimInt> PartialState<N> {
+    pub fn new() -> Self {
+        PartialState {
+            num: N::zero(),
+            shift: 0,
+        }
+    }
+
+    /// Bytes of the value consumed so far.
+    pub fn bytes_seen(&self) -> usize {
+        self.shift / 7
+    }
+
+    /// The most further bytes the value may take before it overflows `N`.
+    pub fn max_remaining(&self) -> usize {
+        max_encoded_len::<N>().saturating_sub(self.bytes_seen())
+    }
+
+    /// Continues decoding with the next input. Error locations count from
+    /// the start of the value, not of `bytes`.
+    pub fn resume(mut self, bytes: &[u8]) -> Result<Partial<N>, io::Error> {
+        for (i, &byte) in bytes.iter().enumerate() {
+            match decode_step(&mut self.num, &mut self.shift, byte) {
+                Ok(true) => return Ok(Partial::Done(self.num, i + 1)),
+                Ok(false) => {}
+                Err(e) => return Err(e.at(self.shift / 7, self.shift)),
+            }
+        }
+        Ok(Partial::NeedMoreData {
+            needed: 1,
+            state: self,
+        })
+    }
+}
+
+/// Decodes the value at the start of `bytes`, returning a resumable state
+/// instead of failing if `bytes` ends before the value does.
+pub fn decode_partial<N: PrimInt>(bytes: &[u8]) -> Result<Partial<N>, io::Error> {
+    PartialState::new().resume(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_partial, Partial, PartialState, PushReport, StreamDecoder};
+    use crate::{Error, Location};
+
+    #[test]
+    fn reports_work_per_push() {
+        let mut decoder = StreamDecoder::<u32>::new();
+        let report = decoder.push_bytes(&[0x01, 0xE5, 0x8E]).unwrap();
+        assert_eq!(
+            report,
+            PushReport {
+                bytes_consumed: 3,
+                values_produced: 1
+            }
+        );
+        assert!(decoder.is_mid_value());
+        let report = decoder.push_bytes(&[0x26, 0x02, 0x03]).unwrap();
+        assert_eq!(report.values_produced, 3);
+        assert_eq!(
+            decoder.totals(),
+            PushReport {
+                bytes_consumed: 6,
+                values_produced: 4
+            }
+        );
+        let values: Vec<u32> = std::iter::from_fn(|| decoder.next_value()).collect();
+        assert_eq!(values, [1, 624485, 2, 3]);
+    }
+
+    #[test]
+    fn respects_value_budget() {
+        let mut decoder = StreamDecoder::<u8>::new();
+        let input = [1, 2, 3, 4, 5];
+        let report = decoder.push_bytes_limited(&input, 2).unwrap();
+        assert_eq!(report.bytes_consumed, 2);
+        let report = decoder.push_bytes_limited(&input[2..], 2).unwrap();
+        assert_eq!(report.bytes_consumed, 2);
+        assert_eq!(decoder.totals().values_produced, 4);
+        assert!(decoder.push_bytes(&[0xFF, 0x7F]).is_err());
+
+        let mut decoder = StreamDecoder::<u8>::new();
+        assert!(decoder.push_bytes(&[1, 2, 0xFF, 0x7F, 3]).is_err());
+        assert_eq!(
+            decoder.totals(),
+            PushReport {
+                bytes_consumed: 4,
+                values_produced: 2
+            }
+        );
+        let values: Vec<u8> = std::iter::from_fn(|| decoder.next_value()).collect();
+        assert_eq!(values, [1, 2]);
+    }
+
+    #[test]
+    fn bounds_unfinished_values() {
+        let mut decoder = StreamDecoder::<u64>::new();
+        let err = decoder.push_bytes(&vec![0x80; 1 << 20]).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+        assert_eq!(Location::of(&err).unwrap().offset, 9);
+        assert!(!decoder.is_mid_value());
+        decoder.push_bytes(&[0x05]).unwrap();
+        assert_eq!(decoder.next_value(), Some(5));
+    }
+
+    #[test]
+    fn resumes_split_values() {
+        let Partial::NeedMoreData { needed, state } = decode_partial::<u32>(&[0xE5]).unwrap()
+        else {
+            panic!("value should be incomplete");
+        };
+        assert_eq!(needed, 1);
+        assert_eq!((state.bytes_seen(), state.max_remaining()), (1, 4));
+        let Partial::NeedMoreData { state, .. } = state.resume(&[]).unwrap() else {
+            panic!("value should be incomplete");
+        };
+        assert_eq!(
+            state.resume(&[0x8E, 0x26, 0x01]).unwrap(),
+            Partial::Done(624485, 2)
+        );
+        assert_eq!(decode_partial::<i8>(&[0x7F]).unwrap(), Partial::Done(-1, 1));
+
+        let state = PartialState::<u16>::new();
+        let Partial::NeedMoreData { state, .. } = state.resume(&[0xFF, 0xFF]).unwrap() else {
+            panic!("value should be incomplete");
+        };
+        let err = state.resume(&[0x04]).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+        assert_eq!(Location::of(&err).unwrap().offset, 2);
+    }
+}
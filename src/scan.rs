@@ -0,0 +1,204 @@
+//! Moving through encoded data without decoding it, and looking ahead without
+//! consuming it.
+
+use std::io;
+
+use crate::{LEB128Codec, CONTINUATION};
+
+/// Consumes one value from `reader`, returning the number of bytes skipped.
+pub fn leb128_skip<R>(reader: &mut R) -> Result<usize, io::Error>
+where
+    R: Sized + io::Read,
+{
+    let mut buffer: [u8; 1] = [0];
+    let mut skipped = 0;
+    loop {
+        reader.read_exact(&mut buffer)?;
+        skipped += 1;
+        if buffer[0] & CONTINUATION == 0 {
+            break Ok(skipped);
+        }
+    }
+}
+
+/// Returns the length of the value at the start of `bytes`.
+#[cfg_attr(feature = "no_panic", no_panic::no_panic)]
+pub fn leb128_skip_slice(bytes: &[u8]) -> Result<usize, io::Error> {
+    bytes
+        .iter()
+        .position(|byte| byte & CONTINUATION == 0)
+        .map(|i| i + 1)
+        .ok_or_else(|| io::ErrorKind::UnexpectedEof.into())
+}
+
+/// Returns the length of the first `n` values in `bytes`.
+pub fn skip_values_slice(bytes: &[u8], n: usize) -> Result<usize, io::Error> {
+    if n == 0 {
+        return Ok(0);
+    }
+    let mut remaining = n;
+    for (i, byte) in bytes.iter().enumerate() {
+        if byte & CONTINUATION == 0 {
+            remaining -= 1;
+            if remaining == 0 {
+                return Ok(i + 1);
+            }
+        }
+    }
+    Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+}
+
+/// Consumes `n` consecutive values from `reader` a buffer at a time,
+/// returning the number of bytes skipped.
+pub fn skip_values<R: io::BufRead>(reader: &mut R, n: usize) -> Result<usize, io::Error> {
+    let mut remaining = n;
+    let mut skipped = 0;
+    while remaining > 0 {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+        }
+        let mut used = buf.len();
+        for (i, byte) in buf.iter().enumerate() {
+            if byte & CONTINUATION == 0 {
+                remaining -= 1;
+                if remaining == 0 {
+                    used = i + 1;
+                    break;
+                }
+            }
+        }
+        reader.consume(used);
+        skipped += used;
+    }
+    Ok(skipped)
+}
+
+/// Skips past the next byte without the continuation bit, so that a reader
+/// left inside a damaged value is positioned where the following value would
+/// start. Returns the number of bytes skipped; if the stream ends first,
+/// everything is consumed and the next read sees end of input.
+pub fn resync<R: io::BufRead>(reader: &mut R) -> Result<usize, io::Error> {
+    let mut skipped = 0;
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(skipped);
+        }
+        match buf.iter().position(|byte| byte & CONTINUATION == 0) {
+            Some(end) => {
+                reader.consume(end + 1);
+                return Ok(skipped + end + 1);
+            }
+            None => {
+                let used = buf.len();
+                reader.consume(used);
+                skipped += used;
+            }
+        }
+    }
+}
+
+/// Decodes the value at the start of `bytes`, returning it and its length.
+pub fn leb128_peek_slice<N: LEB128Codec>(bytes: &[u8]) -> Result<(N, usize), io::Error> {
+    let mut readable = bytes;
+    let num = N::leb128_decode(&mut readable)?;
+    Ok((num, bytes.len() - readable.len()))
+}
+
+/// Decodes the next value from `reader`'s buffer without consuming it.
+///
+/// Only the bytes already buffered are examined: if the value's encoding
+/// extends past the end of the buffer this fails with `WouldBlock`, since the
+/// rest may still be on its way; consume the buffer or use a larger one to
+/// get at it. `UnexpectedEof` means the stream itself has ended.
+pub fn leb128_peek<N, R>(reader: &mut R) -> Result<N, io::Error>
+where
+    N: LEB128Codec,
+    R: io::BufRead,
+{
+    let buf = reader.fill_buf()?;
+    match leb128_peek_slice(buf) {
+        Ok((num, _)) => Ok(num),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof && !buf.is_empty() => {
+            Err(io::ErrorKind::WouldBlock.into())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, BufRead, BufReader};
+
+    use super::{
+        leb128_peek, leb128_peek_slice, leb128_skip, leb128_skip_slice, resync, skip_values,
+        skip_values_slice,
+    };
+    use crate::{LEB128Codec, LEB128Encode};
+
+    #[test]
+    fn skips_single_values() {
+        let bytes = [0xE5, 0x8E, 0x26, 0x01, 0x80];
+        let mut readable = &bytes[..];
+        assert_eq!(leb128_skip(&mut readable).unwrap(), 3);
+        assert_eq!(leb128_skip(&mut readable).unwrap(), 1);
+        let err = leb128_skip(&mut readable).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(leb128_skip_slice(&bytes).unwrap(), 3);
+        assert_eq!(leb128_skip_slice(&bytes[3..]).unwrap(), 1);
+        assert!(leb128_skip_slice(&bytes[4..]).is_err());
+    }
+
+    #[test]
+    fn peeks_without_consuming() {
+        let bytes = [0xE5, 0x8E, 0x26, 0x01];
+        assert_eq!(leb128_peek_slice::<u32>(&bytes).unwrap(), (624485, 3));
+        let mut reader = BufReader::new(&bytes[..]);
+        assert_eq!(leb128_peek::<u32, _>(&mut reader).unwrap(), 624485);
+        assert_eq!(leb128_peek::<u32, _>(&mut reader).unwrap(), 624485);
+        assert_eq!(u32::leb128_decode(&mut reader).unwrap(), 624485);
+        assert_eq!(leb128_peek::<u8, _>(&mut reader).unwrap(), 1);
+        reader.consume(1);
+        let err = leb128_peek::<u8, _>(&mut reader).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        let mut reader = BufReader::with_capacity(2, &bytes[..]);
+        let err = leb128_peek::<u32, _>(&mut reader).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+        assert_eq!(u32::leb128_decode(&mut reader).unwrap(), 624485);
+    }
+
+    #[test]
+    fn skips_many_values() {
+        let mut bytes = Vec::new();
+        for x in 0..1000u32 {
+            (x * 331).leb128_encode(&mut bytes).unwrap();
+        }
+        let expected = skip_values_slice(&bytes, 600).unwrap();
+        let mut reader = BufReader::with_capacity(7, &bytes[..]);
+        assert_eq!(skip_values(&mut reader, 600).unwrap(), expected);
+        assert_eq!(u32::leb128_decode(&mut reader).unwrap(), 600 * 331);
+        assert_eq!(skip_values(&mut reader, 0).unwrap(), 0);
+        let err = skip_values(&mut reader, 400).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert!(skip_values_slice(&bytes, 1001).is_err());
+    }
+
+    #[test]
+    fn resyncs_after_damage() {
+        // A u8 that overflows, then 624485 and 1, with the terminator of the
+        // damaged value beyond the buffer's first fill.
+        let bytes = [0x80, 0x80, 0x80, 0x80, 0x02, 0xE5, 0x8E, 0x26, 0x01];
+        let mut reader = BufReader::with_capacity(2, &bytes[..]);
+        assert!(u8::leb128_decode(&mut reader).is_err());
+        assert_eq!(resync(&mut reader).unwrap(), 2);
+        assert_eq!(u32::leb128_decode(&mut reader).unwrap(), 624485);
+        assert_eq!(resync(&mut reader).unwrap(), 1);
+        assert_eq!(resync(&mut reader).unwrap(), 0);
+
+        let mut reader = BufReader::new(&[0x80, 0x80][..]);
+        assert_eq!(resync(&mut reader).unwrap(), 2);
+        assert!(reader.fill_buf().unwrap().is_empty());
+    }
+}
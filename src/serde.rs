@@ -0,0 +1,107 @@
+//! `serialize` and `deserialize` for serde's `with` attribute, so single
+//! integer fields of otherwise serde-encoded structs go out as LEB128 bytes.
+//!
+//! Fields are serialized with `serialize_bytes`; formats without a byte type,
+//! like JSON, write an array of numbers, which deserializes too. The bytes
+//! must hold exactly one value.
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Sample {
+//!     name: String,
+//!     #[serde(with = "lebase::serde")]
+//!     count: u64,
+//! }
+//!
+//! let sample = Sample { name: "hits".into(), count: 300 };
+//! let json = serde_json::to_string(&sample).unwrap();
+//! assert_eq!(json, r#"{"name":"hits","count":[172,2]}"#);
+//! assert_eq!(serde_json::from_str::<Sample>(&json).unwrap(), sample);
+//! ```
+
+use std::{fmt, marker::PhantomData};
+
+use ::serde::{
+    de::{self, SeqAccess, Visitor},
+    Deserializer, Serializer,
+};
+
+use crate::{LEB128Codec, LEB128Encode};
+
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: LEB128Encode + Copy,
+    S: Serializer,
+{
+    serializer.serialize_bytes(&value.to_leb128_vec())
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: LEB128Codec,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_bytes(EncodedVisitor(PhantomData))
+}
+
+struct EncodedVisitor<T>(PhantomData<fn() -> T>);
+
+impl<'de, T: LEB128Codec> Visitor<'de> for EncodedVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the LEB128 encoding of an integer")
+    }
+
+    fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<T, E> {
+        T::from_leb128_bytes(bytes).map_err(E::custom)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<T, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(19));
+        while let Some(byte) = seq.next_element::<u8>()? {
+            bytes.push(byte);
+        }
+        self.visit_bytes(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Record {
+        #[serde(with = "super")]
+        id: i32,
+        #[serde(with = "super")]
+        len: usize,
+        plain: u8,
+    }
+
+    #[test]
+    fn round_trips_fields() {
+        let record = Record {
+            id: -129,
+            len: 624485,
+            plain: 7,
+        };
+        let json = serde_json::to_string(&record).unwrap();
+        assert_eq!(json, r#"{"id":[255,126],"len":[229,142,38],"plain":7}"#);
+        assert_eq!(serde_json::from_str::<Record>(&json).unwrap(), record);
+    }
+
+    #[test]
+    fn rejects_malformed_fields() {
+        for json in [
+            r#"{"id":[128],"len":[0],"plain":0}"#,
+            r#"{"id":[0,0],"len":[0],"plain":0}"#,
+            r#"{"id":[0],"len":[256],"plain":0}"#,
+            r#"{"id":[0],"len":"zero","plain":0}"#,
+        ] {
+            assert!(serde_json::from_str::<Record>(json).is_err(), "{json}");
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! Reading a varint stream that a single writer is appending to concurrently,
+//! such as a telemetry channel in a shared memory segment.
+
+use std::{
+    io,
+    sync::atomic::{AtomicU8, AtomicUsize, Ordering},
+};
+
+use crate::LEB128Codec;
+
+/// Reads values from `buf` up to the length the writer has published in
+/// `committed`.
+///
+/// The writer must only modify bytes at or past the published length and must
+/// store the new length with `Release` ordering after the bytes are written.
+/// Bytes below the published length are never read before it covers them.
+/// The buffer is borrowed as atomics because the writer mutates it while the
+/// reader holds it; a mapped segment can be viewed that way with
+/// `slice::from_raw_parts(ptr.cast::<AtomicU8>(), len)`.
+pub struct SharedMemReader<'a> {
+    buf: &'a [AtomicU8],
+    committed: &'a AtomicUsize,
+    pos: usize,
+}
+
+impl<'a> SharedMemReader<'a> {
+    pub fn new(buf: &'a [AtomicU8], committed: &'a AtomicUsize) -> Self {
+        SharedMemReader {
+            buf,
+            committed,
+            pos: 0,
+        }
+    }
+
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Decodes the next value, or returns `None` if the writer has not yet
+    /// published all of its bytes.
+    pub fn try_next<N: LEB128Codec>(&mut self) -> Result<Option<N>, io::Error> {
+        let committed = self.committed.load(Ordering::Acquire).min(self.buf.len());
+        if self.pos >= committed {
+            return Ok(None);
+        }
+        let mut readable = Published {
+            bytes: &self.buf[self.pos..committed],
+        };
+        match N::leb128_decode(&mut readable) {
+            Ok(num) => {
+                self.pos = committed - readable.bytes.len();
+                Ok(Some(num))
+            }
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Copies published bytes out one load at a time. The `Acquire` load of the
+/// length orders them, so the loads themselves can be relaxed.
+struct Published<'a> {
+    bytes: &'a [AtomicU8],
+}
+
+impl io::Read for Published<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.bytes.len());
+        let (head, tail) = self.bytes.split_at(n);
+        for (out, byte) in buf.iter_mut().zip(head) {
+            *out = byte.load(Ordering::Relaxed);
+        }
+        self.bytes = tail;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicU8, AtomicUsize, Ordering},
+        thread,
+    };
+
+    use super::SharedMemReader;
+    use crate::LEB128Encode;
+
+    fn atomics(bytes: &[u8]) -> Vec<AtomicU8> {
+        bytes.iter().map(|&b| AtomicU8::new(b)).collect()
+    }
+
+    #[test]
+    fn waits_for_published_bytes() {
+        let mut bytes = Vec::new();
+        300u32.leb128_encode(&mut bytes).unwrap();
+        7u32.leb128_encode(&mut bytes).unwrap();
+        let buf = atomics(&bytes);
+        let committed = AtomicUsize::new(0);
+        let mut reader = SharedMemReader::new(&buf, &committed);
+        assert_eq!(reader.try_next::<u32>().unwrap(), None);
+        committed.store(1, Ordering::Release);
+        assert_eq!(reader.try_next::<u32>().unwrap(), None);
+        committed.store(2, Ordering::Release);
+        assert_eq!(reader.try_next::<u32>().unwrap(), Some(300));
+        assert_eq!(reader.try_next::<u32>().unwrap(), None);
+        committed.store(3, Ordering::Release);
+        assert_eq!(reader.try_next::<u32>().unwrap(), Some(7));
+        assert_eq!(reader.position(), 3);
+    }
+    #[test]
+    fn reads_while_the_writer_appends() {
+        let values: Vec<u64> = (0..20_000u64)
+            .map(|i| i.wrapping_mul(0x9E37_79B9))
+            .collect();
+        let mut bytes = Vec::new();
+        for &value in &values {
+            value.leb128_encode(&mut bytes).unwrap();
+        }
+        let buf = atomics(&vec![0; bytes.len()]);
+        let committed = AtomicUsize::new(0);
+        thread::scope(|s| {
+            s.spawn(|| {
+                // Publishes in uneven steps so that values are split.
+                for chunk in bytes.chunks(7) {
+                    let start = committed.load(Ordering::Relaxed);
+                    for (slot, &byte) in buf[start..].iter().zip(chunk) {
+                        slot.store(byte, Ordering::Relaxed);
+                    }
+                    committed.store(start + chunk.len(), Ordering::Release);
+                }
+            });
+            let mut reader = SharedMemReader::new(&buf, &committed);
+            let mut decoded = Vec::with_capacity(values.len());
+            while decoded.len() < values.len() {
+                match reader.try_next::<u64>().unwrap() {
+                    Some(value) => decoded.push(value),
+                    None => thread::yield_now(),
+                }
+            }
+            assert_eq!(decoded, values);
+            assert_eq!(reader.position(), bytes.len());
+        });
+    }
+}
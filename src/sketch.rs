@@ -0,0 +1,211 @@
+//! Column statistics collected while decoding.
+
+use std::{hash::Hash, io};
+
+use num_traits::PrimInt;
+
+#[derive(Clone, Debug)]
+pub struct Sketch<N> {
+    count: u64,
+    min: Option<N>,
+    max: Option<N>,
+    /// `None` once the running sum has overflowed `N`.
+    sum: Option<N>,
+    #[cfg(feature = "hll")]
+    distinct: HyperLogLog,
+}
+
+impl<N: PrimInt + Hash> Default for Sketch<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N: PrimInt + Hash> Sketch<N> {
+    pub fn new() -> Self {
+        Sketch {
+            count: 0,
+            min: None,
+            max: None,
+            sum: Some(N::zero()),
+            #[cfg(feature = "hll")]
+            distinct: HyperLogLog::new(),
+        }
+    }
+
+    pub fn observe(&mut self, num: N) {
+        self.count += 1;
+        self.min = Some(self.min.map_or(num, |min| min.min(num)));
+        self.max = Some(self.max.map_or(num, |max| max.max(num)));
+        self.sum = self.sum.and_then(|sum| sum.checked_add(&num));
+        #[cfg(feature = "hll")]
+        self.distinct.insert(&num);
+    }
+
+    /// Observes a batch of values, such as the output of a bulk decoder.
+    pub fn observe_all(&mut self, values: &[N]) {
+        for &num in values {
+            self.observe(num);
+        }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn min(&self) -> Option<N> {
+        self.min
+    }
+
+    pub fn max(&self) -> Option<N> {
+        self.max
+    }
+
+    /// The sum of all observed values, or `None` if it does not fit `N`.
+    pub fn sum(&self) -> Option<N> {
+        self.sum
+    }
+
+    /// Approximate number of distinct values observed.
+    #[cfg(feature = "hll")]
+    pub fn distinct(&self) -> f64 {
+        self.distinct.estimate()
+    }
+}
+
+/// An iterator adapter that observes each decoded value in a [`Sketch`].
+pub struct Sketched<'s, I, N> {
+    iter: I,
+    sketch: &'s mut Sketch<N>,
+}
+
+impl<'s, I, N> Sketched<'s, I, N>
+where
+    I: Iterator<Item = Result<N, io::Error>>,
+    N: PrimInt + Hash,
+{
+    pub fn new(iter: I, sketch: &'s mut Sketch<N>) -> Self {
+        Sketched { iter, sketch }
+    }
+}
+
+impl<I, N> Iterator for Sketched<'_, I, N>
+where
+    I: Iterator<Item = Result<N, io::Error>>,
+    N: PrimInt + Hash,
+{
+    type Item = Result<N, io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.iter.next()?;
+        if let Ok(num) = item {
+            self.sketch.observe(num);
+        }
+        Some(item)
+    }
+}
+
+#[cfg(feature = "hll")]
+const HLL_BITS: u32 = 10;
+
+#[cfg(feature = "hll")]
+#[derive(Clone, Debug)]
+struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+#[cfg(feature = "hll")]
+impl HyperLogLog {
+    fn new() -> Self {
+        HyperLogLog {
+            registers: vec![0; 1 << HLL_BITS],
+        }
+    }
+
+    fn insert<T: Hash>(&mut self, item: &T) {
+        use std::hash::{DefaultHasher, Hasher};
+        let mut hasher = DefaultHasher::new();
+        item.hash(&mut hasher);
+        let hash = hasher.finish();
+        let index = (hash >> (64 - HLL_BITS)) as usize;
+        let rank = ((hash << HLL_BITS) | (1 << (HLL_BITS - 1))).leading_zeros() as u8 + 1;
+        self.registers[index] = self.registers[index].max(rank);
+    }
+
+    fn estimate(&self) -> f64 {
+        let m = self.registers.len() as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self.registers.iter().map(|&r| 2f64.powi(-(r as i32))).sum();
+        let raw = alpha * m * m / sum;
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+        if raw <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Sketch;
+    use crate::{bulk, iter::decode_iter, LEB128Encode};
+
+    #[test]
+    fn collects_while_decoding() {
+        let mut buf = Vec::new();
+        for x in [5i16, -300, 7, 7, 12000] {
+            x.leb128_encode(&mut buf).unwrap();
+        }
+        let mut sketch = Sketch::new();
+        let values: Vec<i16> = decode_iter(&buf[..])
+            .sketched(&mut sketch)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(values.len(), 5);
+        assert_eq!(sketch.count(), 5);
+        assert_eq!(sketch.min(), Some(-300));
+        assert_eq!(sketch.max(), Some(12000));
+        assert_eq!(sketch.sum(), Some(11719));
+        sketch.observe(i16::MAX);
+        assert_eq!(sketch.sum(), None);
+    }
+
+    #[test]
+    fn collects_from_bulk_decoders() {
+        let mut buf = Vec::new();
+        for x in (0..1000u32).map(|x| x * 37 % 1000) {
+            x.leb128_encode(&mut buf).unwrap();
+        }
+        let mut sketch = Sketch::new();
+        let mut out = vec![u32::MAX];
+        let count = bulk::decode_slice_into_sketched(&buf, &mut out, &mut sketch).unwrap();
+        assert_eq!(count, 1000);
+        // Values already in `out` are not observed.
+        assert_eq!(
+            (sketch.count(), sketch.min(), sketch.max()),
+            (1000, Some(0), Some(999))
+        );
+        assert_eq!(sketch.sum(), Some(499500));
+
+        let mut sketch = Sketch::new();
+        let mut out = [0u32; 10];
+        bulk::decode_many_into_sketched(&mut &buf[..], &mut out, &mut sketch).unwrap();
+        assert_eq!(sketch.count(), 10);
+        assert_eq!(sketch.sum(), Some(out.iter().sum()));
+        let err = bulk::decode_slice_into_sketched(&[0x80], &mut Vec::new(), &mut sketch);
+        assert!(err.is_err());
+        assert_eq!(sketch.count(), 10);
+    }
+
+    #[cfg(feature = "hll")]
+    #[test]
+    fn approximate_distinct() {
+        let mut sketch = Sketch::new();
+        for x in 0..20000u32 {
+            sketch.observe(x % 5000);
+        }
+        let distinct = sketch.distinct();
+        assert!((4500.0..5500.0).contains(&distinct), "{distinct}");
+    }
+}
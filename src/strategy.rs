@@ -0,0 +1,107 @@
+//! proptest strategies for testing parsers built on this crate.
+//!
+//! ```
+//! use lebase::strategy;
+//! use proptest::prelude::*;
+//!
+//! proptest!(|((value, bytes) in strategy::encoded::<u32>())| {
+//!     assert_eq!(my_parser(&bytes), Some(value));
+//! });
+//! # fn my_parser(mut bytes: &[u8]) -> Option<u32> {
+//! #     lebase::LEB128Codec::leb128_decode(&mut bytes).ok()
+//! # }
+//! ```
+
+use std::fmt;
+
+use num_traits::PrimInt;
+use proptest::{collection::vec, prelude::*, sample::select};
+
+use crate::{is_signed, LEB128Codec, CONTINUATION};
+
+fn max_len<N: PrimInt>() -> usize {
+    (N::zero().count_zeros() as usize).div_ceil(7)
+}
+
+/// Values at every 7-bit group boundary, where encoders and decoders change
+/// length, plus the type's extremes.
+fn boundaries<N: PrimInt>() -> Vec<N> {
+    let bits = N::zero().count_zeros() as usize;
+    let mut values = vec![N::zero(), N::one(), N::min_value(), N::max_value()];
+    for shift in (7..bits).step_by(7) {
+        let edge = if is_signed::<N>() {
+            N::one() << (shift - 1)
+        } else {
+            N::one() << shift
+        };
+        values.extend([edge, edge - N::one()]);
+        if is_signed::<N>() {
+            values.extend([N::zero() - edge, N::zero() - edge - N::one()]);
+        }
+    }
+    values
+}
+
+/// Any value of `N`, biased towards group boundaries, together with its
+/// canonical encoding.
+pub fn encoded<N>() -> impl Strategy<Value = (N, Vec<u8>)>
+where
+    N: PrimInt + LEB128Codec + Arbitrary + fmt::Debug + 'static,
+{
+    prop_oneof![any::<N>(), select(boundaries::<N>())]
+        .prop_map(|value| (value, value.to_leb128_vec()))
+}
+
+/// Byte strings that decoding as `N` must reject: input ending mid-value,
+/// encodings longer than `N` allows, and final bytes carrying bits that do
+/// not fit.
+pub fn malformed<N>() -> impl Strategy<Value = Vec<u8>>
+where
+    N: PrimInt + 'static,
+{
+    let max_len = max_len::<N>();
+    let used_bits = N::zero().count_zeros() as usize - 7 * (max_len - 1);
+    let truncated = vec(CONTINUATION..=u8::MAX, 0..max_len);
+    let overlong = (vec(CONTINUATION..=u8::MAX, max_len), vec(any::<u8>(), 1..4))
+        .prop_map(|(head, tail)| [head, tail].concat());
+    let overflowing =
+        (vec(CONTINUATION..=u8::MAX, max_len - 1), any::<u8>()).prop_map(move |(mut head, low)| {
+            // The lowest bit that must agree with the value's top bit.
+            let first_bad = used_bits - is_signed::<N>() as usize;
+            let last = if is_signed::<N>() {
+                1 << first_bad | low & ((1 << first_bad) - 1)
+            } else {
+                1 << first_bad | low & !CONTINUATION
+            };
+            head.push(last);
+            head
+        });
+    prop_oneof![truncated, overlong, overflowing]
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::{encoded, malformed};
+    use crate::LEB128Codec;
+
+    macro_rules! strategy_tests {
+        ($($name:ident => $ty:ty),*) => {
+            proptest! {
+                $(
+                    #[test]
+                    fn $name((value, bytes) in encoded::<$ty>(), bad in malformed::<$ty>()) {
+                        prop_assert_eq!(<$ty>::leb128_decode(&mut &bytes[..]).unwrap(), value);
+                        prop_assert!(<$ty>::leb128_decode(&mut &bad[..]).is_err(), "{:02x?}", bad);
+                    }
+                )*
+            }
+        };
+    }
+
+    strategy_tests!(
+        u8_cases => u8, u32_cases => u32, u64_cases => u64, u128_cases => u128,
+        i8_cases => i8, i16_cases => i16, i64_cases => i64, i128_cases => i128
+    );
+}
@@ -0,0 +1,186 @@
+//! Stream VByte: `u32`s stored as 1 to 4 little-endian data bytes, with their
+//! lengths packed two bits each into a separate control stream.
+//!
+//! The control and data planes are kept independent so that transports can
+//! frame, interleave or compress them separately. The conventional layout of
+//! control bytes followed by data bytes is available through
+//! [`Streams::to_contiguous`] and [`split_contiguous`].
+
+use std::io;
+
+/// Number of control bytes describing `count` values.
+pub fn control_len(count: usize) -> usize {
+    count.div_ceil(4)
+}
+
+fn byte_len(control: &[u8], index: usize) -> usize {
+    ((control[index / 4] >> ((index % 4) * 2)) & 0b11) as usize + 1
+}
+
+/// Number of data bytes described by the control stream for `count` values.
+pub fn data_len(control: &[u8], count: usize) -> Result<usize, io::Error> {
+    if control.len() < control_len(count) {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+    }
+    Ok((0..count).map(|i| byte_len(control, i)).sum())
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Streams {
+    pub control: Vec<u8>,
+    pub data: Vec<u8>,
+}
+
+impl Streams {
+    pub fn to_contiguous(&self) -> Vec<u8> {
+        [&self.control[..], &self.data[..]].concat()
+    }
+}
+
+/// Appends the encoding of `values` to the two planes.
+pub fn encode_into(values: &[u32], control: &mut Vec<u8>, data: &mut Vec<u8>) {
+    for quad in values.chunks(4) {
+        let mut key = 0;
+        for (i, &num) in quad.iter().enumerate() {
+            let len = (4 - num.leading_zeros() as usize / 8).max(1);
+            key |= ((len - 1) as u8) << (i * 2);
+            data.extend_from_slice(&num.to_le_bytes()[..len]);
+        }
+        control.push(key);
+    }
+}
+
+pub fn encode(values: &[u32]) -> Streams {
+    let mut streams = Streams::default();
+    encode_into(values, &mut streams.control, &mut streams.data);
+    streams
+}
+
+/// Splits the conventional contiguous layout holding `count` values into its
+/// control and data planes.
+pub fn split_contiguous(bytes: &[u8], count: usize) -> Result<(&[u8], &[u8]), io::Error> {
+    let (control, rest) = bytes
+        .split_at_checked(control_len(count))
+        .ok_or(io::ErrorKind::UnexpectedEof)?;
+    let data = rest
+        .get(..data_len(control, count)?)
+        .ok_or(io::ErrorKind::UnexpectedEof)?;
+    Ok((control, data))
+}
+
+/// Reads values from independently held control and data planes.
+pub struct Decoder<'a> {
+    control: &'a [u8],
+    data: &'a [u8],
+    count: usize,
+    index: usize,
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(control: &'a [u8], data: &'a [u8], count: usize) -> Self {
+        Decoder {
+            control,
+            data,
+            count,
+            index: 0,
+        }
+    }
+
+    /// The data bytes not yet consumed.
+    pub fn remaining_data(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+impl Iterator for Decoder<'_> {
+    type Item = Result<u32, io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index == self.count {
+            return None;
+        }
+        if self.control.len() <= self.index / 4 {
+            self.index = self.count;
+            return Some(Err(io::Error::from(io::ErrorKind::UnexpectedEof)));
+        }
+        let len = byte_len(self.control, self.index);
+        let Some((bytes, rest)) = self.data.split_at_checked(len) else {
+            self.index = self.count;
+            return Some(Err(io::Error::from(io::ErrorKind::UnexpectedEof)));
+        };
+        let mut le = [0; 4];
+        le[..len].copy_from_slice(bytes);
+        self.data = rest;
+        self.index += 1;
+        Some(Ok(u32::from_le_bytes(le)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.count - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+/// Decodes `count` values onto the end of `out`, returning the number of data
+/// bytes consumed. On error `out` is left as it was.
+pub fn decode_into(
+    control: &[u8],
+    data: &[u8],
+    count: usize,
+    out: &mut Vec<u32>,
+) -> Result<usize, io::Error> {
+    let mut decoder = Decoder::new(control, data, count);
+    let start = out.len();
+    // Four values per control byte is all the planes can hold, whatever
+    // `count` claims.
+    out.reserve(count.min(control.len().saturating_mul(4)));
+    for num in &mut decoder {
+        match num {
+            Ok(num) => out.push(num),
+            Err(e) => {
+                out.truncate(start);
+                return Err(e);
+            }
+        }
+    }
+    Ok(data.len() - decoder.remaining_data().len())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{data_len, decode_into, encode, split_contiguous, Decoder};
+
+    #[test]
+    fn planes_trip_independently() {
+        let values = [1, 0x100, 0x1_0000, u32::MAX, 7];
+        let streams = encode(&values);
+        assert_eq!(streams.control, [0b11_10_01_00, 0b00]);
+        assert_eq!(streams.data.len(), 1 + 2 + 3 + 4 + 1);
+        assert_eq!(data_len(&streams.control, 5).unwrap(), 11);
+        let decoded: Vec<u32> = Decoder::new(&streams.control, &streams.data, 5)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(decoded, values);
+
+        let contiguous = streams.to_contiguous();
+        let (control, data) = split_contiguous(&contiguous, 5).unwrap();
+        let mut out = Vec::new();
+        assert_eq!(decode_into(control, data, 5, &mut out).unwrap(), 11);
+        assert_eq!(out, values);
+    }
+
+    #[test]
+    fn truncated_planes() {
+        let streams = encode(&[1, 2, 0x1234]);
+        let mut out = vec![9];
+        let err = decode_into(&streams.control, &streams.data[..3], 3, &mut out).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(out, [9]);
+        let err = decode_into(&[0], &[1], usize::MAX, &mut out).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(out, [9]);
+        assert!(split_contiguous(&[0], 5).is_err());
+    }
+}
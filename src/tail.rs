@@ -0,0 +1,205 @@
+//! Reading the most recent values of an append-only varint stream.
+//!
+//! Every value ends with the only byte of its encoding that has the
+//! continuation bit clear, so value boundaries can be found scanning backwards
+//! from the end of a stream without reading what comes before.
+//!
+//! Footers that are parsed from the end can instead store each value back to
+//! front, first byte last, so that [`decode_backwards`] reads its groups in
+//! their natural order while walking towards the start of the buffer.
+
+use std::{
+    collections::VecDeque,
+    io::{self, Read, Seek, SeekFrom},
+};
+
+use num_traits::PrimInt;
+
+use crate::{max_encoded_len, Error, LEB128Codec, CONTINUATION};
+
+const CHUNK: usize = 256;
+
+pub struct TailReader<R> {
+    reader: R,
+    /// Offset of the first byte held in `buffer`.
+    buffer_start: u64,
+    /// Bytes preceding the unread end of the stream, grown at the front as
+    /// the scan moves back.
+    buffer: VecDeque<u8>,
+}
+
+impl<R: Read + Seek> TailReader<R> {
+    pub fn new(mut reader: R) -> Result<Self, io::Error> {
+        let end = reader.seek(SeekFrom::End(0))?;
+        Ok(TailReader {
+            reader,
+            buffer_start: end,
+            buffer: VecDeque::new(),
+        })
+    }
+
+    /// Offset one past the last value not yet returned.
+    pub fn position(&self) -> u64 {
+        self.buffer_start + self.buffer.len() as u64
+    }
+
+    fn fill_before(&mut self) -> Result<bool, io::Error> {
+        if self.buffer_start == 0 {
+            return Ok(false);
+        }
+        let len = self.buffer_start.min(CHUNK as u64) as usize;
+        self.buffer_start -= len as u64;
+        let mut chunk = vec![0; len];
+        self.reader.seek(SeekFrom::Start(self.buffer_start))?;
+        self.reader.read_exact(&mut chunk)?;
+        for &byte in chunk.iter().rev() {
+            self.buffer.push_front(byte);
+        }
+        Ok(true)
+    }
+
+    /// Decodes the value ending at the current position and moves before it,
+    /// or returns `None` at the start of the stream. A value longer than `N`
+    /// can encode fails with [`Error::Overflow`] without scanning further.
+    pub fn read_prev<N: LEB128Codec + PrimInt>(&mut self) -> Result<Option<N>, io::Error> {
+        if self.buffer.is_empty() && !self.fill_before()? {
+            return Ok(None);
+        }
+        if self.buffer[self.buffer.len() - 1] & CONTINUATION != 0 {
+            return Err(io::Error::from(io::ErrorKind::InvalidData));
+        }
+        let max_len = max_encoded_len::<N>();
+        let mut len = 1;
+        loop {
+            if len == self.buffer.len() && !self.fill_before()? {
+                break;
+            }
+            if self.buffer[self.buffer.len() - 1 - len] & CONTINUATION == 0 {
+                break;
+            }
+            len += 1;
+            if len > max_len {
+                return Err(Error::Overflow.into());
+            }
+        }
+        let start = self.buffer.len() - len;
+        let value: Vec<u8> = self.buffer.range(start..).copied().collect();
+        let mut readable = &value[..];
+        let num = N::leb128_decode(&mut readable)?;
+        if !readable.is_empty() {
+            return Err(io::Error::from(io::ErrorKind::InvalidData));
+        }
+        self.buffer.truncate(start);
+        Ok(Some(num))
+    }
+
+    /// Returns up to the last `n` values before the current position, in
+    /// stream order.
+    pub fn last<N: LEB128Codec + PrimInt>(&mut self, n: usize) -> Result<Vec<N>, io::Error> {
+        let mut values = Vec::with_capacity(n.min(CHUNK));
+        while values.len() < n {
+            match self.read_prev()? {
+                Some(num) => values.push(num),
+                None => break,
+            }
+        }
+        values.reverse();
+        Ok(values)
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+/// Appends `num` with its encoding reversed, for [`decode_backwards`].
+pub fn encode_backwards<N: LEB128Codec>(num: N, buf: &mut Vec<u8>) -> Result<usize, io::Error> {
+    let start = buf.len();
+    let len = num.leb128_encode(buf)?;
+    buf[start..].reverse();
+    Ok(len)
+}
+
+/// Decodes a value stored back to front whose first byte is at
+/// `end_offset - 1`, returning it and the offset of its lowest byte, which
+/// is where the next value back ends.
+pub fn decode_backwards<N: LEB128Codec>(
+    buf: &[u8],
+    end_offset: usize,
+) -> Result<(N, usize), io::Error> {
+    let mut reversed = Backwards {
+        bytes: buf.get(..end_offset).ok_or(io::ErrorKind::InvalidInput)?,
+    };
+    let num = N::leb128_decode(&mut reversed)?;
+    Ok((num, reversed.bytes.len()))
+}
+
+struct Backwards<'a> {
+    bytes: &'a [u8],
+}
+
+impl io::Read for Backwards<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.bytes.len());
+        let (rest, taken) = self.bytes.split_at(self.bytes.len() - n);
+        for (out, byte) in buf.iter_mut().zip(taken.iter().rev()) {
+            *out = *byte;
+        }
+        self.bytes = rest;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Cursor};
+
+    use super::{decode_backwards, encode_backwards, TailReader};
+    use crate::{Error, LEB128Encode};
+
+    #[test]
+    fn reads_most_recent_values() {
+        let mut log = Vec::new();
+        for x in 0..2000u64 {
+            (x * x * 977).leb128_encode(&mut log).unwrap();
+        }
+        let mut tail = TailReader::new(Cursor::new(&log)).unwrap();
+        let expected: Vec<u64> = (1990..2000).map(|x| x * x * 977).collect();
+        assert_eq!(tail.last::<u64>(10).unwrap(), expected);
+        assert_eq!(tail.read_prev::<u64>().unwrap(), Some(1989 * 1989 * 977));
+        assert_eq!(tail.last::<u64>(5000).unwrap().len(), 1989);
+        assert_eq!(tail.position(), 0);
+        assert_eq!(tail.read_prev::<u64>().unwrap(), None);
+    }
+
+    #[test]
+    fn rejects_truncated_tail() {
+        let mut tail = TailReader::new(Cursor::new([0x05, 0x85])).unwrap();
+        assert!(tail.read_prev::<u32>().is_err());
+
+        let mut log = vec![0x80; 100_000];
+        log.push(0x00);
+        let mut tail = TailReader::new(Cursor::new(log)).unwrap();
+        let err = tail.read_prev::<u32>().unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+        assert_eq!(tail.buffer_start, 100_001 - 256);
+    }
+
+    #[test]
+    fn decodes_reversed_footer() {
+        let mut file = b"payload".to_vec();
+        encode_backwards(-1000i64, &mut file).unwrap();
+        encode_backwards(624485u32, &mut file).unwrap();
+        assert_eq!(&file[7..], [0x78, 0x98, 0x26, 0x8E, 0xE5]);
+
+        let (size, end) = decode_backwards::<u32>(&file, file.len()).unwrap();
+        assert_eq!((size, end), (624485, 9));
+        let (delta, end) = decode_backwards::<i64>(&file, end).unwrap();
+        assert_eq!((delta, end), (-1000, 7));
+
+        let err = decode_backwards::<u32>(&[0x80, 0x80], 2).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        let err = decode_backwards::<u32>(&file, file.len() + 1).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,161 @@
+//! Loop-free decoders for the hottest types. They accept exactly what the
+//! generic decoder accepts.
+
+use std::io;
+
+use crate::{Error, CONTINUATION};
+
+#[inline(always)]
+fn next_byte<R: io::Read>(reader: &mut R) -> Result<u8, io::Error> {
+    let mut buffer = [0u8; 1];
+    reader.read_exact(&mut buffer)?;
+    Ok(buffer[0])
+}
+
+pub(crate) fn decode_u32<R: io::Read>(reader: &mut R) -> Result<u32, io::Error> {
+    let b = next_byte(reader)?;
+    let mut num = (b & !CONTINUATION) as u32;
+    if b & CONTINUATION == 0 {
+        return Ok(num);
+    }
+    let b = next_byte(reader)?;
+    num |= ((b & !CONTINUATION) as u32) << 7;
+    if b & CONTINUATION == 0 {
+        return Ok(num);
+    }
+    let b = next_byte(reader)?;
+    num |= ((b & !CONTINUATION) as u32) << 14;
+    if b & CONTINUATION == 0 {
+        return Ok(num);
+    }
+    let b = next_byte(reader)?;
+    num |= ((b & !CONTINUATION) as u32) << 21;
+    if b & CONTINUATION == 0 {
+        return Ok(num);
+    }
+    let b = next_byte(reader)?;
+    // Only the low four bits fit; a set continuation bit means a sixth byte.
+    if b & !0x0F != 0 {
+        return Err(Error::Overflow.at(4, 28));
+    }
+    Ok(num | (b as u32) << 28)
+}
+
+pub(crate) fn decode_u64<R: io::Read>(reader: &mut R) -> Result<u64, io::Error> {
+    let mut num = 0u64;
+    macro_rules! step {
+        ($shift:literal) => {
+            let b = next_byte(reader)?;
+            num |= ((b & !CONTINUATION) as u64) << $shift;
+            if b & CONTINUATION == 0 {
+                return Ok(num);
+            }
+        };
+    }
+    step!(0);
+    step!(7);
+    step!(14);
+    step!(21);
+    step!(28);
+    step!(35);
+    step!(42);
+    step!(49);
+    step!(56);
+    let b = next_byte(reader)?;
+    if b & !0x01 != 0 {
+        return Err(Error::Overflow.at(9, 63));
+    }
+    Ok(num | (b as u64) << 63)
+}
+
+pub(crate) fn decode_i64<R: io::Read>(reader: &mut R) -> Result<i64, io::Error> {
+    let mut num = 0u64;
+    macro_rules! step {
+        ($shift:literal) => {
+            let b = next_byte(reader)?;
+            num |= ((b & !CONTINUATION) as u64) << $shift;
+            if b & CONTINUATION == 0 {
+                // Move bit 6 of the last group to the top and shift it back
+                // down arithmetically to extend the sign.
+                let unused = 64 - ($shift + 7);
+                return Ok(((num << unused) as i64) >> unused);
+            }
+        };
+    }
+    step!(0);
+    step!(7);
+    step!(14);
+    step!(21);
+    step!(28);
+    step!(35);
+    step!(42);
+    step!(49);
+    step!(56);
+    let b = next_byte(reader)?;
+    // The last byte holds bit 63; the rest of it must repeat that bit.
+    if b != 0x00 && b != 0x7F {
+        return Err(Error::Overflow.at(9, 63));
+    }
+    Ok((num | (b as u64) << 63) as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fmt::Debug, io};
+
+    use crate::{decode_prim, Error};
+
+    fn assert_same<N: PartialEq + Debug>(
+        input: &[u8],
+        fast: Result<N, io::Error>,
+        generic: Result<N, io::Error>,
+    ) {
+        match (fast, generic) {
+            (Ok(a), Ok(b)) => assert_eq!(a, b),
+            (Err(a), Err(b)) => {
+                assert_eq!(a.kind(), b.kind());
+                assert_eq!(Error::from_io_error(&a), Error::from_io_error(&b));
+            }
+            (a, b) => panic!("{input:?}: {a:?} != {b:?}"),
+        }
+    }
+
+    #[test]
+    fn u32_matches_generic() {
+        let inputs: [&[u8]; 8] = [
+            &[0x00],
+            &[0x7F],
+            &[0xE5, 0x8E, 0x26],
+            &[0xFF, 0xFF, 0xFF, 0xFF, 0x0F],
+            &[0x80, 0x80, 0x80, 0x80, 0x00],
+            &[0xFF, 0xFF, 0xFF, 0xFF, 0x1F],
+            &[0x80, 0x80, 0x80, 0x80, 0x80, 0x00],
+            &[0x80, 0x80],
+        ];
+        for input in inputs {
+            let fast = super::decode_u32(&mut &input[..]);
+            assert_same(input, fast, decode_prim::<u32, _>(&mut &input[..]));
+        }
+    }
+
+    #[test]
+    fn u64_and_i64_match_generic() {
+        let mut inputs: Vec<Vec<u8>> = vec![vec![0x80], vec![0x80; 11]];
+        for len in 1..=10 {
+            for last in [0x00, 0x01, 0x02, 0x3F, 0x40, 0x7E, 0x7F] {
+                let mut input = vec![0xFF; len - 1];
+                input.push(last);
+                inputs.push(input);
+                let mut input = vec![0x80; len - 1];
+                input.push(last);
+                inputs.push(input);
+            }
+        }
+        for input in &inputs {
+            let fast = super::decode_u64(&mut &input[..]);
+            assert_same(input, fast, decode_prim::<u64, _>(&mut &input[..]));
+            let fast = super::decode_i64(&mut &input[..]);
+            assert_same(input, fast, decode_prim::<i64, _>(&mut &input[..]));
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! A common interface over the byte-oriented `u64` varint encodings, so code
+//! can be generic over which one it uses.
+
+use std::io;
+
+use crate::{format::Uleb128, vlq::Vlq, LEB128Codec, LEB128Encode};
+
+pub trait Varint {
+    /// The longest encoding of any `u64`.
+    const MAX_LEN: usize;
+
+    fn encoded_len(value: u64) -> usize;
+
+    fn encode<W>(value: u64, writer: &mut W) -> Result<usize, io::Error>
+    where
+        W: Sized + io::Write;
+
+    fn decode<R>(reader: &mut R) -> Result<u64, io::Error>
+    where
+        R: Sized + io::Read;
+}
+
+/// Groups of seven significant bits needed for `value`, at least one.
+fn groups(value: u64) -> usize {
+    (64 - value.leading_zeros() as usize).div_ceil(7).max(1)
+}
+
+impl Varint for Uleb128 {
+    const MAX_LEN: usize = 10;
+
+    fn encoded_len(value: u64) -> usize {
+        groups(value)
+    }
+
+    fn encode<W>(value: u64, writer: &mut W) -> Result<usize, io::Error>
+    where
+        W: Sized + io::Write,
+    {
+        value.leb128_encode(writer)
+    }
+
+    fn decode<R>(reader: &mut R) -> Result<u64, io::Error>
+    where
+        R: Sized + io::Read,
+    {
+        u64::leb128_decode(reader)
+    }
+}
+
+impl Varint for Vlq {
+    const MAX_LEN: usize = 10;
+
+    fn encoded_len(value: u64) -> usize {
+        groups(value)
+    }
+
+    fn encode<W>(value: u64, writer: &mut W) -> Result<usize, io::Error>
+    where
+        W: Sized + io::Write,
+    {
+        crate::vlq::encode(value, writer)
+    }
+
+    fn decode<R>(reader: &mut R) -> Result<u64, io::Error>
+    where
+        R: Sized + io::Read,
+    {
+        crate::vlq::decode(reader)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Varint;
+    use crate::{format::Uleb128, prefix::PrefixVarint, vlq::Vlq};
+
+    fn round_trip<V: Varint>() {
+        for value in [0, 1, 127, 128, 16383, 16384, 1 << 56, u64::MAX] {
+            let mut bytes = Vec::new();
+            let len = V::encode(value, &mut bytes).unwrap();
+            assert_eq!(len, bytes.len());
+            assert_eq!(len, V::encoded_len(value));
+            assert!(len <= V::MAX_LEN);
+            assert_eq!(V::decode(&mut &bytes[..]).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn all_encodings_round_trip() {
+        round_trip::<Uleb128>();
+        round_trip::<Vlq>();
+        round_trip::<PrefixVarint>();
+    }
+}
@@ -0,0 +1,112 @@
+//! `Vec` and `String` helpers that need an allocator but not `std`, for
+//! embedded targets without `std::io`. Bytes are appended to a `Vec` and
+//! decoded from slices; decoders return the value and the bytes it took,
+//! failing with the [`ErrorCode`] the `io` based decoders would report.
+
+use alloc::{string::String, vec::Vec};
+
+use num_traits::PrimInt;
+
+use crate::{decode_step, encode_prim_to_vec, ErrorCode};
+
+/// Appends the encoding of `value` to `out`, returning its length.
+pub fn encode_into<N: PrimInt>(value: N, out: &mut Vec<u8>) -> usize {
+    encode_prim_to_vec(value, out)
+}
+
+pub fn encode_to_vec<N: PrimInt>(value: N) -> Vec<u8> {
+    let mut out = Vec::new();
+    encode_into(value, &mut out);
+    out
+}
+
+/// Decodes the value at the start of `bytes`, returning it and its length.
+#[cfg_attr(feature = "no_panic", no_panic::no_panic)]
+pub fn decode_slice<N: PrimInt>(bytes: &[u8]) -> Result<(N, usize), ErrorCode> {
+    let mut num = N::zero();
+    let mut shift = 0;
+    for (i, &byte) in bytes.iter().enumerate() {
+        if decode_step(&mut num, &mut shift, byte).map_err(|e| e.code())? {
+            return Ok((num, i + 1));
+        }
+    }
+    Err(ErrorCode::UnexpectedEof)
+}
+
+/// Appends `bytes` with a ULEB128 length prefix, returning the total length.
+pub fn encode_bytes(bytes: &[u8], out: &mut Vec<u8>) -> usize {
+    let prefix = encode_into(bytes.len(), out);
+    out.extend_from_slice(bytes);
+    prefix + bytes.len()
+}
+
+/// Decodes a length-prefixed byte string of at most `max_len` bytes,
+/// returning it and the length of the whole encoding.
+pub fn decode_bytes(bytes: &[u8], max_len: usize) -> Result<(Vec<u8>, usize), ErrorCode> {
+    let (len, prefix) = decode_slice::<u64>(bytes)?;
+    let len = usize::try_from(len).map_err(|_| ErrorCode::TooLong)?;
+    if len > max_len {
+        return Err(ErrorCode::TooLong);
+    }
+    let end = prefix.checked_add(len).ok_or(ErrorCode::TooLong)?;
+    let payload = bytes.get(prefix..end).ok_or(ErrorCode::UnexpectedEof)?;
+    Ok((payload.to_vec(), end))
+}
+
+pub fn encode_str(s: &str, out: &mut Vec<u8>) -> usize {
+    encode_bytes(s.as_bytes(), out)
+}
+
+/// Like [`decode_bytes`], additionally failing with
+/// [`ErrorCode::InvalidUtf8`] if the string is not valid UTF-8.
+pub fn decode_string(bytes: &[u8], max_len: usize) -> Result<(String, usize), ErrorCode> {
+    let (payload, len) = decode_bytes(bytes, max_len)?;
+    let s = String::from_utf8(payload).map_err(|_| ErrorCode::InvalidUtf8)?;
+    Ok((s, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn matches_io_codec() {
+        let cases: [(i64, &[u8]); 7] = [
+            (0, &[0x00]),
+            (-1, &[0x7F]),
+            (63, &[0x3F]),
+            (-64, &[0x40]),
+            (624485, &[0xE5, 0x8E, 0x26]),
+            (
+                i64::MIN,
+                &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x7F],
+            ),
+            (
+                i64::MAX,
+                &[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00],
+            ),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(encode_to_vec(value), expected);
+            assert_eq!(decode_slice::<i64>(expected), Ok((value, expected.len())));
+        }
+        assert_eq!(encode_to_vec(u128::MAX).len(), 19);
+        assert_eq!(decode_slice::<u8>(&[0x80, 0x02]), Err(ErrorCode::Overflow));
+        assert_eq!(decode_slice::<u8>(&[0x80]), Err(ErrorCode::UnexpectedEof));
+    }
+
+    #[test]
+    fn length_prefixed() {
+        let mut out = vec![0xAA];
+        assert_eq!(encode_str("hello", &mut out), 6);
+        assert_eq!(out, [0xAA, 5, b'h', b'e', b'l', b'l', b'o']);
+        assert_eq!(decode_string(&out[1..], 5), Ok(("hello".into(), 6)));
+        assert_eq!(decode_string(&out[1..], 4), Err(ErrorCode::TooLong));
+        assert_eq!(decode_bytes(&out[1..4], 5), Err(ErrorCode::UnexpectedEof));
+        let huge = [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01];
+        assert_eq!(decode_bytes(&huge, usize::MAX), Err(ErrorCode::TooLong));
+        assert_eq!(decode_string(&[1, 0xFF], 5), Err(ErrorCode::InvalidUtf8));
+    }
+}
@@ -0,0 +1,166 @@
+//! Conformance vectors: values paired with their encodings, for validating other
+//! implementations against this crate byte-for-byte.
+
+use std::{fmt::Write as _, io};
+
+use crate::{format, LEB128Codec};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Vector {
+    pub format: &'static str,
+    pub ty: &'static str,
+    /// The value in decimal.
+    pub value: String,
+    pub encoding: Vec<u8>,
+}
+
+impl Vector {
+    pub fn encoding_hex(&self) -> String {
+        self.encoding.iter().fold(String::new(), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+    }
+}
+
+/// Values around every 7-bit group boundary, plus the extremes of each width.
+fn candidates() -> Vec<i128> {
+    let mut values = Vec::new();
+    for bit in 0..127 {
+        let power = 1i128 << bit;
+        values.extend([power - 1, power, power + 1, -power, -power - 1, -power + 1]);
+    }
+    values.extend([i128::MAX, i128::MIN]);
+    values.sort_unstable();
+    values.dedup();
+    values
+}
+
+fn push_vectors<N>(vectors: &mut Vec<Vector>, format: &'static str, ty: &'static str, values: &[N])
+where
+    N: LEB128Codec + Copy + ToString,
+{
+    for &num in values {
+        let encoding = num.to_leb128_vec();
+        vectors.push(Vector {
+            format,
+            ty,
+            value: num.to_string(),
+            encoding,
+        });
+    }
+}
+
+macro_rules! type_vectors {
+    ($vectors:ident, $candidates:ident, $format:literal, $($ty:ty),*) => {
+        $(
+            let values: Vec<$ty> = $candidates
+                .iter()
+                .filter_map(|&x| <$ty>::try_from(x).ok())
+                .collect();
+            push_vectors(&mut $vectors, $format, stringify!($ty), &values);
+        )*
+    };
+}
+
+/// Generates vectors for every supported format and primitive width, followed
+/// by vectors for formats added to the [`format`] registry, typed as `i128`.
+pub fn generate() -> Vec<Vector> {
+    let candidates = candidates();
+    let mut vectors = Vec::new();
+    type_vectors!(vectors, candidates, "uleb128", u8, u16, u32, u64);
+    let mut u128s: Vec<u128> = candidates
+        .iter()
+        .filter_map(|&x| u128::try_from(x).ok())
+        .collect();
+    u128s.extend([u128::MAX - 1, u128::MAX]);
+    push_vectors(&mut vectors, "uleb128", "u128", &u128s);
+    type_vectors!(vectors, candidates, "sleb128", i8, i16, i32, i64, i128);
+    for plugin in format::formats() {
+        if ["uleb128", "sleb128"].contains(&plugin.name()) {
+            continue;
+        }
+        for &value in &candidates {
+            let mut encoding = Vec::new();
+            if plugin.encode(value, &mut encoding).is_ok() {
+                vectors.push(Vector {
+                    format: plugin.name(),
+                    ty: "i128",
+                    value: value.to_string(),
+                    encoding,
+                });
+            }
+        }
+    }
+    vectors
+}
+
+pub fn write_csv<W>(vectors: &[Vector], writer: &mut W) -> Result<(), io::Error>
+where
+    W: Sized + io::Write,
+{
+    writeln!(writer, "format,type,value,encoding")?;
+    for vector in vectors {
+        writeln!(
+            writer,
+            "{},{},{},{}",
+            vector.format,
+            vector.ty,
+            vector.value,
+            vector.encoding_hex()
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes a JSON array of objects. Values are strings so that consumers without
+/// 128-bit integers can still read them exactly.
+pub fn write_json<W>(vectors: &[Vector], writer: &mut W) -> Result<(), io::Error>
+where
+    W: Sized + io::Write,
+{
+    writeln!(writer, "[")?;
+    for (i, vector) in vectors.iter().enumerate() {
+        let separator = if i + 1 == vectors.len() { "" } else { "," };
+        writeln!(
+            writer,
+            "  {{\"format\":\"{}\",\"type\":\"{}\",\"value\":\"{}\",\"encoding\":\"{}\"}}{}",
+            vector.format,
+            vector.ty,
+            vector.value,
+            vector.encoding_hex(),
+            separator
+        )?;
+    }
+    writeln!(writer, "]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate, write_csv};
+
+    #[test]
+    fn contains_known_encodings() {
+        let vectors = generate();
+        let find = |format: &str, ty: &str, value: &str| {
+            vectors
+                .iter()
+                .find(|v| v.format == format && v.ty == ty && v.value == value)
+                .map(|v| v.encoding_hex())
+        };
+        assert_eq!(find("uleb128", "u8", "128").as_deref(), Some("8001"));
+        assert_eq!(find("uleb128", "u8", "256"), None);
+        assert_eq!(find("sleb128", "i8", "-128").as_deref(), Some("807f"));
+        assert_eq!(
+            find("uleb128", "u128", &u128::MAX.to_string()).as_deref(),
+            Some("ffffffffffffffffffffffffffffffffffff03")
+        );
+    }
+
+    #[test]
+    fn csv_layout() {
+        let mut out = Vec::new();
+        write_csv(&generate()[..1], &mut out).unwrap();
+        assert_eq!(out, b"format,type,value,encoding\nuleb128,u8,0,00\n");
+    }
+}
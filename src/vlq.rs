@@ -0,0 +1,188 @@
+//! Big-endian variable-length quantities, as used by MIDI and ASN.1 object
+//! identifier arcs: seven bits per byte, most significant group first, with
+//! the continuation bit set on every byte but the last.
+//!
+//! Also the offset encoding from git packfiles, where every continuation adds
+//! one to the accumulated value before shifting. That removes the redundant
+//! leading-zero encodings, so each value has exactly one representation.
+
+use std::io;
+
+use crate::{format::VarintFormat, Error, CONTINUATION};
+
+pub fn encode<W>(value: u64, writer: &mut W) -> Result<usize, io::Error>
+where
+    W: Sized + io::Write,
+{
+    let groups = (64 - value.leading_zeros() as usize).div_ceil(7).max(1);
+    let mut bytes = [0u8; 10];
+    for (i, byte) in bytes[..groups].iter_mut().enumerate() {
+        let shift = 7 * (groups - 1 - i);
+        *byte = (value >> shift) as u8 & !CONTINUATION | CONTINUATION;
+    }
+    bytes[groups - 1] &= !CONTINUATION;
+    writer.write_all(&bytes[..groups])?;
+    Ok(groups)
+}
+
+/// Fails with [`Error::Overflow`] once the value no longer fits a `u64`.
+/// Leading `0x80` bytes are accepted.
+pub fn decode<R>(reader: &mut R) -> Result<u64, io::Error>
+where
+    R: Sized + io::Read,
+{
+    let mut num: u64 = 0;
+    let mut buffer = [0u8; 1];
+    loop {
+        reader.read_exact(&mut buffer)?;
+        if num >> 57 != 0 {
+            return Err(Error::Overflow.into());
+        }
+        num = num << 7 | (buffer[0] & !CONTINUATION) as u64;
+        if buffer[0] & CONTINUATION == 0 {
+            break Ok(num);
+        }
+    }
+}
+
+/// Encodes a packfile `OFS_DELTA` offset, returning the bytes written.
+pub fn encode_offset<W>(value: u64, writer: &mut W) -> Result<usize, io::Error>
+where
+    W: Sized + io::Write,
+{
+    let mut bytes = [0u8; 10];
+    let mut pos = bytes.len() - 1;
+    let mut rest = value;
+    bytes[pos] = (rest & 0x7F) as u8;
+    rest >>= 7;
+    while rest != 0 {
+        rest -= 1;
+        pos -= 1;
+        bytes[pos] = CONTINUATION | (rest & 0x7F) as u8;
+        rest >>= 7;
+    }
+    writer.write_all(&bytes[pos..])?;
+    Ok(bytes.len() - pos)
+}
+
+/// Decodes a packfile `OFS_DELTA` offset, failing with [`Error::Overflow`]
+/// once it no longer fits a `u64`.
+pub fn decode_offset<R>(reader: &mut R) -> Result<u64, io::Error>
+where
+    R: Sized + io::Read,
+{
+    let mut buffer = [0u8; 1];
+    reader.read_exact(&mut buffer)?;
+    let mut num = (buffer[0] & !CONTINUATION) as u64;
+    while buffer[0] & CONTINUATION != 0 {
+        reader.read_exact(&mut buffer)?;
+        num += 1;
+        if num >> 57 != 0 {
+            return Err(Error::Overflow.into());
+        }
+        num = num << 7 | (buffer[0] & !CONTINUATION) as u64;
+    }
+    Ok(num)
+}
+
+/// [`VarintFormat`] for unsigned values up to `u64::MAX`.
+pub struct Vlq;
+
+impl VarintFormat for Vlq {
+    fn name(&self) -> &str {
+        "vlq"
+    }
+
+    fn encode(&self, value: i128, out: &mut Vec<u8>) -> Result<usize, io::Error> {
+        let value = u64::try_from(value).map_err(|_| io::ErrorKind::InvalidInput)?;
+        encode(value, out)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<(i128, usize), io::Error> {
+        let mut readable = bytes;
+        let num = decode(&mut readable)?;
+        Ok((num.into(), bytes.len() - readable.len()))
+    }
+}
+
+/// [`VarintFormat`] for git packfile offsets up to `u64::MAX`.
+pub struct GitOffset;
+
+impl VarintFormat for GitOffset {
+    fn name(&self) -> &str {
+        "git-offset"
+    }
+
+    fn encode(&self, value: i128, out: &mut Vec<u8>) -> Result<usize, io::Error> {
+        let value = u64::try_from(value).map_err(|_| io::ErrorKind::InvalidInput)?;
+        encode_offset(value, out)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<(i128, usize), io::Error> {
+        let mut readable = bytes;
+        let num = decode_offset(&mut readable)?;
+        Ok((num.into(), bytes.len() - readable.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, decode_offset, encode, encode_offset};
+    use crate::{format, Error};
+
+    #[test]
+    fn midi_examples() {
+        let cases: [(u64, &[u8]); 8] = [
+            (0, &[0x00]),
+            (0x40, &[0x40]),
+            (0x7F, &[0x7F]),
+            (0x80, &[0x81, 0x00]),
+            (0x2000, &[0xC0, 0x00]),
+            (0x3FFF, &[0xFF, 0x7F]),
+            (0x0FFF_FFFF, &[0xFF, 0xFF, 0xFF, 0x7F]),
+            (
+                u64::MAX,
+                &[0x81, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x7F],
+            ),
+        ];
+        for (value, bytes) in cases {
+            let mut out = Vec::new();
+            assert_eq!(encode(value, &mut out).unwrap(), bytes.len());
+            assert_eq!(out, bytes);
+            assert_eq!(decode(&mut &bytes[..]).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn rejects_overflow() {
+        let bytes = [0x82, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00];
+        let err = decode(&mut &bytes[..]).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+        let vlq = format::lookup("vlq").unwrap();
+        assert_eq!(vlq.decode(&[0x81, 0x00, 0x05]).unwrap(), (128, 2));
+    }
+
+    #[test]
+    fn git_offsets() {
+        let cases: [(u64, &[u8]); 6] = [
+            (0, &[0x00]),
+            (127, &[0x7F]),
+            (128, &[0x80, 0x00]),
+            (16511, &[0xFF, 0x7F]),
+            (16512, &[0x80, 0x80, 0x00]),
+            (
+                u64::MAX,
+                &[0x80, 0xFE, 0xFE, 0xFE, 0xFE, 0xFE, 0xFE, 0xFE, 0xFE, 0x7F],
+            ),
+        ];
+        for (value, bytes) in cases {
+            let mut out = Vec::new();
+            assert_eq!(encode_offset(value, &mut out).unwrap(), bytes.len());
+            assert_eq!(out, bytes);
+            assert_eq!(decode_offset(&mut &bytes[..]).unwrap(), value);
+        }
+        let too_big = [0x80, 0xFE, 0xFE, 0xFE, 0xFE, 0xFE, 0xFE, 0xFE, 0xFF, 0x00];
+        let err = decode_offset(&mut &too_big[..]).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+    }
+}
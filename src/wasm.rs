@@ -0,0 +1,153 @@
+//! Primitives of the WebAssembly binary format, enough to walk a module's
+//! sections without a full parser.
+//!
+//! ```
+//! use std::io::{self, Read};
+//!
+//! use lebase::wasm::{read_name, read_preamble, read_section_header, CUSTOM_SECTION};
+//!
+//! let module = b"\0asm\x01\0\0\0\x00\x05\x04name\x01\x01\x00";
+//! let mut reader = &module[..];
+//! assert_eq!(read_preamble(&mut reader).unwrap(), 1);
+//! let header = read_section_header(&mut reader).unwrap().unwrap();
+//! assert_eq!(header.id, CUSTOM_SECTION);
+//! let mut body = (&mut reader).take(header.size.into());
+//! assert_eq!(read_name(&mut body, 64).unwrap(), "name");
+//! io::copy(&mut body, &mut io::sink()).unwrap();
+//! assert_eq!(read_section_header(&mut reader).unwrap().unwrap().id, 1);
+//! ```
+
+use std::io;
+
+use crate::{bytes, checked, Error, LEB128Codec};
+
+pub const MAGIC: [u8; 4] = *b"\0asm";
+pub const CUSTOM_SECTION: u8 = 0;
+
+/// A section id and the size of the section's contents in bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SectionHeader {
+    pub id: u8,
+    pub size: u32,
+}
+
+/// Reads the magic number and returns the format version, failing with
+/// `InvalidData` if the magic number is wrong.
+pub fn read_preamble<R>(reader: &mut R) -> Result<u32, io::Error>
+where
+    R: Sized + io::Read,
+{
+    let mut magic = [0u8; 4];
+    let mut version = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    reader.read_exact(&mut version)?;
+    if magic != MAGIC {
+        return Err(io::ErrorKind::InvalidData.into());
+    }
+    Ok(u32::from_le_bytes(version))
+}
+
+/// Reads a section header, or returns `None` if the reader is exhausted
+/// before it, at the end of the module. The id is a plain byte and the size
+/// a `varuint32`.
+pub fn read_section_header<R>(reader: &mut R) -> Result<Option<SectionHeader>, io::Error>
+where
+    R: Sized + io::Read,
+{
+    let mut id = [0u8; 1];
+    loop {
+        match reader.read(&mut id) {
+            Ok(0) => return Ok(None),
+            Ok(_) => break,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    let size = read_u32_immediate(reader)?;
+    Ok(Some(SectionHeader { id: id[0], size }))
+}
+
+/// Reads a `varuint32`: at most five bytes, with no bits set above bit 31.
+pub fn read_u32_immediate<R>(reader: &mut R) -> Result<u32, io::Error>
+where
+    R: Sized + io::Read,
+{
+    u32::leb128_decode(reader)
+}
+
+/// Reads a `varint33`, as used for block types: at most five bytes, holding
+/// a signed value that fits 33 bits.
+pub fn read_i33<R>(reader: &mut R) -> Result<i64, io::Error>
+where
+    R: Sized + io::Read,
+{
+    checked::decode_bits::<33, _, _>(reader)
+}
+
+/// Reads a name: a `varuint32` length and that many bytes of UTF-8, failing
+/// with [`Error::TooLong`] if the length exceeds `max_len`.
+pub fn read_name<R>(reader: &mut R, max_len: usize) -> Result<String, io::Error>
+where
+    R: Sized + io::Read,
+{
+    let len = read_u32_immediate(reader)?;
+    let len = usize::try_from(len).map_err(|_| Error::TooLong)?;
+    if len > max_len {
+        return Err(Error::TooLong.into());
+    }
+    let name = bytes::read_payload(reader, len)?;
+    String::from_utf8(name).map_err(|e| Error::InvalidUtf8(e.utf8_error()).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{read_i33, read_name, read_preamble, read_section_header, SectionHeader};
+    use crate::{Error, LEB128Encode};
+
+    #[test]
+    fn walks_sections() {
+        let mut reader = &b"\0asm\x01\0\0\0\x01\x80\x01"[..];
+        assert_eq!(read_preamble(&mut reader).unwrap(), 1);
+        assert_eq!(
+            read_section_header(&mut reader).unwrap(),
+            Some(SectionHeader { id: 1, size: 128 })
+        );
+        assert_eq!(read_section_header(&mut reader).unwrap(), None);
+        let err = read_section_header(&mut &[0x01][..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        let err = read_preamble(&mut &b"\0elf\x01\0\0\0"[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn block_types() {
+        for value in [0i64, -64, 0x40, (1 << 32) - 1, -(1 << 32), 63, -1] {
+            let bytes = value.to_leb128_vec();
+            assert_eq!(read_i33(&mut &bytes[..]).unwrap(), value);
+        }
+        for too_big in [1i64 << 32, -(1 << 32) - 1] {
+            let err = read_i33(&mut &too_big.to_leb128_vec()[..]).unwrap_err();
+            assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+        }
+        let err = read_i33(&mut &[0x80, 0x80, 0x80, 0x80, 0x80, 0x00][..]).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::TooManyBytes));
+    }
+
+    #[test]
+    fn names() {
+        let mut reader = &b"\x03abc\x02\xff\xfe"[..];
+        assert_eq!(read_name(&mut reader, 3).unwrap(), "abc");
+        let err = read_name(&mut reader, 3).unwrap_err();
+        assert!(matches!(
+            Error::from_io_error(&err),
+            Some(Error::InvalidUtf8(_))
+        ));
+        let err = read_name(&mut &b"\x04abcd"[..], 3).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::TooLong));
+        // Longer than a varuint32 may be, though the value is small.
+        let err = read_name(&mut &b"\x81\x80\x80\x80\x80\x00a"[..], 64).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+    }
+}
@@ -0,0 +1,149 @@
+//! Fixed-width integers wider than `u128` from the `ethnum` and `ruint` crates.
+
+use std::io;
+
+use crate::{
+    groups::{fit_le, read_groups, write_groups},
+    Error, LEB128Codec, LEB128Encode,
+};
+
+#[cfg(feature = "ethnum")]
+impl LEB128Codec for ethnum::U256 {
+    fn leb128_decode<R>(reader: &mut R) -> Result<Self, io::Error>
+    where
+        R: Sized + io::Read,
+        Self: Sized,
+    {
+        let bytes = read_groups(reader, false, 256usize.div_ceil(7))?;
+        let bytes = fit_le(bytes, 32, false).ok_or(Error::Overflow)?;
+        let bytes = bytes.try_into().map_err(|_| Error::Overflow)?;
+        Ok(Self::from_le_bytes(bytes))
+    }
+}
+
+#[cfg(feature = "ethnum")]
+impl LEB128Encode for ethnum::U256 {
+    fn leb128_encode<W>(self, writer: &mut W) -> Result<usize, io::Error>
+    where
+        W: Sized + io::Write,
+        Self: Sized,
+    {
+        let groups = (256 - self.leading_zeros() as usize).div_ceil(7).max(1);
+        write_groups(&self.to_le_bytes(), groups, 0, writer)
+    }
+}
+
+#[cfg(feature = "ethnum")]
+impl LEB128Codec for ethnum::I256 {
+    fn leb128_decode<R>(reader: &mut R) -> Result<Self, io::Error>
+    where
+        R: Sized + io::Read,
+        Self: Sized,
+    {
+        let bytes = read_groups(reader, true, 256usize.div_ceil(7))?;
+        let bytes = fit_le(bytes, 32, true).ok_or(Error::Overflow)?;
+        let bytes = bytes.try_into().map_err(|_| Error::Overflow)?;
+        Ok(Self::from_le_bytes(bytes))
+    }
+}
+
+#[cfg(feature = "ethnum")]
+impl LEB128Encode for ethnum::I256 {
+    fn leb128_encode<W>(self, writer: &mut W) -> Result<usize, io::Error>
+    where
+        W: Sized + io::Write,
+        Self: Sized,
+    {
+        let negative = self < 0;
+        let redundant = if negative {
+            self.leading_ones()
+        } else {
+            self.leading_zeros()
+        };
+        let groups = (256 - redundant as usize + 1).div_ceil(7);
+        let fill = if negative { 0xFF } else { 0 };
+        write_groups(&self.to_le_bytes(), groups, fill, writer)
+    }
+}
+
+#[cfg(feature = "ruint")]
+impl<const BITS: usize, const LIMBS: usize> LEB128Codec for ruint::Uint<BITS, LIMBS> {
+    fn leb128_decode<R>(reader: &mut R) -> Result<Self, io::Error>
+    where
+        R: Sized + io::Read,
+        Self: Sized,
+    {
+        let bytes = read_groups(reader, false, BITS.div_ceil(7).max(1))?;
+        let bytes = fit_le(bytes, BITS.div_ceil(8), false).ok_or(Error::Overflow)?;
+        Self::try_from_le_slice(&bytes).ok_or(Error::Overflow.into())
+    }
+}
+
+#[cfg(feature = "ruint")]
+impl<const BITS: usize, const LIMBS: usize> LEB128Encode for ruint::Uint<BITS, LIMBS> {
+    fn leb128_encode<W>(self, writer: &mut W) -> Result<usize, io::Error>
+    where
+        W: Sized + io::Write,
+        Self: Sized,
+    {
+        let groups = self.bit_len().div_ceil(7).max(1);
+        write_groups(&self.to_le_bytes_vec(), groups, 0, writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+
+    use crate::{Error, LEB128Codec};
+
+    fn encode<N: LEB128Codec>(num: N) -> Vec<u8> {
+        let mut buf = Vec::new();
+        num.leb128_encode(&mut buf).unwrap();
+        buf
+    }
+
+    fn assert_trip<N: LEB128Codec + Copy + PartialEq + Debug>(num: N) {
+        let bytes = encode(num);
+        assert_eq!(N::leb128_decode(&mut &bytes[..]).unwrap(), num);
+    }
+
+    #[cfg(feature = "ethnum")]
+    #[test]
+    fn ethnum_trips() {
+        use ethnum::{I256, U256};
+        assert_eq!(encode(U256::new(0x29442)), encode(0x29442u32));
+        assert_eq!(encode(I256::new(-0x8652)), encode(-0x8652i32));
+        for x in [U256::ZERO, U256::ONE, U256::new(u128::MAX), U256::MAX] {
+            assert_trip(x);
+        }
+        for x in [
+            I256::ZERO,
+            I256::MINUS_ONE,
+            I256::new(i128::MIN),
+            I256::MIN,
+            I256::MAX,
+        ] {
+            assert_trip(x);
+        }
+        let mut max = vec![0xFF; 36];
+        max.push(0x0F);
+        assert_eq!(U256::leb128_decode(&mut &max[..]).unwrap(), U256::MAX);
+        *max.last_mut().unwrap() = 0x1F;
+        let err = U256::leb128_decode(&mut &max[..]).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+    }
+
+    #[cfg(feature = "ruint")]
+    #[test]
+    fn ruint_trips() {
+        use ruint::{aliases::U256, Uint};
+        assert_eq!(encode(U256::from(0x29442u32)), encode(0x29442u32));
+        for x in [U256::ZERO, U256::from(u128::MAX), U256::MAX] {
+            assert_trip(x);
+        }
+        assert_trip(Uint::<33, 1>::MAX);
+        let err = Uint::<33, 1>::leb128_decode(&mut &encode(u64::MAX)[..]).unwrap_err();
+        assert_eq!(Error::from_io_error(&err), Some(Error::Overflow));
+    }
+}
@@ -0,0 +1,111 @@
+//! A buffered writer with typed encode methods.
+
+use std::io;
+
+use crate::{bytes, LEB128Encode};
+
+const DEFAULT_CAPACITY: usize = 512;
+
+/// Wraps a writer in a small buffer and counts the bytes written through it.
+/// Buffered bytes reach the inner writer on `flush`, `into_inner`, or when
+/// the buffer fills.
+pub struct Leb128Writer<W: io::Write> {
+    inner: io::BufWriter<W>,
+    written: u64,
+}
+
+macro_rules! typed_writes {
+    ($($name:ident => $ty:ty),*) => {
+        $(
+            pub fn $name(&mut self, value: $ty) -> Result<usize, io::Error> {
+                self.write_value(value)
+            }
+        )*
+    };
+}
+
+impl<W: io::Write> Leb128Writer<W> {
+    pub fn new(inner: W) -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY, inner)
+    }
+
+    pub fn with_capacity(capacity: usize, inner: W) -> Self {
+        Leb128Writer {
+            inner: io::BufWriter::with_capacity(capacity, inner),
+            written: 0,
+        }
+    }
+
+    /// Bytes written so far, including any still buffered.
+    pub fn bytes_written(&self) -> u64 {
+        self.written
+    }
+
+    pub fn write_value<N: LEB128Encode>(&mut self, value: N) -> Result<usize, io::Error> {
+        value.leb128_encode(self)
+    }
+
+    typed_writes!(
+        write_u8 => u8, write_u16 => u16, write_u32 => u32, write_u64 => u64, write_u128 => u128,
+        write_i8 => i8, write_i16 => i16, write_i32 => i32, write_i64 => i64, write_i128 => i128,
+        write_usize => usize, write_isize => isize
+    );
+
+    /// Writes a length-prefixed byte string.
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> Result<usize, io::Error> {
+        bytes::encode_bytes(bytes, self)
+    }
+
+    /// Writes a length-prefixed UTF-8 string.
+    pub fn write_str(&mut self, s: &str) -> Result<usize, io::Error> {
+        bytes::encode_str(s, self)
+    }
+
+    pub fn get_ref(&self) -> &W {
+        self.inner.get_ref()
+    }
+
+    /// Flushes the buffer and returns the wrapped writer.
+    pub fn into_inner(self) -> Result<W, io::Error> {
+        self.inner.into_inner().map_err(|e| e.into_error())
+    }
+}
+
+impl<W: io::Write> io::Write for Leb128Writer<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::Leb128Writer;
+    use crate::reader::Leb128Reader;
+
+    #[test]
+    fn typed_writes_round_trip() {
+        let mut writer = Leb128Writer::with_capacity(4, Vec::new());
+        assert_eq!(writer.write_u32(300).unwrap(), 2);
+        assert_eq!(writer.write_i64(-2).unwrap(), 1);
+        assert_eq!(writer.write_str("hello").unwrap(), 6);
+        assert_eq!(writer.write_u128(u128::MAX).unwrap(), 19);
+        assert_eq!(writer.bytes_written(), 28);
+        writer.flush().unwrap();
+        assert_eq!(writer.get_ref().len(), 28);
+        let data = writer.into_inner().unwrap();
+
+        let mut reader = Leb128Reader::new(&data[..]);
+        assert_eq!(reader.read_u32().unwrap(), 300);
+        assert_eq!(reader.read_i64().unwrap(), -2);
+        assert_eq!(reader.read_string(16).unwrap(), "hello");
+        assert_eq!(reader.read_u128().unwrap(), u128::MAX);
+    }
+}